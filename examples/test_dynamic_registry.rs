@@ -61,6 +61,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 pending_blocks: false,
                 pending_hashes_only: false,
                 deployments: false,
+                trace_creations: false,
+                hot_reload: false,
             }),
             common: evm_track::cli::CommonFlags {
                 config: None,
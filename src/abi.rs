@@ -1,30 +1,68 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
-use alloy_json_abi::{Event, Function};
-use alloy_primitives::{Address, B256, U256};
+use alloy_dyn_abi::{DynSolType, DynSolValue, Specifier};
+use alloy_json_abi::{Error as AbiError, Event, EventParam, Function, Param};
+use alloy_primitives::{Address, B256, I256, U256};
 use anyhow::{Context, Result};
 use once_cell::sync::OnceCell;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct EventSigEntry {
     pub abi: Event,
     pub name: String,
     pub sig: String,
+    /// 来自签名数据库的纯文本签名不含 indexed 信息，解码时按 topics 数量假定前 N 个参数为 indexed
+    #[serde(default)]
+    pub unknown_indexed: bool,
+    /// 同一 topic0 下的其他候选签名
+    #[serde(default)]
+    pub collisions: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct FuncSigEntry {
     pub abi: Function,
     pub name: String,
     pub sig: String,
+    /// 同一 selector 下的其他候选签名
+    #[serde(default)]
+    pub collisions: Vec<String>,
 }
 
-pub type EventSigMap = HashMap<String, EventSigEntry>; // topic0 hex -> entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct ErrorSigEntry {
+    pub abi: AbiError,
+    pub name: String,
+    pub sig: String,
+    #[serde(default)]
+    pub collisions: Vec<String>,
+}
+
+/// topic0 hex -> 同一 topic0 下的所有 ABI 变体（如 ERC-20 / ERC-721 Transfer）
+pub type EventSigMap = HashMap<String, Vec<EventSigEntry>>;
 pub type FuncSigMap = HashMap<String, FuncSigEntry>; // selector hex -> entry
+pub type ErrorSigMap = HashMap<String, ErrorSigEntry>; // selector hex -> entry
+
+/// 匿名事件没有 topic0，统一放在该键下，按 topics 数量与 data 匹配
+pub const ANONYMOUS_EVENTS_KEY: &str = "anonymous";
+
+/// event_sigs.json 中每个键可以是单个条目，也可以是变体数组
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EventSigEntries {
+    One(EventSigEntry),
+    Many(Vec<EventSigEntry>),
+}
 
 static EVENT_SIGS_PATH: OnceCell<String> = OnceCell::new();
 static FUNC_SIGS_PATH: OnceCell<String> = OnceCell::new();
+static ERROR_SIGS_PATH: OnceCell<String> = OnceCell::new();
 
 pub fn set_event_sigs_path(path: String) {
     let _ = EVENT_SIGS_PATH.set(path);
@@ -34,25 +72,121 @@ pub fn set_func_sigs_path(path: String) {
     let _ = FUNC_SIGS_PATH.set(path);
 }
 
-pub fn load_event_sigs_default() -> Result<EventSigMap> {
-    let p = EVENT_SIGS_PATH
+pub fn set_error_sigs_path(path: String) {
+    let _ = ERROR_SIGS_PATH.set(path);
+}
+
+pub fn event_sigs_path() -> String {
+    EVENT_SIGS_PATH
         .get()
         .cloned()
-        .unwrap_or_else(|| "./data/event_sigs.json".to_string());
-    load_event_sigs(p)
+        .unwrap_or_else(|| "./data/event_sigs.json".to_string())
 }
 
-pub fn load_func_sigs_default() -> Result<FuncSigMap> {
-    let p = FUNC_SIGS_PATH
+pub fn func_sigs_path() -> String {
+    FUNC_SIGS_PATH
+        .get()
+        .cloned()
+        .unwrap_or_else(|| "./data/func_sigs.json".to_string())
+}
+
+pub fn error_sigs_path() -> String {
+    ERROR_SIGS_PATH
         .get()
         .cloned()
-        .unwrap_or_else(|| "./data/func_sigs.json".to_string());
-    load_func_sigs(p)
+        .unwrap_or_else(|| "./data/error_sigs.json".to_string())
+}
+
+pub fn load_event_sigs_default() -> Result<EventSigMap> {
+    load_event_sigs(event_sigs_path())
+}
+
+pub fn load_func_sigs_default() -> Result<FuncSigMap> {
+    load_func_sigs(func_sigs_path())
+}
+
+pub fn load_error_sigs_default() -> Result<ErrorSigMap> {
+    load_error_sigs(error_sigs_path())
+}
+
+/// 内存中的签名表快照。运行时通过 [`sigs`] 取得当前快照，热重载时整体替换，
+/// 已取得快照的调用方不受影响。
+#[derive(Debug, Default)]
+pub struct SigSet {
+    pub events: EventSigMap,
+    pub funcs: FuncSigMap,
+    pub errors: ErrorSigMap,
+}
+
+static SIGS: RwLock<Option<Arc<SigSet>>> = RwLock::new(None);
+
+/// 当前签名表快照；首次调用时从默认路径加载（文件缺失或无法解析时为空表）
+pub fn sigs() -> Arc<SigSet> {
+    if let Some(s) = SIGS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return s.clone();
+    }
+    let mut guard = SIGS.write().unwrap_or_else(|e| e.into_inner());
+    guard
+        .get_or_insert_with(|| {
+            Arc::new(SigSet {
+                events: load_event_sigs_default().unwrap_or_default(),
+                funcs: load_func_sigs_default().unwrap_or_default(),
+                errors: load_error_sigs_default().unwrap_or_default(),
+            })
+        })
+        .clone()
+}
+
+/// 从磁盘重新读取签名文件并原子替换快照。
+/// 某个文件读取/解析失败时保留该表的旧内容（避免写入一半的文件清空签名），并返回错误信息。
+pub fn reload_sigs() -> (Arc<SigSet>, Vec<String>) {
+    let old = sigs();
+    let mut errs = Vec::new();
+    let events = load_event_sigs_default().unwrap_or_else(|e| {
+        errs.push(format!("{}: {:#}", event_sigs_path(), e));
+        old.events.clone()
+    });
+    let funcs = load_func_sigs_default().unwrap_or_else(|e| {
+        errs.push(format!("{}: {:#}", func_sigs_path(), e));
+        old.funcs.clone()
+    });
+    // error_sigs.json 是可选文件，不存在时视为空表
+    let errors = match load_error_sigs_default() {
+        Ok(m) => m,
+        Err(_) if !Path::new(&error_sigs_path()).exists() => ErrorSigMap::new(),
+        Err(e) => {
+            errs.push(format!("{}: {:#}", error_sigs_path(), e));
+            old.errors.clone()
+        }
+    };
+    let new = Arc::new(SigSet {
+        events,
+        funcs,
+        errors,
+    });
+    *SIGS.write().unwrap_or_else(|e| e.into_inner()) = Some(new.clone());
+    (new, errs)
 }
 
 pub fn load_event_sigs<P: AsRef<Path>>(path: P) -> Result<EventSigMap> {
     let s = fs::read_to_string(path).context("reading event_sigs.json")?;
-    let m: EventSigMap = serde_json::from_str(&s).context("parsing event_sigs.json")?;
+    let raw: HashMap<String, EventSigEntries> =
+        serde_json::from_str(&s).context("parsing event_sigs.json")?;
+    let mut m = EventSigMap::new();
+    for (key, entries) in raw {
+        let entries = match entries {
+            EventSigEntries::One(e) => vec![e],
+            EventSigEntries::Many(v) => v,
+        };
+        for e in entries {
+            let key = if e.abi.anonymous {
+                ANONYMOUS_EVENTS_KEY.to_string()
+            } else {
+                key.to_lowercase()
+            };
+            m.entry(key).or_default().push(e);
+        }
+    }
     Ok(m)
 }
 
@@ -62,210 +196,777 @@ pub fn load_func_sigs<P: AsRef<Path>>(path: P) -> Result<FuncSigMap> {
     Ok(m)
 }
 
-#[derive(Debug, Clone)]
+pub fn load_error_sigs<P: AsRef<Path>>(path: P) -> Result<ErrorSigMap> {
+    let s = fs::read_to_string(path).context("reading error_sigs.json")?;
+    let m: ErrorSigMap = serde_json::from_str(&s).context("parsing error_sigs.json")?;
+    Ok(m)
+}
+
+/// 解码后的值。不携带 Solidity 类型（如 `Uint` 不区分 uint8 与 uint256），类型只保存在
+/// [`DecodedField::ty`] 中；需要类型信息的输出应序列化 [`DecodedField`]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DecodedValue {
     Address(Address),
     Uint(U256),
-    Int(U256),
+    Int(I256),
     Bool(bool),
     Bytes32([u8; 32]),
+    /// bytes1..bytes31（以及 24 字节的 function 类型）
+    FixedBytes(Vec<u8>),
     Bytes(Vec<u8>),
     String(String),
+    /// T[] 与 T[N]
     Array(Vec<DecodedValue>),
+    /// struct / tuple，保留 ABI 中的字段名（可能为空）
+    Tuple(Vec<(String, DecodedValue)>),
     Unsupported(&'static str),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DecodedField {
     pub name: String,
+    /// 规范化的 Solidity 类型（如 `uint256`、`(address,uint8[2])[]`）；
+    /// indexed 的引用类型在 topic 中只有 keccak256 哈希，记为 `bytes32`
+    pub ty: String,
     pub value: DecodedValue,
     pub indexed: bool,
 }
 
-pub fn decode_indexed(topic: &B256, typ: &str) -> DecodedValue {
-    let bytes = topic.0;
-    match typ {
-        "address" => {
-            let mut a = [0u8; 20];
-            a.copy_from_slice(&bytes[12..]);
-            DecodedValue::Address(Address::from(a))
+impl DecodedField {
+    fn from_param(p: &Param, value: DecodedValue) -> Self {
+        DecodedField {
+            name: p.name.clone(),
+            ty: p.selector_type().into_owned(),
+            value,
+            indexed: false,
         }
-        "bool" => DecodedValue::Bool(bytes[31] != 0),
-        t if t.starts_with("uint") => DecodedValue::Uint(U256::from_be_bytes(bytes)),
-        t if t.starts_with("int") => DecodedValue::Int(U256::from_be_bytes(bytes)),
-        "bytes32" => DecodedValue::Bytes32(bytes),
-        _ => DecodedValue::Unsupported("indexed dynamic or unsupported type"),
+    }
+
+    /// 类型化 JSON：`{"name","type","value","indexed"}`，value 的表示见 [`DecodedValue::to_json`]
+    pub fn to_json(&self) -> serde_json::Value {
+        let ty = DynSolType::parse(&self.ty).ok();
+        serde_json::json!({
+            "name": self.name,
+            "type": self.ty,
+            "value": self.value.to_json(ty.as_ref()),
+            "indexed": self.indexed,
+        })
     }
 }
 
-pub fn decode_static_word(word: &[u8], typ: &str) -> DecodedValue {
-    match typ {
-        "address" => {
-            let mut a = [0u8; 20];
-            a.copy_from_slice(&word[12..]);
-            DecodedValue::Address(Address::from(a))
+impl Serialize for DecodedField {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl DecodedValue {
+    /// 将 alloy 的动态值转换为 DecodedValue；`components` 为 tuple 的字段定义（用于字段名）
+    pub fn from_dyn(v: DynSolValue, components: &[Param]) -> Self {
+        match v {
+            DynSolValue::Address(a) => DecodedValue::Address(a),
+            DynSolValue::Uint(u, _) => DecodedValue::Uint(u),
+            DynSolValue::Int(i, _) => DecodedValue::Int(i),
+            DynSolValue::Bool(b) => DecodedValue::Bool(b),
+            DynSolValue::FixedBytes(w, 32) => DecodedValue::Bytes32(w.0),
+            DynSolValue::FixedBytes(w, size) => DecodedValue::FixedBytes(w[..size].to_vec()),
+            DynSolValue::Function(f) => DecodedValue::FixedBytes(f.0.to_vec()),
+            DynSolValue::Bytes(b) => DecodedValue::Bytes(b),
+            DynSolValue::String(s) => DecodedValue::String(s),
+            DynSolValue::Array(items) | DynSolValue::FixedArray(items) => DecodedValue::Array(
+                items
+                    .into_iter()
+                    .map(|x| DecodedValue::from_dyn(x, components))
+                    .collect(),
+            ),
+            DynSolValue::Tuple(items) => DecodedValue::Tuple(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, x)| match components.get(i) {
+                        Some(p) => (p.name.clone(), DecodedValue::from_dyn(x, &p.components)),
+                        None => (String::new(), DecodedValue::from_dyn(x, &[])),
+                    })
+                    .collect(),
+            ),
+            #[allow(unreachable_patterns)]
+            _ => DecodedValue::Unsupported("unknown dynamic value"),
         }
-        "bool" => DecodedValue::Bool(word[31] != 0),
-        t if t.starts_with("uint") => {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(word);
-            DecodedValue::Uint(U256::from_be_bytes(arr))
+    }
+}
+
+impl DecodedValue {
+    /// 机器可读的 JSON 表示：
+    /// - 整数为十进制字符串（避免超出 JSON 数字精度），地址为 EIP-55 校验和格式；
+    /// - bytes / bytesN 为 0x 十六进制，bool / string 原样；
+    /// - 数组为元素值的数组，tuple 为 `{"name","type","value"}` 对象数组（`ty` 未知时省略 type）；
+    /// - 无法解码的值为 null。
+    pub fn to_json(&self, ty: Option<&DynSolType>) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            DecodedValue::Address(a) => Value::String(a.to_checksum(None)),
+            DecodedValue::Uint(u) => Value::String(u.to_string()),
+            DecodedValue::Int(i) => Value::String(i.to_string()),
+            DecodedValue::Bool(b) => Value::Bool(*b),
+            DecodedValue::Bytes32(b) => Value::String(format!("0x{}", hex::encode(b))),
+            DecodedValue::FixedBytes(b) | DecodedValue::Bytes(b) => {
+                Value::String(format!("0x{}", hex::encode(b)))
+            }
+            DecodedValue::String(s) => Value::String(s.clone()),
+            DecodedValue::Array(items) => {
+                let inner = match ty {
+                    Some(DynSolType::Array(t)) | Some(DynSolType::FixedArray(t, _)) => Some(&**t),
+                    _ => None,
+                };
+                Value::Array(items.iter().map(|v| v.to_json(inner)).collect())
+            }
+            DecodedValue::Tuple(items) => {
+                let types = match ty {
+                    Some(DynSolType::Tuple(ts)) => Some(ts),
+                    _ => None,
+                };
+                Value::Array(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (name, v))| {
+                            let t = types.and_then(|ts| ts.get(i));
+                            let mut obj = serde_json::Map::new();
+                            obj.insert("name".into(), Value::String(name.clone()));
+                            if let Some(t) = t {
+                                obj.insert(
+                                    "type".into(),
+                                    Value::String(t.sol_type_name().into_owned()),
+                                );
+                            }
+                            obj.insert("value".into(), v.to_json(t));
+                            Value::Object(obj)
+                        })
+                        .collect(),
+                )
+            }
+            DecodedValue::Unsupported(_) => Value::Null,
         }
-        t if t.starts_with("int") => {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(word);
-            DecodedValue::Int(U256::from_be_bytes(arr))
+    }
+}
+
+/// 无类型的值表示（即 `to_json(None)`）：tuple 组件不带 type。类型化输出见 [`DecodedField`] 的序列化
+impl Serialize for DecodedValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.to_json(None).serialize(serializer)
+    }
+}
+
+/// 与 JSON 输出一致的单行文本（字符串不加引号），供终端日志使用
+impl fmt::Display for DecodedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_json(None) {
+            serde_json::Value::String(s) => f.write_str(&s),
+            v => write!(f, "{}", v),
         }
-        "bytes32" => DecodedValue::Bytes32(word.try_into().unwrap()),
-        _ => DecodedValue::Unsupported("dynamic or unsupported type"),
     }
 }
 
-fn is_dynamic_type(typ: &str) -> bool {
-    matches!(typ, "string" | "bytes") || typ.ends_with("[]")
+/// Solidity 中 indexed 的引用类型（string/bytes/数组/struct）在 topic 中只保存 keccak256 哈希
+fn is_hashed_when_indexed(ty: &DynSolType) -> bool {
+    matches!(
+        ty,
+        DynSolType::String
+            | DynSolType::Bytes
+            | DynSolType::Array(_)
+            | DynSolType::FixedArray(..)
+            | DynSolType::Tuple(_)
+    )
 }
 
-fn decode_dynamic<'a>(data: &'a [u8], offset: usize, elem_type: &str) -> Option<DecodedValue> {
-    if offset + 32 > data.len() {
-        return None;
+fn decode_topic(topic: &B256, ty: &DynSolType, components: &[Param]) -> DecodedValue {
+    if is_hashed_when_indexed(ty) {
+        return DecodedValue::Bytes32(topic.0);
     }
-    if elem_type == "string" || elem_type == "bytes" {
-        // offset -> length -> bytes
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&data[offset..offset + 32]);
-        let len = U256::from_be_bytes(arr).to::<usize>();
-        let start = offset + 32;
-        let _end = start + ((len + 31) / 32) * 32; // padded end (unused)
-        if start + len > data.len() {
-            return None;
-        }
-        let raw = &data[start..start + len];
-        return Some(if elem_type == "string" {
-            DecodedValue::String(String::from_utf8_lossy(raw).into_owned())
-        } else {
-            DecodedValue::Bytes(raw.to_vec())
-        });
+    match ty.abi_decode(topic.as_slice()) {
+        Ok(v) => DecodedValue::from_dyn(v, components),
+        Err(_) => DecodedValue::Unsupported("invalid indexed value"),
     }
-    // dynamic array: T[]
-    if let Some(base) = elem_type.strip_suffix("[]") {
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&data[offset..offset + 32]);
-        let count = U256::from_be_bytes(arr).to::<usize>();
-        let start = offset + 32;
-        // static element size = 32 bytes for primitive statics
-        let mut out = Vec::with_capacity(count);
-        for i in 0..count {
-            let off = start + i * 32;
-            if off + 32 > data.len() {
-                return None;
-            }
-            if is_dynamic_type(base) {
-                // nested dynamic not supported in this simple decoder
-                return Some(DecodedValue::Unsupported("nested dynamic array"));
-            } else {
-                out.push(decode_static_word(&data[off..off + 32], base));
-            }
-        }
-        return Some(DecodedValue::Array(out));
+}
+
+pub fn decode_indexed(topic: &B256, typ: &str) -> DecodedValue {
+    match DynSolType::parse(typ) {
+        Ok(ty) => decode_topic(topic, &ty, &[]),
+        Err(_) => DecodedValue::Unsupported("unknown type"),
     }
-    None
 }
 
-pub fn try_decode_event(
-    topic0_hex: &str,
+pub fn decode_static_word(word: &[u8], typ: &str) -> DecodedValue {
+    match DynSolType::parse(typ) {
+        Ok(ty) if !is_hashed_when_indexed(&ty) && word.len() == 32 => match ty.abi_decode(word) {
+            Ok(v) => DecodedValue::from_dyn(v, &[]),
+            Err(_) => DecodedValue::Unsupported("invalid static word"),
+        },
+        _ => DecodedValue::Unsupported("dynamic or unsupported type"),
+    }
+}
+
+/// 按参数列表解码一段 ABI 编码（函数参数 / 事件 data），失败时返回 None
+fn decode_params(params: &[Param], data: &[u8]) -> Option<Vec<DecodedValue>> {
+    let types = params
+        .iter()
+        .map(|p| p.resolve())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+    let decoded = DynSolType::Tuple(types).abi_decode_sequence(data).ok()?;
+    let DynSolValue::Tuple(values) = decoded else {
+        return None;
+    };
+    Some(
+        values
+            .into_iter()
+            .zip(params)
+            .map(|(v, p)| DecodedValue::from_dyn(v, &p.components))
+            .collect(),
+    )
+}
+
+/// 用单个 ABI 变体解码日志；`topics` 不含 topic0（匿名事件即全部 topics）。
+/// 返回的 bool 表示 indexed 数量与 data 长度是否与该变体完全吻合。
+fn decode_event_with(
+    entry: &EventSigEntry,
     topics: &[B256],
     data: &[u8],
-    events: &EventSigMap,
-) -> Option<(String, Vec<DecodedField>)> {
-    let entry = events.get(topic0_hex)?;
+) -> (Vec<DecodedField>, bool) {
+    let inputs: Vec<EventParam> = if entry.unknown_indexed {
+        let mut v = entry.abi.inputs.clone();
+        for (i, p) in v.iter_mut().enumerate() {
+            p.indexed = i < topics.len();
+        }
+        v
+    } else {
+        entry.abi.inputs.clone()
+    };
+    // non-indexed inputs are ABI-encoded together in data
+    let body_params: Vec<Param> = inputs
+        .iter()
+        .filter(|i| !i.indexed)
+        .map(|i| Param {
+            ty: i.ty.clone(),
+            name: i.name.clone(),
+            components: i.components.clone(),
+            internal_type: i.internal_type.clone(),
+        })
+        .collect();
+    let body_values = decode_params(&body_params, data);
+    let indexed_count = inputs.iter().filter(|i| i.indexed).count();
+    let exact = indexed_count == topics.len()
+        && body_values
+            .as_ref()
+            .is_some_and(|v| v.len() == body_params.len() && encoded_len(&body_params, data));
+    let mut body = body_values.map(|v| v.into_iter());
+
     let mut fields = Vec::new();
-    // indexed decoding from topics[1..]
-    let mut ti = 1usize;
-    // head area is 32 bytes per non-indexed input
-    let _non_indexed: Vec<_> = entry.abi.inputs.iter().filter(|i| !i.indexed).collect();
-    // decode
-    let mut head_index = 0usize;
-    for input in &entry.abi.inputs {
+    let mut ti = 0usize;
+    for input in &inputs {
         let name = input.name.clone();
         if input.indexed {
             if ti < topics.len() {
-                let v = decode_indexed(&topics[ti], &input.ty);
+                let (value, ty) = match input.resolve() {
+                    Ok(ty) if is_hashed_when_indexed(&ty) => (
+                        decode_topic(&topics[ti], &ty, &input.components),
+                        "bytes32".to_string(),
+                    ),
+                    Ok(ty) => (
+                        decode_topic(&topics[ti], &ty, &input.components),
+                        input.selector_type().into_owned(),
+                    ),
+                    Err(_) => (
+                        DecodedValue::Unsupported("unknown type"),
+                        input.selector_type().into_owned(),
+                    ),
+                };
                 ti += 1;
                 fields.push(DecodedField {
                     name,
-                    value: v,
+                    ty,
+                    value,
                     indexed: true,
                 });
             }
         } else {
-            let typ = input.ty.as_str();
-            // head word
-            let head_off = head_index * 32;
-            head_index += 1;
-            if head_off + 32 > data.len() {
-                continue;
-            }
-            if is_dynamic_type(typ) {
-                // read offset relative to start of data
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(&data[head_off..head_off + 32]);
-                let off = U256::from_be_bytes(arr).to::<usize>();
-                if let Some(v) = decode_dynamic(data, off, typ) {
-                    fields.push(DecodedField {
-                        name,
-                        value: v,
-                        indexed: false,
-                    });
-                } else {
-                    fields.push(DecodedField {
-                        name,
-                        value: DecodedValue::Unsupported("dynamic decode failed"),
-                        indexed: false,
-                    });
-                }
-            } else {
-                let word = &data[head_off..head_off + 32];
-                let v = decode_static_word(word, typ);
-                fields.push(DecodedField {
-                    name,
-                    value: v,
-                    indexed: false,
-                });
+            let value = match body.as_mut().and_then(|it| it.next()) {
+                Some(v) => v,
+                None => DecodedValue::Unsupported("abi decode failed"),
+            };
+            fields.push(DecodedField {
+                name,
+                ty: input.selector_type().into_owned(),
+                value,
+                indexed: false,
+            });
+        }
+    }
+    (fields, exact)
+}
+
+/// 重新编码后长度一致才视为完全匹配（避免 data 过长时被宽松解码接受）
+fn encoded_len(params: &[Param], data: &[u8]) -> bool {
+    let Ok(types) = params
+        .iter()
+        .map(|p| p.resolve())
+        .collect::<std::result::Result<Vec<_>, _>>()
+    else {
+        return false;
+    };
+    match DynSolType::Tuple(types).abi_decode_sequence(data) {
+        Ok(v) => v
+            .abi_encode_sequence()
+            .is_some_and(|enc| enc.len() == data.len()),
+        Err(_) => false,
+    }
+}
+
+pub fn try_decode_event(
+    topic0_hex: &str,
+    topics: &[B256],
+    data: &[u8],
+    events: &EventSigMap,
+) -> Option<(String, Vec<DecodedField>)> {
+    if let Some(variants) = events.get(topic0_hex) {
+        // 优先选择 indexed 数量与 data 长度都吻合的变体，否则退回第一个变体
+        let rest = topics.get(1..).unwrap_or_default();
+        let mut fallback = None;
+        for entry in variants {
+            let (fields, exact) = decode_event_with(entry, rest, data);
+            if exact {
+                return Some((entry.name.clone(), fields));
             }
+            fallback.get_or_insert((entry.name.clone(), fields));
         }
+        return fallback;
     }
-    Some((entry.name.clone(), fields))
+    // 匿名事件：topic0 也是 indexed 参数
+    events.get(ANONYMOUS_EVENTS_KEY)?.iter().find_map(|entry| {
+        let (fields, exact) = decode_event_with(entry, topics, data);
+        exact.then(|| (entry.name.clone(), fields))
+    })
 }
 
 pub fn try_decode_function(
     selector_hex: &str,
     calldata: &[u8],
     funcs: &FuncSigMap,
-) -> Option<(String, Vec<DecodedValue>)> {
+) -> Option<(String, Vec<DecodedField>)> {
     let entry = funcs.get(selector_hex)?;
-    let head_base = 4; // skip selector
-    let count = entry.abi.inputs.len();
-    let mut values = Vec::with_capacity(count);
-    for (i, input) in entry.abi.inputs.iter().enumerate() {
-        let typ = input.ty.as_str();
-        let off = head_base + i * 32;
-        if off + 32 > calldata.len() {
-            break;
-        }
-        if is_dynamic_type(typ) {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&calldata[off..off + 32]);
-            let rel = U256::from_be_bytes(arr).to::<usize>();
-            let data_off = head_base + rel;
-            if let Some(v) = decode_dynamic(&calldata, data_off, typ) {
-                values.push(v);
-            } else {
-                values.push(DecodedValue::Unsupported("dynamic decode failed"));
+    // skip selector
+    let args = calldata.get(4..).unwrap_or_default();
+    let inputs = &entry.abi.inputs;
+    let values = decode_params(inputs, args)
+        .unwrap_or_else(|| vec![DecodedValue::Unsupported("abi decode failed"); inputs.len()]);
+    let fields = inputs
+        .iter()
+        .zip(values)
+        .map(|(p, value)| DecodedField::from_param(p, value))
+        .collect();
+    Some((entry.name.clone(), fields))
+}
+
+/// `Error(string)` 的 selector
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// `Panic(uint256)` 的 selector
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// 失败交易 revert 数据的解码结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevertReason {
+    /// 没有 revert 数据（`revert()`、无消息的 `require`、out of gas 等）
+    Empty,
+    /// `Error(string)`，即 `require(cond, "msg")` / `revert("msg")`
+    Error(String),
+    /// `Panic(uint256)`，编译器插入的断言（溢出、除零、越界等）
+    Panic {
+        code: U256,
+        desc: Option<&'static str>,
+    },
+    /// error_sigs.json 中登记的自定义错误
+    Custom {
+        name: String,
+        sig: String,
+        fields: Vec<DecodedField>,
+    },
+    /// 未知 selector 或数据无法按签名解码
+    Unknown { selector: Option<[u8; 4]> },
+}
+
+impl RevertReason {
+    /// 便于日志输出的单行描述
+    pub fn describe(&self) -> String {
+        match self {
+            RevertReason::Empty => "empty".to_string(),
+            RevertReason::Error(msg) => format!("Error({:?})", msg),
+            RevertReason::Panic { code, desc } => match desc {
+                Some(d) => format!("Panic(0x{:02x}: {})", code, d),
+                None => format!("Panic(0x{:02x})", code),
+            },
+            RevertReason::Custom { sig, .. } => sig.clone(),
+            RevertReason::Unknown {
+                selector: Some(sel),
+            } => {
+                format!("unknown(0x{})", hex::encode(sel))
             }
-        } else {
-            let word = &calldata[off..off + 32];
-            values.push(decode_static_word(word, typ));
+            RevertReason::Unknown { selector: None } => "unknown".to_string(),
+        }
+    }
+}
+
+/// Solidity 编译器定义的 panic code 含义
+pub fn panic_code_desc(code: U256) -> Option<&'static str> {
+    let code: u64 = code.try_into().ok()?;
+    Some(match code {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to zero-initialized function pointer",
+        _ => return None,
+    })
+}
+
+/// 解码 revert 数据：依次尝试 `Error(string)`、`Panic(uint256)` 与自定义错误
+pub fn decode_revert(data: &[u8], errors: &ErrorSigMap) -> RevertReason {
+    if data.is_empty() {
+        return RevertReason::Empty;
+    }
+    let Some(selector) = data.get(..4).and_then(|s| <[u8; 4]>::try_from(s).ok()) else {
+        return RevertReason::Unknown { selector: None };
+    };
+    let args = &data[4..];
+    if selector == ERROR_STRING_SELECTOR {
+        if let Ok(DynSolValue::String(msg)) = DynSolType::String.abi_decode(args) {
+            return RevertReason::Error(msg);
+        }
+    } else if selector == PANIC_SELECTOR {
+        if let Ok(DynSolValue::Uint(code, _)) = DynSolType::Uint(256).abi_decode(args) {
+            return RevertReason::Panic {
+                code,
+                desc: panic_code_desc(code),
+            };
         }
+    } else if let Some(entry) = errors.get(&format!("0x{}", hex::encode(selector)))
+        && let Some(values) = decode_params(&entry.abi.inputs, args)
+    {
+        let fields = entry
+            .abi
+            .inputs
+            .iter()
+            .zip(values)
+            .map(|(p, value)| DecodedField::from_param(p, value))
+            .collect();
+        return RevertReason::Custom {
+            name: entry.name.clone(),
+            sig: entry.sig.clone(),
+            fields,
+        };
+    }
+    RevertReason::Unknown {
+        selector: Some(selector),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, address, keccak256};
+
+    fn event_map(abi: serde_json::Value) -> (String, EventSigMap) {
+        let abi: Event = serde_json::from_value(abi).unwrap();
+        let topic0 = format!("0x{}", hex::encode(abi.selector()));
+        let entry = EventSigEntry {
+            name: abi.name.clone(),
+            sig: abi.signature(),
+            abi,
+            unknown_indexed: false,
+            collisions: vec![],
+        };
+        (topic0.clone(), HashMap::from([(topic0, vec![entry])]))
+    }
+
+    #[test]
+    fn function_round_trip_all_types() {
+        let abi = Function::parse(
+            "function f(int24 a, bytes3 b, (address,uint8[2])[] c, string[][] d, bytes32 e, bool g)",
+        )
+        .unwrap();
+        let owner = address!("00000000000000000000000000000000000000aa");
+        let args = DynSolValue::Tuple(vec![
+            DynSolValue::Int(I256::try_from(-42).unwrap(), 24),
+            DynSolValue::FixedBytes(
+                FixedBytes::right_padding_from(&[0xab, 0xcd, 0xef]).into(),
+                3,
+            ),
+            DynSolValue::Array(vec![DynSolValue::Tuple(vec![
+                DynSolValue::Address(owner),
+                DynSolValue::FixedArray(vec![
+                    DynSolValue::Uint(U256::from(1), 8),
+                    DynSolValue::Uint(U256::from(2), 8),
+                ]),
+            ])]),
+            DynSolValue::Array(vec![
+                DynSolValue::Array(vec![
+                    DynSolValue::String("a".into()),
+                    DynSolValue::String("bc".into()),
+                ]),
+                DynSolValue::Array(vec![]),
+            ]),
+            DynSolValue::FixedBytes(B256::repeat_byte(7), 32),
+            DynSolValue::Bool(true),
+        ]);
+        let mut calldata = abi.selector().to_vec();
+        calldata.extend(args.abi_encode_params());
+
+        let selector = format!("0x{}", hex::encode(abi.selector()));
+        let entry = FuncSigEntry {
+            name: abi.name.clone(),
+            sig: abi.signature(),
+            abi,
+            collisions: vec![],
+        };
+        let funcs = HashMap::from([(selector.clone(), entry)]);
+        let (name, fields) = try_decode_function(&selector, &calldata, &funcs).unwrap();
+
+        assert_eq!(name, "f");
+        let values: Vec<_> = fields.iter().map(|f| f.value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                DecodedValue::Int(I256::try_from(-42).unwrap()),
+                DecodedValue::FixedBytes(vec![0xab, 0xcd, 0xef]),
+                DecodedValue::Array(vec![DecodedValue::Tuple(vec![
+                    (String::new(), DecodedValue::Address(owner)),
+                    (
+                        String::new(),
+                        DecodedValue::Array(vec![
+                            DecodedValue::Uint(U256::from(1)),
+                            DecodedValue::Uint(U256::from(2)),
+                        ])
+                    ),
+                ])]),
+                DecodedValue::Array(vec![
+                    DecodedValue::Array(vec![
+                        DecodedValue::String("a".into()),
+                        DecodedValue::String("bc".into())
+                    ]),
+                    DecodedValue::Array(vec![]),
+                ]),
+                DecodedValue::Bytes32([7; 32]),
+                DecodedValue::Bool(true),
+            ]
+        );
+
+        // 类型化 JSON：整数为十进制字符串，地址为校验和格式，tuple 组件带类型
+        assert_eq!(fields[2].ty, "(address,uint8[2])[]");
+        assert_eq!(
+            fields[2].to_json(),
+            serde_json::json!({
+                "name": "c",
+                "type": "(address,uint8[2])[]",
+                "indexed": false,
+                "value": [[
+                    { "name": "", "type": "address", "value": owner.to_checksum(None) },
+                    { "name": "", "type": "uint8[2]", "value": ["1", "2"] }
+                ]]
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&fields[0]).unwrap(),
+            serde_json::json!({ "name": "a", "type": "int24", "indexed": false, "value": "-42" })
+        );
+        assert_eq!(
+            serde_json::to_value(&fields[3].value).unwrap(),
+            serde_json::json!([["a", "bc"], []])
+        );
+        // 单独序列化的值不带类型，tuple 组件也只有 name / value
+        assert_eq!(
+            serde_json::to_value(&fields[2].value).unwrap(),
+            serde_json::json!([[
+                { "name": "", "value": owner.to_checksum(None) },
+                { "name": "", "value": ["1", "2"] }
+            ]])
+        );
+    }
+
+    #[test]
+    fn event_round_trip_with_indexed_dynamic() {
+        // E(int256 indexed delta, string indexed tag, (uint256 x, bytes y) info, int8 small)
+        let (topic0, events) = event_map(serde_json::json!({
+            "type": "event",
+            "name": "E",
+            "anonymous": false,
+            "inputs": [
+                { "name": "delta", "type": "int256", "indexed": true },
+                { "name": "tag", "type": "string", "indexed": true },
+                { "name": "info", "type": "tuple", "indexed": false, "components": [
+                    { "name": "x", "type": "uint256" },
+                    { "name": "y", "type": "bytes" }
+                ] },
+                { "name": "small", "type": "int8", "indexed": false }
+            ]
+        }));
+        let tag_hash = keccak256("hello");
+        let topics = vec![
+            events[&topic0][0].abi.selector(),
+            B256::from_slice(&DynSolValue::Int(I256::MINUS_ONE, 256).abi_encode()),
+            tag_hash,
+        ];
+        let data = DynSolValue::Tuple(vec![
+            DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(5), 256),
+                DynSolValue::Bytes(vec![1, 2, 3]),
+            ]),
+            DynSolValue::Int(I256::try_from(-3).unwrap(), 8),
+        ])
+        .abi_encode_params();
+
+        let (name, fields) = try_decode_event(&topic0, &topics, &data, &events).unwrap();
+        assert_eq!(name, "E");
+        // indexed string 只有哈希
+        assert_eq!(fields[1].ty, "bytes32");
+        assert_eq!(fields[2].ty, "(uint256,bytes)");
+        let values: Vec<_> = fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.indexed, f.value.clone()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("delta", true, DecodedValue::Int(I256::MINUS_ONE)),
+                ("tag", true, DecodedValue::Bytes32(tag_hash.0)),
+                (
+                    "info",
+                    false,
+                    DecodedValue::Tuple(vec![
+                        ("x".to_string(), DecodedValue::Uint(U256::from(5))),
+                        ("y".to_string(), DecodedValue::Bytes(vec![1, 2, 3])),
+                    ])
+                ),
+                (
+                    "small",
+                    false,
+                    DecodedValue::Int(I256::try_from(-3).unwrap())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn picks_erc721_transfer_variant_by_indexed_layout() {
+        let events = load_event_sigs("./data/event_sigs.json").unwrap();
+        let topic0 = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        let t0 = events[topic0][0].abi.selector();
+        let from = B256::left_padding_from(&[1]);
+        let to = B256::left_padding_from(&[2]);
+
+        // ERC-20: value in data
+        let data = U256::from(1000).to_be_bytes::<32>();
+        let (_, fields) = try_decode_event(topic0, &[t0, from, to], &data, &events).unwrap();
+        assert_eq!(fields[2].name, "value");
+        assert_eq!(fields[2].value, DecodedValue::Uint(U256::from(1000)));
+        assert!(!fields[2].indexed);
+
+        // ERC-721: tokenId indexed, empty data
+        let token_id = B256::left_padding_from(&[7]);
+        let (_, fields) =
+            try_decode_event(topic0, &[t0, from, to, token_id], &[], &events).unwrap();
+        assert_eq!(fields[2].name, "tokenId");
+        assert_eq!(fields[2].value, DecodedValue::Uint(U256::from(7)));
+        assert!(fields[2].indexed);
+    }
+
+    #[test]
+    fn decodes_anonymous_event() {
+        let abi: Event = serde_json::from_value(serde_json::json!({
+            "type": "event",
+            "name": "Note",
+            "anonymous": true,
+            "inputs": [
+                { "name": "sig", "type": "bytes4", "indexed": true },
+                { "name": "guy", "type": "address", "indexed": true },
+                { "name": "wad", "type": "uint256", "indexed": false }
+            ]
+        }))
+        .unwrap();
+        let entry = EventSigEntry {
+            name: abi.name.clone(),
+            sig: abi.signature(),
+            abi,
+            unknown_indexed: false,
+            collisions: vec![],
+        };
+        let events = HashMap::from([(ANONYMOUS_EVENTS_KEY.to_string(), vec![entry])]);
+        let topics = [
+            B256::right_padding_from(&[0xde, 0xad, 0xbe, 0xef]),
+            B256::left_padding_from(&[9]),
+        ];
+        let topic0_hex = format!("0x{}", hex::encode(topics[0]));
+        let data = U256::from(5).to_be_bytes::<32>();
+
+        let (name, fields) = try_decode_event(&topic0_hex, &topics, &data, &events).unwrap();
+        assert_eq!(name, "Note");
+        assert_eq!(
+            fields[0].value,
+            DecodedValue::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(fields[2].value, DecodedValue::Uint(U256::from(5)));
+        // 不匹配的 topics 数量不会误判
+        assert!(try_decode_event(&topic0_hex, &topics[..1], &data, &events).is_none());
+    }
+
+    #[test]
+    fn decodes_revert_data() {
+        let errors = ErrorSigMap::new();
+        assert_eq!(decode_revert(&[], &errors), RevertReason::Empty);
+
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(DynSolValue::String("not owner".into()).abi_encode_params());
+        assert_eq!(
+            decode_revert(&data, &errors),
+            RevertReason::Error("not owner".into())
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(U256::from(0x11).to_be_bytes::<32>());
+        let reason = decode_revert(&data, &errors);
+        assert_eq!(
+            reason.describe(),
+            "Panic(0x11: arithmetic overflow or underflow)"
+        );
+
+        let abi = AbiError::parse("error InsufficientBalance(uint256 available, uint256 required)")
+            .unwrap();
+        let selector = format!("0x{}", hex::encode(abi.selector()));
+        let entry = ErrorSigEntry {
+            name: abi.name.clone(),
+            sig: abi.signature(),
+            abi: abi.clone(),
+            collisions: vec![],
+        };
+        let errors = HashMap::from([(selector, entry)]);
+        let mut data = abi.selector().to_vec();
+        data.extend(U256::from(1).to_be_bytes::<32>());
+        data.extend(U256::from(2).to_be_bytes::<32>());
+        let RevertReason::Custom { name, fields, .. } = decode_revert(&data, &errors) else {
+            panic!("expected custom error");
+        };
+        assert_eq!(name, "InsufficientBalance");
+        assert_eq!(fields[1].name, "required");
+        assert_eq!(fields[1].value, DecodedValue::Uint(U256::from(2)));
+
+        assert_eq!(
+            decode_revert(&[0xde, 0xad, 0xbe, 0xef], &HashMap::new()),
+            RevertReason::Unknown {
+                selector: Some([0xde, 0xad, 0xbe, 0xef])
+            }
+        );
     }
-    Some((entry.name.clone(), values))
 }
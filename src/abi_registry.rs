@@ -0,0 +1,732 @@
+use crate::abi::{
+    self, DecodedField, EventSigEntry, EventSigMap, FuncSigEntry, FuncSigMap,
+    ANONYMOUS_EVENTS_KEY,
+};
+use crate::actions::{deployment, proxy};
+use crate::config::Config;
+use crate::data_cmd;
+use crate::error::{AppError, Result};
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::{hex, keccak256, Address, B256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_transport::BoxTransport;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+// Per-contract ABI registry
+// - 解码时优先使用合约自身的 ABI，未命中再退回全局 event_sigs.json / func_sigs.json
+// - 首次见到被监控地址时，按 blockscanners.json 中当前链的 "contract-abi" 模板从浏览器拉取
+// - 结果缓存在 <cache-dir>/<chain_id>/<address>.json，之后的运行无需联网（未验证合约缓存为空 ABI）
+// - 被监控地址若是代理（EIP-1167 / EIP-1967 / beacon），解析当前实现并用实现合约的 ABI 解码；
+//   代理发出 Upgraded / BeaconUpgraded 时失效映射，下次解码重新解析
+// - 磁盘未命中记入内存负缓存，TTL 内不再读盘；浏览器拉取失败按指数退避重试
+
+/// 磁盘缓存未命中后多久再读一次
+const MISS_TTL: Duration = Duration::from_secs(300);
+/// 拉取失败后的首次重试间隔，之后每次翻倍
+const FETCH_RETRY_BASE: Duration = Duration::from_secs(60);
+const FETCH_RETRY_MAX: Duration = Duration::from_secs(3600);
+
+static REGISTRY: OnceCell<Arc<AbiRegistry>> = OnceCell::new();
+
+/// 配置文件中的 "contract-abi" 段
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct ContractAbiConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+    #[serde(default = "default_scanners_path")]
+    pub scanners_path: String,
+    /// 覆盖 blockscanners.json 中的 URL 模板（%v 为地址）
+    #[serde(default)]
+    pub scanner_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 解析代理合约的实现地址，并用实现合约的 ABI 解码
+    #[serde(default = "default_enabled")]
+    pub resolve_proxies: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_cache_dir() -> String {
+    "./data/abi_cache".to_string()
+}
+
+fn default_scanners_path() -> String {
+    "./data/blockscanners.json".to_string()
+}
+
+/// 单个合约的已解析 ABI
+#[derive(Default)]
+pub struct ContractAbi {
+    pub events: EventSigMap,
+    pub funcs: FuncSigMap,
+}
+
+impl ContractAbi {
+    pub fn from_json_abi(json: &JsonAbi) -> Self {
+        let mut out = ContractAbi::default();
+        for ev in json.events() {
+            let key = if ev.anonymous {
+                ANONYMOUS_EVENTS_KEY.to_string()
+            } else {
+                format!("0x{}", hex::encode(ev.selector()))
+            };
+            out.events.entry(key).or_default().push(EventSigEntry {
+                abi: ev.clone(),
+                name: ev.name.clone(),
+                sig: ev.signature(),
+                unknown_indexed: false,
+                collisions: Vec::new(),
+            });
+        }
+        for f in json.functions() {
+            out.funcs.insert(
+                format!("0x{}", hex::encode(f.selector())),
+                FuncSigEntry {
+                    abi: f.clone(),
+                    name: f.name.clone(),
+                    sig: f.signature(),
+                    collisions: Vec::new(),
+                },
+            );
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Miss {
+    until: Instant,
+    /// 连续拉取失败次数
+    failures: u32,
+}
+
+/// 第 n 次连续失败后的重试间隔
+fn retry_delay(failures: u32) -> Duration {
+    if failures == 0 {
+        return MISS_TTL;
+    }
+    FETCH_RETRY_BASE
+        .saturating_mul(1u32 << (failures - 1).min(16))
+        .min(FETCH_RETRY_MAX)
+}
+
+/// 代理合约当前指向的实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxyTarget {
+    pub implementation: Address,
+    /// beacon 代理经由的 beacon 合约（beacon 自身升级时同样需要失效）
+    pub beacon: Option<Address>,
+}
+
+pub struct AbiRegistry {
+    chain_id: u64,
+    cache_dir: PathBuf,
+    scanner_url: Option<String>,
+    api_key: Option<String>,
+    watched: HashSet<Address>,
+    /// None 表示浏览器确认没有 ABI（未验证合约）
+    entries: RwLock<HashMap<Address, Option<Arc<ContractAbi>>>>,
+    /// 负缓存：磁盘未命中 / 拉取中 / 拉取失败，到期前不再查
+    misses: Mutex<HashMap<Address, Miss>>,
+    inflight: Mutex<HashSet<Address>>,
+    /// 设置后对被监控地址解析代理实现
+    provider: Option<RootProvider<BoxTransport>>,
+    /// 代理 -> 实现；None 表示不是代理（或解析中）
+    proxies: RwLock<HashMap<Address, Option<ProxyTarget>>>,
+    /// 每次观察到升级事件加一；早于最近一次失效开始的解析结果丢弃
+    proxy_generation: AtomicU64,
+}
+
+impl AbiRegistry {
+    pub fn new(
+        chain_id: u64,
+        cache_dir: impl Into<PathBuf>,
+        scanner_url: Option<String>,
+        api_key: Option<String>,
+        watched: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            chain_id,
+            cache_dir: cache_dir.into(),
+            scanner_url,
+            api_key,
+            watched: watched.into_iter().collect(),
+            entries: RwLock::new(HashMap::new()),
+            misses: Mutex::new(HashMap::new()),
+            inflight: Mutex::new(HashSet::new()),
+            provider: None,
+            proxies: RwLock::new(HashMap::new()),
+            proxy_generation: AtomicU64::new(0),
+        }
+    }
+
+    /// 启用代理解析
+    pub fn with_proxy_resolution(mut self, provider: RootProvider<BoxTransport>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    fn cache_path(&self, address: Address) -> PathBuf {
+        self.cache_dir
+            .join(self.chain_id.to_string())
+            .join(format!("0x{}.json", hex::encode(address)))
+    }
+
+    /// 同步查询：命中内存或磁盘缓存即返回；被监控地址未缓存时在后台拉取，本次返回 None
+    pub fn lookup(self: &Arc<Self>, address: Address) -> Option<Arc<ContractAbi>> {
+        if let Some(v) = self.cached(address) {
+            return v;
+        }
+        if self.watched.contains(&address)
+            && self.scanner_url.is_some()
+            && let Ok(handle) = tokio::runtime::Handle::try_current()
+        {
+            let this = self.clone();
+            handle.spawn(async move {
+                this.ensure_loaded(address).await;
+            });
+        }
+        None
+    }
+
+    /// 内存 / 磁盘缓存。Some 为确定答复（负缓存未到期时为 Some(None)）；
+    /// None 表示磁盘也未命中，已记入负缓存，调用方可以去拉取
+    fn cached(&self, address: Address) -> Option<Option<Arc<ContractAbi>>> {
+        if let Some(v) = self.entries.read().unwrap().get(&address) {
+            return Some(v.clone());
+        }
+        if let Some(m) = self.misses.lock().unwrap().get(&address)
+            && Instant::now() < m.until
+        {
+            return Some(None);
+        }
+        if let Some(abi) = self.load_cached(address) {
+            self.entries.write().unwrap().insert(address, abi.clone());
+            self.misses.lock().unwrap().remove(&address);
+            return Some(abi);
+        }
+        self.note_miss(address, false);
+        None
+    }
+
+    /// 记入负缓存；`failed` 为拉取失败，退避时间随连续失败次数增长
+    fn note_miss(&self, address: Address, failed: bool) {
+        let mut misses = self.misses.lock().unwrap();
+        let m = misses.entry(address).or_insert(Miss { until: Instant::now(), failures: 0 });
+        if failed {
+            m.failures += 1;
+        }
+        m.until = Instant::now() + retry_delay(m.failures);
+    }
+
+    /// 读取磁盘缓存；文件不存在返回 None，空 ABI 返回 Some(None)
+    fn load_cached(&self, address: Address) -> Option<Option<Arc<ContractAbi>>> {
+        let text = std::fs::read_to_string(self.cache_path(address)).ok()?;
+        match serde_json::from_str::<JsonAbi>(&text) {
+            Ok(json) if json.is_empty() => Some(None),
+            Ok(json) => Some(Some(Arc::new(ContractAbi::from_json_abi(&json)))),
+            Err(e) => {
+                warn!("invalid cached abi for {}: {}", address, e);
+                None
+            }
+        }
+    }
+
+    /// 确保 ABI 已加载（磁盘缓存优先，其次浏览器），并发调用只会请求一次
+    pub async fn ensure_loaded(&self, address: Address) -> Option<Arc<ContractAbi>> {
+        if let Some(Some(abi)) = self.entries.read().unwrap().get(&address) {
+            return Some(abi.clone());
+        }
+        if let Some(abi) = self.load_cached(address) {
+            self.entries.write().unwrap().insert(address, abi.clone());
+            return abi;
+        }
+        let url = self.scanner_url.as_ref()?;
+        if !self.inflight.lock().unwrap().insert(address) {
+            return None;
+        }
+        let res = self.fetch(url, address).await;
+        self.inflight.lock().unwrap().remove(&address);
+        match res {
+            Ok(json) => {
+                let abi = (!json.is_empty()).then(|| Arc::new(ContractAbi::from_json_abi(&json)));
+                if let Err(e) = self.store_cached(address, &json) {
+                    warn!("write abi cache for {} failed: {}", address, e);
+                }
+                self.entries.write().unwrap().insert(address, abi.clone());
+                self.misses.lock().unwrap().remove(&address);
+                abi
+            }
+            Err(e) => {
+                warn!("fetch abi for {} failed: {}", address, e);
+                self.note_miss(address, true);
+                None
+            }
+        }
+    }
+
+    /// 同步查询代理实现的 ABI：映射未知时在后台解析代理并拉取实现 ABI，本次返回 None
+    pub fn implementation_abi(self: &Arc<Self>, proxy: Address) -> Option<Arc<ContractAbi>> {
+        let provider = self.provider.as_ref()?;
+        if !self.watched.contains(&proxy) {
+            return None;
+        }
+        let known = self.proxies.read().unwrap().get(&proxy).copied();
+        match known {
+            Some(Some(target)) => {
+                let implementation = target.implementation;
+                if let Some(v) = self.cached(implementation) {
+                    return v;
+                }
+                // 实现 ABI 拉取失败且负缓存已到期：重试
+                if self.scanner_url.is_some()
+                    && let Ok(handle) = tokio::runtime::Handle::try_current()
+                {
+                    let this = self.clone();
+                    handle.spawn(async move {
+                        this.ensure_loaded(implementation).await;
+                    });
+                }
+                None
+            }
+            Some(None) => None,
+            None => {
+                self.proxies.write().unwrap().insert(proxy, None);
+                if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                    let this = self.clone();
+                    let provider = provider.clone();
+                    let generation = self.proxy_generation.load(Ordering::SeqCst);
+                    handle.spawn(async move {
+                        let target = resolve_proxy(&provider, proxy).await;
+                        if !this.store_resolved(proxy, generation, target) {
+                            debug!("[abi] drop stale proxy resolution for {}", proxy);
+                            return;
+                        }
+                        if let Some(t) = target {
+                            info!(
+                                "[abi] proxy {} -> implementation {} (beacon {:?})",
+                                proxy, t.implementation, t.beacon
+                            );
+                            this.ensure_loaded(t.implementation).await;
+                        }
+                    });
+                }
+                None
+            }
+        }
+    }
+
+    /// 写入解析结果；解析开始后出现过升级事件时丢弃并清掉占位，下次解码重新解析
+    fn store_resolved(&self, proxy: Address, generation: u64, target: Option<ProxyTarget>) -> bool {
+        let mut proxies = self.proxies.write().unwrap();
+        if self.proxy_generation.load(Ordering::SeqCst) != generation {
+            proxies.remove(&proxy);
+            return false;
+        }
+        proxies.insert(proxy, target);
+        true
+    }
+
+    /// 当前缓存的代理映射
+    pub fn proxy_target(&self, proxy: Address) -> Option<ProxyTarget> {
+        self.proxies.read().unwrap().get(&proxy).copied().flatten()
+    }
+
+    /// `Upgraded` / `BeaconUpgraded` 出现时失效：发出事件的代理本身，以及经由该 beacon 的所有代理
+    pub fn observe_log(&self, address: Address, topics: &[B256]) {
+        let Some(topic0) = topics.first() else {
+            return;
+        };
+        if *topic0 != upgraded_topic() && *topic0 != beacon_upgraded_topic() {
+            return;
+        }
+        let mut proxies = self.proxies.write().unwrap();
+        // 持锁递增，与 store_resolved 的检查互斥
+        self.proxy_generation.fetch_add(1, Ordering::SeqCst);
+        let before = proxies.len();
+        proxies.retain(|proxy, target| {
+            *proxy != address && !target.is_some_and(|t| t.beacon == Some(address))
+        });
+        if proxies.len() != before {
+            debug!("[abi] proxy mapping invalidated by upgrade at {}", address);
+        }
+    }
+
+    async fn fetch(&self, url: &str, address: Address) -> Result<JsonAbi> {
+        let addr = format!("0x{}", hex::encode(address));
+        let text = data_cmd::fetch_abi_from_scanner(&addr, url, self.api_key.as_deref()).await?;
+        let json = parse_explorer_abi(&text)?;
+        info!("[abi] fetched abi for {} ({} items)", addr, json.len());
+        Ok(json)
+    }
+
+    fn store_cached(&self, address: Address, json: &JsonAbi) -> Result<()> {
+        let path = self.cache_path(address);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(json)?)?;
+        Ok(())
+    }
+}
+
+fn upgraded_topic() -> B256 {
+    keccak256("Upgraded(address)")
+}
+
+fn beacon_upgraded_topic() -> B256 {
+    keccak256("BeaconUpgraded(address)")
+}
+
+/// 解析代理当前的实现：EIP-1167 字节码 > EIP-1967 implementation 槽 > EIP-1967 beacon 槽
+pub async fn resolve_proxy(
+    provider: &RootProvider<BoxTransport>,
+    proxy: Address,
+) -> Option<ProxyTarget> {
+    crate::throttle::acquire().await;
+    if let Ok(code) = provider.get_code_at(proxy).await
+        && let (true, Some(implementation)) = deployment::detect_eip1167_minimal_proxy(&code)
+    {
+        return Some(ProxyTarget {
+            implementation,
+            beacon: None,
+        });
+    }
+    let implementation =
+        proxy::read_slot_address(provider, proxy, proxy::eip1967_implementation_slot()).await;
+    if let Some(implementation) = implementation.filter(|a| *a != Address::ZERO) {
+        return Some(ProxyTarget {
+            implementation,
+            beacon: None,
+        });
+    }
+    let beacon = proxy::read_slot_address(provider, proxy, proxy::eip1967_beacon_slot())
+        .await
+        .filter(|a| *a != Address::ZERO)?;
+    let implementation = proxy::beacon_implementation(provider, beacon)
+        .await
+        .filter(|a| *a != Address::ZERO)?;
+    Some(ProxyTarget {
+        implementation,
+        beacon: Some(beacon),
+    })
+}
+
+/// 解析浏览器响应：`format=raw` 时直接是 ABI 数组，否则为 `{"status","message","result"}`。
+/// 未验证的合约返回空 ABI（同样会被缓存），其他错误（限流等）返回 Err，不写缓存，下次运行重试。
+pub fn parse_explorer_abi(text: &str) -> Result<JsonAbi> {
+    if let Ok(json) = serde_json::from_str::<JsonAbi>(text) {
+        return Ok(json);
+    }
+    let v: serde_json::Value = serde_json::from_str(text)
+        .map_err(|_| AppError::General(format!("unexpected explorer response: {}", text)))?;
+    let result = v.get("result").and_then(|r| r.as_str()).unwrap_or_default();
+    if let Ok(json) = serde_json::from_str::<JsonAbi>(result) {
+        return Ok(json);
+    }
+    if result.to_ascii_lowercase().contains("not verified") {
+        return Ok(JsonAbi::new());
+    }
+    Err(AppError::General(format!("explorer error: {}", text)))
+}
+
+/// 从 blockscanners.json 读取指定链的 "contract-abi" URL 模板
+pub fn scanner_url_for_chain<P: AsRef<Path>>(path: P, chain_id: u64) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let v: serde_json::Value = serde_json::from_str(&text).ok()?;
+    v.get(chain_id.to_string())?
+        .get("contract-abi")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// 根据配置初始化全局注册表；未配置 "contract-abi" 时不启用
+pub async fn init_from_config(
+    cfg: &Config,
+    provider: &RootProvider<BoxTransport>,
+    watched: &[Address],
+) -> Result<()> {
+    let Some(c) = cfg.contract_abi.as_ref().filter(|c| c.enabled) else {
+        return Ok(());
+    };
+    if REGISTRY.get().is_some() {
+        return Ok(());
+    }
+    crate::throttle::acquire().await;
+    let chain_id = provider.get_chain_id().await?;
+    let scanner_url = c
+        .scanner_url
+        .clone()
+        .or_else(|| scanner_url_for_chain(&c.scanners_path, chain_id));
+    if scanner_url.is_none() {
+        warn!(
+            "[abi] no contract-abi scanner for chain {}; using disk cache only",
+            chain_id
+        );
+    }
+    debug!(
+        "[abi] registry chain={} cache={} scanner={:?}",
+        chain_id, c.cache_dir, scanner_url
+    );
+    let mut reg = AbiRegistry::new(
+        chain_id,
+        &c.cache_dir,
+        scanner_url,
+        c.api_key.clone(),
+        watched.iter().copied(),
+    );
+    if c.resolve_proxies {
+        reg = reg.with_proxy_resolution(provider.clone());
+    }
+    let _ = REGISTRY.set(Arc::new(reg));
+    Ok(())
+}
+
+pub fn global() -> Option<&'static Arc<AbiRegistry>> {
+    REGISTRY.get()
+}
+
+/// 先用合约自身 ABI 解码事件，其次是代理实现的 ABI，最后用全局签名表
+pub fn try_decode_event(
+    address: Address,
+    topic0_hex: &str,
+    topics: &[B256],
+    data: &[u8],
+    events: &EventSigMap,
+) -> Option<(String, Vec<DecodedField>)> {
+    if let Some(reg) = global() {
+        reg.observe_log(address, topics);
+        if let Some(contract) = reg.lookup(address)
+            && let Some(res) = abi::try_decode_event(topic0_hex, topics, data, &contract.events)
+        {
+            return Some(res);
+        }
+        if let Some(implementation) = reg.implementation_abi(address)
+            && let Some(res) =
+                abi::try_decode_event(topic0_hex, topics, data, &implementation.events)
+        {
+            return Some(res);
+        }
+    }
+    abi::try_decode_event(topic0_hex, topics, data, events)
+}
+
+/// 先用目标合约 ABI 解码 calldata，其次是代理实现的 ABI，最后用全局签名表
+pub fn try_decode_function(
+    to: Option<Address>,
+    selector_hex: &str,
+    calldata: &[u8],
+    funcs: &FuncSigMap,
+) -> Option<(String, Vec<DecodedField>)> {
+    if let (Some(reg), Some(to)) = (global(), to) {
+        if let Some(contract) = reg.lookup(to)
+            && let Some(res) = abi::try_decode_function(selector_hex, calldata, &contract.funcs)
+        {
+            return Some(res);
+        }
+        if let Some(implementation) = reg.implementation_abi(to)
+            && let Some(res) =
+                abi::try_decode_function(selector_hex, calldata, &implementation.funcs)
+        {
+            return Some(res);
+        }
+    }
+    abi::try_decode_function(selector_hex, calldata, funcs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::DecodedValue;
+    use alloy_primitives::address;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const ABI: &str = r#"[{"type":"event","name":"Swap","anonymous":false,"inputs":[
+        {"name":"sender","type":"address","indexed":true},
+        {"name":"amountIn","type":"uint256","indexed":false}]}]"#;
+
+    /// 本地 HTTP 替身：每个连接返回同一个 etherscan 风格的响应，并记录请求次数
+    async fn stand_in_explorer(body: String) -> (String, Arc<Mutex<usize>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let hits = Arc::new(Mutex::new(0usize));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                *counter.lock().unwrap() += 1;
+                let mut buf = [0u8; 2048];
+                let _ = sock.read(&mut buf).await;
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        (
+            format!("http://127.0.0.1:{port}/api?module=contract&action=getabi&address=%v"),
+            hits,
+        )
+    }
+
+    #[tokio::test]
+    async fn fetches_once_then_serves_from_disk_cache() {
+        let body = serde_json::json!({ "status": "1", "message": "OK", "result": ABI }).to_string();
+        let (url, hits) = stand_in_explorer(body).await;
+        let dir = std::env::temp_dir().join(format!("evm-track-abi-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let pool = address!("00000000000000000000000000000000000000c1");
+
+        let reg = AbiRegistry::new(56, &dir, Some(url), None, [pool]);
+        let abi = reg.ensure_loaded(pool).await.expect("abi fetched");
+        assert!(abi.events.values().flatten().any(|e| e.name == "Swap"));
+        assert_eq!(*hits.lock().unwrap(), 1);
+        assert!(dir
+            .join("56")
+            .join(format!("0x{}.json", hex::encode(pool)))
+            .exists());
+
+        // 新实例、无浏览器 URL：完全依赖磁盘缓存
+        let offline = Arc::new(AbiRegistry::new(56, &dir, None, None, [pool]));
+        let cached = offline.lookup(pool).expect("abi from disk cache");
+        let topic0 = cached.events.keys().next().unwrap().clone();
+        let topics = [
+            B256::from_slice(&hex::decode(&topic0[2..]).unwrap()),
+            B256::left_padding_from(&[0xaa]),
+        ];
+        let data = alloy_primitives::U256::from(42).to_be_bytes::<32>();
+        let (name, fields) =
+            abi::try_decode_event(&topic0, &topics, &data, &cached.events).unwrap();
+        assert_eq!(name, "Swap");
+        assert_eq!(
+            fields[1].value,
+            DecodedValue::Uint(alloy_primitives::U256::from(42))
+        );
+        assert_eq!(*hits.lock().unwrap(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unverified_contract_yields_empty_abi() {
+        let text =
+            r#"{"status":"0","message":"NOTOK","result":"Contract source code not verified"}"#;
+        assert_eq!(parse_explorer_abi(text).unwrap().len(), 0);
+        let limited = r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#;
+        assert!(parse_explorer_abi(limited).is_err());
+    }
+
+    #[test]
+    fn upgrade_events_invalidate_proxy_mapping() {
+        let proxy = address!("00000000000000000000000000000000000000d1");
+        let beacon_proxy = address!("00000000000000000000000000000000000000d2");
+        let beacon = address!("00000000000000000000000000000000000000be");
+        let implementation = address!("00000000000000000000000000000000000000e1");
+        let reg = AbiRegistry::new(1, std::env::temp_dir(), None, None, [proxy, beacon_proxy]);
+        {
+            let mut proxies = reg.proxies.write().unwrap();
+            proxies.insert(
+                proxy,
+                Some(ProxyTarget {
+                    implementation,
+                    beacon: None,
+                }),
+            );
+            proxies.insert(
+                beacon_proxy,
+                Some(ProxyTarget {
+                    implementation,
+                    beacon: Some(beacon),
+                }),
+            );
+        }
+
+        // 其他事件不影响映射
+        reg.observe_log(proxy, &[keccak256("Transfer(address,address,uint256)")]);
+        assert!(reg.proxy_target(proxy).is_some());
+
+        // 代理自身 Upgraded
+        let new_impl = B256::left_padding_from(&[0xe2]);
+        reg.observe_log(proxy, &[upgraded_topic(), new_impl]);
+        assert!(reg.proxy_target(proxy).is_none());
+        assert!(reg.proxies.read().unwrap().get(&proxy).is_none());
+        assert!(reg.proxy_target(beacon_proxy).is_some());
+
+        // beacon 合约升级时，经由它的代理全部失效
+        reg.observe_log(beacon, &[upgraded_topic(), new_impl]);
+        assert!(reg.proxy_target(beacon_proxy).is_none());
+    }
+
+    #[test]
+    fn disk_misses_are_cached_until_ttl_expires() {
+        let dir = std::env::temp_dir().join(format!("evm-track-abi-miss-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let pool = address!("00000000000000000000000000000000000000c2");
+        let reg = Arc::new(AbiRegistry::new(56, &dir, None, None, [pool]));
+
+        assert!(reg.lookup(pool).is_none());
+        assert!(reg.misses.lock().unwrap().contains_key(&pool));
+
+        // 文件出现后，负缓存到期前仍不读盘
+        let path = reg.cache_path(pool);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, ABI).unwrap();
+        assert!(reg.lookup(pool).is_none());
+
+        reg.misses.lock().unwrap().get_mut(&pool).unwrap().until = Instant::now();
+        assert!(reg.lookup(pool).is_some());
+        assert!(reg.misses.lock().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_fetches_back_off_exponentially() {
+        assert_eq!(retry_delay(0), MISS_TTL);
+        assert_eq!(retry_delay(1), FETCH_RETRY_BASE);
+        assert_eq!(retry_delay(2), FETCH_RETRY_BASE * 2);
+        assert_eq!(retry_delay(40), FETCH_RETRY_MAX);
+
+        let a = address!("00000000000000000000000000000000000000c3");
+        let reg = AbiRegistry::new(1, std::env::temp_dir(), None, None, []);
+        reg.note_miss(a, true);
+        reg.note_miss(a, true);
+        let m = reg.misses.lock().unwrap()[&a];
+        assert_eq!(m.failures, 2);
+        assert!(m.until > Instant::now() + FETCH_RETRY_BASE);
+    }
+
+    #[test]
+    fn resolves_started_before_an_upgrade_are_dropped() {
+        let proxy = address!("00000000000000000000000000000000000000d3");
+        let old_impl = address!("00000000000000000000000000000000000000e1");
+        let new_impl = address!("00000000000000000000000000000000000000e2");
+        let reg = AbiRegistry::new(1, std::env::temp_dir(), None, None, [proxy]);
+        let target = |implementation| Some(ProxyTarget { implementation, beacon: None });
+
+        // 解析开始（占位），随后代理升级，旧解析才返回
+        let stale = reg.proxy_generation.load(Ordering::SeqCst);
+        reg.proxies.write().unwrap().insert(proxy, None);
+        reg.observe_log(proxy, &[upgraded_topic(), new_impl.into_word()]);
+        assert!(!reg.store_resolved(proxy, stale, target(old_impl)));
+        assert!(reg.proxies.read().unwrap().get(&proxy).is_none());
+
+        let fresh = reg.proxy_generation.load(Ordering::SeqCst);
+        assert!(reg.store_resolved(proxy, fresh, target(new_impl)));
+        assert_eq!(reg.proxy_target(proxy).map(|t| t.implementation), Some(new_impl));
+    }
+}
@@ -0,0 +1,464 @@
+use alloy_primitives::{keccak256, B256};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+// 运行时字节码的静态分析工具
+
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const CALLDATALOAD: u8 = 0x35;
+const CALLDATACOPY: u8 = 0x37;
+const MLOAD: u8 = 0x51;
+const JUMPDEST: u8 = 0x5b;
+const DUP1: u8 = 0x80;
+const DUP16: u8 = 0x8f;
+const SWAP1: u8 = 0x90;
+const SWAP16: u8 = 0x9f;
+pub const CALLCODE: u8 = 0xf2;
+pub const DELEGATECALL: u8 = 0xf4;
+pub const SELFDESTRUCT: u8 = 0xff;
+
+// 分发器中 selector 不超过此数且使用 DELEGATECALL 的合约视为代理
+const PROXY_MAX_SELECTORS: usize = 8;
+
+/// 提取字节码中所有 PUSH4 的立即数，即 Solidity / Vyper 函数分发器比较的 selector。
+/// 按指令遍历，其它 PUSH 的数据部分不会被误认为操作码；末尾的 metadata 可能带来少量误报，
+/// 调用方只用结果与已知签名求交集。
+pub fn push4_selectors(code: &[u8]) -> BTreeSet<[u8; 4]> {
+    let mut out = BTreeSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        if op == PUSH4
+            && let Some(imm) = code.get(pc + 1..pc + 5)
+        {
+            out.insert([imm[0], imm[1], imm[2], imm[3]]);
+        }
+        pc += 1;
+        if (PUSH1..=PUSH32).contains(&op) {
+            pc += (op - PUSH1 + 1) as usize;
+        }
+    }
+    out
+}
+
+/// 去掉 solc 追加在末尾的 CBOR metadata（最后两字节为其长度），避免其中的字节被当作指令
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    let n = code.len();
+    if n < 2 {
+        return code;
+    }
+    let meta = u16::from_be_bytes([code[n - 2], code[n - 1]]) as usize;
+    // CBOR map 头：0xa1..=0xa5（ipfs / bzzr / solc 等键）
+    if meta + 2 <= n && (0xa1..=0xa5).contains(&code[n - 2 - meta]) {
+        return &code[..n - 2 - meta];
+    }
+    code
+}
+
+/// 字节码（去掉 metadata 后）是否包含某个操作码；PUSH 的数据部分不计
+pub fn has_opcode(code: &[u8], opcode: u8) -> bool {
+    let code = strip_metadata(code);
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        if op == opcode {
+            return true;
+        }
+        pc += 1;
+        if (PUSH1..=PUSH32).contains(&op) {
+            pc += (op - PUSH1 + 1) as usize;
+        }
+    }
+    false
+}
+
+/// 归一化字节码：去掉 metadata，并把 PUSH32 的立即数清零。
+/// solc 把 immutable 以 PUSH32 内联进运行时代码，同一模板不同构造参数的部署归一化后相同。
+pub fn normalize(code: &[u8]) -> Vec<u8> {
+    let mut out = strip_metadata(code).to_vec();
+    let mut pc = 0;
+    while pc < out.len() {
+        let op = out[pc];
+        pc += 1;
+        if op == PUSH32 {
+            let end = (pc + 32).min(out.len());
+            out[pc..end].fill(0);
+        }
+        if (PUSH1..=PUSH32).contains(&op) {
+            pc += (op - PUSH1 + 1) as usize;
+        }
+    }
+    out
+}
+
+/// 归一化字节码的 keccak，用作模板指纹
+pub fn fingerprint(code: &[u8]) -> B256 {
+    keccak256(normalize(code))
+}
+
+/// 按接口（必须全部实现的函数）粗分的合约类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    Erc20,
+    Erc721,
+    Erc1155,
+    Erc4626,
+    UniswapV2Pair,
+    Proxy,
+    Multisig,
+    Unknown,
+}
+
+impl ContractKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContractKind::Erc20 => "erc20",
+            ContractKind::Erc721 => "erc721",
+            ContractKind::Erc1155 => "erc1155",
+            ContractKind::Erc4626 => "erc4626",
+            ContractKind::UniswapV2Pair => "univ2-pair",
+            ContractKind::Proxy => "proxy",
+            ContractKind::Multisig => "multisig",
+            ContractKind::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for ContractKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ContractKind {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(self.as_str())
+    }
+}
+
+const ERC20: &[&str] = &[
+    "totalSupply()",
+    "balanceOf(address)",
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "allowance(address,address)",
+];
+const ERC721: &[&str] = &[
+    "balanceOf(address)",
+    "ownerOf(uint256)",
+    "safeTransferFrom(address,address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "setApprovalForAll(address,bool)",
+    "getApproved(uint256)",
+    "isApprovedForAll(address,address)",
+];
+const ERC1155: &[&str] = &[
+    "balanceOf(address,uint256)",
+    "balanceOfBatch(address[],uint256[])",
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    "setApprovalForAll(address,bool)",
+    "isApprovedForAll(address,address)",
+];
+// 在 ERC20 之上
+const ERC4626: &[&str] = &[
+    "asset()",
+    "totalAssets()",
+    "convertToShares(uint256)",
+    "convertToAssets(uint256)",
+    "deposit(uint256,address)",
+    "mint(uint256,address)",
+    "withdraw(uint256,address,address)",
+    "redeem(uint256,address,address)",
+];
+const UNISWAP_V2_PAIR: &[&str] = &[
+    "getReserves()",
+    "token0()",
+    "token1()",
+    "swap(uint256,uint256,address,bytes)",
+    "mint(address)",
+    "burn(address)",
+    "skim(address)",
+    "sync()",
+];
+// Safe 单例与 Gnosis MultiSigWallet
+const SAFE: &[&str] = &[
+    "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+    "getOwners()",
+    "getThreshold()",
+];
+const MULTISIG_WALLET: &[&str] = &[
+    "submitTransaction(address,uint256,bytes)",
+    "confirmTransaction(uint256)",
+    "executeTransaction(uint256)",
+    "getOwners()",
+];
+
+fn selector(sig: &str) -> [u8; 4] {
+    let h = keccak256(sig);
+    [h[0], h[1], h[2], h[3]]
+}
+
+fn implements(selectors: &BTreeSet<[u8; 4]>, iface: &[&str]) -> bool {
+    iface.iter().all(|sig| selectors.contains(&selector(sig)))
+}
+
+/// 部署时的字节码分析结果
+#[derive(Debug, Clone, Serialize)]
+pub struct Classification {
+    pub kind: ContractKind,
+    #[serde(serialize_with = "ser_selectors")]
+    pub selectors: BTreeSet<[u8; 4]>,
+    pub selfdestruct: bool,
+    /// DELEGATECALL 的目标地址取自 calldata（同一基本块内可追踪到 CALLDATALOAD）
+    pub delegatecall_calldata_target: bool,
+    pub callcode: bool,
+}
+
+impl Classification {
+    /// 命中的危险操作码，用于输出
+    pub fn dangerous_opcodes(&self) -> Vec<&'static str> {
+        let mut out = vec![];
+        if self.selfdestruct {
+            out.push("SELFDESTRUCT");
+        }
+        if self.delegatecall_calldata_target {
+            out.push("DELEGATECALL(calldata-target)");
+        }
+        if self.callcode {
+            out.push("CALLCODE");
+        }
+        out
+    }
+}
+
+fn ser_selectors<S: serde::Serializer>(sels: &BTreeSet<[u8; 4]>, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.collect_seq(sels.iter().map(|x| format!("0x{}", hex::encode(x))))
+}
+
+/// 提取分发器 selector 并按接口分类，同时标记危险操作码。
+/// 更具体的类型优先：multisig > ERC-4626 > Uniswap V2 pair > ERC-1155 > ERC-721 > ERC-20 > proxy。
+pub fn classify(code: &[u8]) -> Classification {
+    let body = strip_metadata(code);
+    let selectors = push4_selectors(body);
+    let delegatecall = has_opcode(body, DELEGATECALL);
+    let kind = if implements(&selectors, SAFE) || implements(&selectors, MULTISIG_WALLET) {
+        ContractKind::Multisig
+    } else if implements(&selectors, ERC20) && implements(&selectors, ERC4626) {
+        ContractKind::Erc4626
+    } else if implements(&selectors, UNISWAP_V2_PAIR) {
+        ContractKind::UniswapV2Pair
+    } else if implements(&selectors, ERC1155) {
+        ContractKind::Erc1155
+    } else if implements(&selectors, ERC721) {
+        ContractKind::Erc721
+    } else if implements(&selectors, ERC20) {
+        ContractKind::Erc20
+    } else if delegatecall && selectors.len() <= PROXY_MAX_SELECTORS {
+        ContractKind::Proxy
+    } else {
+        ContractKind::Unknown
+    };
+    Classification {
+        kind,
+        selfdestruct: has_opcode(body, SELFDESTRUCT),
+        delegatecall_calldata_target: delegatecall && delegatecall_to_calldata(body),
+        callcode: has_opcode(body, CALLCODE),
+        selectors,
+    }
+}
+
+/// 操作码的 (出栈数, 入栈数)；未定义的操作码返回 None
+fn stack_io(op: u8) -> Option<(usize, usize)> {
+    Some(match op {
+        0x00 | JUMPDEST | 0xfe => (0, 0),
+        0x01..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d | 0x20 => (2, 1),
+        0x08 | 0x09 => (3, 1),
+        0x15 | 0x19 => (1, 1),
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x48 | 0x4a | 0x58..=0x5a | 0x5f => (0, 1),
+        0x31 | CALLDATALOAD | 0x3b | 0x3f | 0x40 | 0x49 | MLOAD | 0x54 | 0x5c => (1, 1),
+        CALLDATACOPY | 0x39 | 0x3e | 0x5e => (3, 0),
+        0x3c => (4, 0),
+        0x50 | 0x56 | SELFDESTRUCT => (1, 0),
+        0x52 | 0x53 | 0x55 | 0x57 | 0x5d | 0xf3 | 0xfd => (2, 0),
+        PUSH1..=PUSH32 => (0, 1),
+        DUP1..=DUP16 => ((op - DUP1 + 1) as usize, (op - DUP1 + 2) as usize),
+        SWAP1..=SWAP16 => ((op - SWAP1 + 2) as usize, (op - SWAP1 + 2) as usize),
+        0xa0..=0xa4 => ((op - 0xa0 + 2) as usize, 0),
+        0xf0 => (3, 1),
+        0xf1 | CALLCODE => (7, 1),
+        DELEGATECALL | 0xfa => (6, 1),
+        0xf5 => (4, 1),
+        _ => return None,
+    })
+}
+
+/// 按基本块模拟栈上的"来自 calldata"标记，判断是否有 DELEGATECALL 的目标地址（栈上第二项）
+/// 取自 CALLDATALOAD，或取自 CALLDATACOPY 写过的内存。块入口的栈与内存视为未知（不标记），
+/// 因此只能发现同一基本块内的数据流；代理常见的 `SLOAD` 取实现地址不会命中。
+fn delegatecall_to_calldata(code: &[u8]) -> bool {
+    let mut stack: Vec<bool> = vec![];
+    let mut mem_tainted = false;
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        pc += 1;
+        if (PUSH1..=PUSH32).contains(&op) {
+            pc += (op - PUSH1 + 1) as usize;
+        }
+        if op == JUMPDEST {
+            stack.clear();
+            mem_tainted = false;
+            continue;
+        }
+        let Some((pops, pushes)) = stack_io(op) else {
+            stack.clear();
+            mem_tainted = false;
+            continue;
+        };
+        let at = |stack: &Vec<bool>, i: usize| stack.len().checked_sub(i + 1).is_some_and(|j| stack[j]);
+        if op == DELEGATECALL && at(&stack, 1) {
+            return true;
+        }
+        match op {
+            DUP1..=DUP16 => {
+                let v = at(&stack, (op - DUP1) as usize);
+                stack.push(v);
+                continue;
+            }
+            SWAP1..=SWAP16 => {
+                let n = (op - SWAP1 + 1) as usize;
+                if stack.len() > n {
+                    let top = stack.len() - 1;
+                    stack.swap(top, top - n);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        let mut tainted = false;
+        for i in 0..pops {
+            tainted |= at(&stack, i);
+        }
+        stack.truncate(stack.len().saturating_sub(pops));
+        match op {
+            CALLDATALOAD => tainted = true,
+            CALLDATACOPY => mem_tainted = true,
+            MLOAD => tainted |= mem_tainted,
+            _ => {}
+        }
+        for _ in 0..pushes {
+            stack.push(tainted);
+        }
+        // 块结束：JUMP、STOP、RETURN、REVERT、INVALID、SELFDESTRUCT
+        if matches!(op, 0x56 | 0x00 | 0xf3 | 0xfd | 0xfe | SELFDESTRUCT) {
+            stack.clear();
+            mem_tainted = false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    #[test]
+    fn extracts_dispatcher_selectors_and_skips_push_data() {
+        // DUP1 PUSH4 8129fc1c EQ PUSH2 0040 JUMPI
+        let mut code = hex!("80638129fc1c1461004057").to_vec();
+        // PUSH32 whose data starts with 0x63 aabbccdd, then POP
+        code.push(0x7f);
+        code.extend_from_slice(&hex!("63aabbccdd"));
+        code.extend_from_slice(&[0u8; 27]);
+        code.push(0x50);
+        // DUP1 PUSH4 c4d66de8 EQ, then a truncated PUSH4 at the end
+        code.extend_from_slice(&hex!("8063c4d66de8146312"));
+        let sels = push4_selectors(&code);
+        assert_eq!(
+            sels.into_iter().collect::<Vec<_>>(),
+            vec![hex!("8129fc1c"), hex!("c4d66de8")]
+        );
+    }
+
+    #[test]
+    fn finds_opcodes_outside_push_data_and_metadata() {
+        // PUSH2 f4ff POP, then CBOR metadata a1 65 'bzzr0' ... with length suffix
+        let mut code = hex!("61f4ff50").to_vec();
+        assert!(!has_opcode(&code, DELEGATECALL));
+        let meta = hex!("a165627a7a7230ff");
+        code.extend_from_slice(&meta);
+        code.extend_from_slice(&(meta.len() as u16).to_be_bytes());
+        assert_eq!(strip_metadata(&code), &hex!("61f4ff50"));
+        assert!(!has_opcode(&code, SELFDESTRUCT));
+        // GAS ... DELEGATECALL
+        assert!(has_opcode(&hex!("5af4"), DELEGATECALL));
+    }
+
+    #[test]
+    fn fingerprint_ignores_metadata_and_immutables() {
+        // PUSH32 <immutable> PUSH20 <constant> CALLER, then CBOR metadata
+        let build = |immutable: u8, constant: u8, meta: &[u8]| {
+            let mut code = vec![PUSH32];
+            code.extend_from_slice(&[immutable; 32]);
+            code.push(0x73);
+            code.extend_from_slice(&[constant; 20]);
+            code.push(0x33);
+            code.extend_from_slice(meta);
+            code.extend_from_slice(&(meta.len() as u16).to_be_bytes());
+            code
+        };
+        let a = build(0x11, 0xaa, &hex!("a165627a7a7230aa"));
+        let b = build(0x22, 0xaa, &hex!("a165627a7a7230bb"));
+        assert_ne!(keccak256(&a), keccak256(&b));
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert_eq!(normalize(&a).len(), 1 + 32 + 1 + 20 + 1);
+        // PUSH20 constants are part of the template
+        assert_ne!(fingerprint(&a), fingerprint(&build(0x11, 0xbb, &hex!("a165627a7a7230aa"))));
+        // a truncated PUSH32 at the end does not panic
+        assert_eq!(normalize(&hex!("7f0102")), hex!("7f0000"));
+    }
+
+    #[test]
+    fn classifies_by_interface_and_flags_calldata_delegatecall() {
+        let dispatcher = |sigs: &[&str]| {
+            let mut code = vec![];
+            for sig in sigs {
+                // DUP1 PUSH4 <selector> EQ
+                code.extend_from_slice(&[0x80, PUSH4]);
+                code.extend_from_slice(&selector(sig));
+                code.push(0x14);
+            }
+            code
+        };
+        assert_eq!(selector("transfer(address,uint256)"), hex!("a9059cbb"));
+        assert_eq!(classify(&dispatcher(ERC20)).kind, ContractKind::Erc20);
+        assert_eq!(classify(&dispatcher(&[ERC20, ERC721].concat())).kind, ContractKind::Erc721);
+        assert_eq!(classify(&dispatcher(&[ERC20, ERC4626].concat())).kind, ContractKind::Erc4626);
+        assert_eq!(classify(&dispatcher(&[ERC20, UNISWAP_V2_PAIR].concat())).kind, ContractKind::UniswapV2Pair);
+        assert_eq!(classify(&dispatcher(ERC1155)).kind, ContractKind::Erc1155);
+        assert_eq!(classify(&dispatcher(SAFE)).kind, ContractKind::Multisig);
+        assert_eq!(classify(&dispatcher(&ERC20[..5])).kind, ContractKind::Unknown);
+
+        // EIP-1967 style: CALLDATACOPY(0,0,CALLDATASIZE) then DELEGATECALL(GAS, SLOAD(slot), 0, CALLDATASIZE, 0, 0)
+        let proxy = hex!("3660008037600080366000600054 5af4").to_vec();
+        let c = classify(&proxy);
+        assert_eq!(c.kind, ContractKind::Proxy);
+        assert!(c.dangerous_opcodes().is_empty());
+
+        // DELEGATECALL(GAS, CALLDATALOAD(4), 0, 0, 0, 0), plus CALLCODE and SELFDESTRUCT
+        let exec = hex!("600080808060043560ff16 5af4 50 f2 ff");
+        let c = classify(&exec);
+        assert!(c.delegatecall_calldata_target);
+        assert_eq!(c.dangerous_opcodes(), vec!["SELFDESTRUCT", "DELEGATECALL(calldata-target)", "CALLCODE"]);
+
+        // the taint does not survive a JUMPDEST
+        assert!(!classify(&hex!("600080808060043560ff16 5b 5af4")).delegatecall_calldata_target);
+    }
+}
@@ -1,16 +1,37 @@
 use crate::error::Result;
+use crate::fingerprints::{FingerprintIndex, Member};
 use crate::throttle;
 use alloy_primitives::{keccak256, Address, B256};
 use alloy_provider::{Provider, RootProvider};
 use alloy_transport::BoxTransport;
 use serde::Serialize;
+use serde_json::Value;
 use std::sync::Arc;
 
-use super::{Action, TxRecord};
+use super::bytecode::{self, ContractKind};
+use super::initscan::send_webhook;
+use super::{Action, ContractCreationRecord, TxRecord, initscan};
 
 #[derive(Clone, Default)]
 pub struct DeploymentOptions {
     pub output_filepath: Option<String>,
+    /// 字节码指纹索引；配置了 `fingerprint-index-filepath` 或 `fingerprint-references-filepath` 时启用
+    pub fingerprints: Option<Arc<FingerprintIndex>>,
+    pub webhook_url: Option<String>,
+}
+
+/// 解析 Deployment 的 options（工厂与 app 共用）
+pub fn parse_options(o: &Value, default_webhook: Option<&String>) -> Result<DeploymentOptions> {
+    let s = |k: &str| o.get(k).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+    let index_path = s("fingerprint-index-filepath");
+    let refs_path = s("fingerprint-references-filepath");
+    let fingerprints = if index_path.is_some() || refs_path.is_some() {
+        let index = FingerprintIndex::open(index_path.as_deref(), refs_path.as_deref())?;
+        Some(Arc::new(index))
+    } else {
+        None
+    };
+    Ok(DeploymentOptions { output_filepath: s("output-filepath"), fingerprints, webhook_url: s("webhook-url").or_else(|| default_webhook.cloned()) })
 }
 
 pub struct DeploymentScanAction {
@@ -29,14 +50,27 @@ impl Action for DeploymentScanAction {
         if let Some(addr) = t.contract_address {
             let provider = self.provider.clone();
             let opts = self.opts.clone();
+            let origin = (t.from, t.block_number);
             tokio::spawn(async move {
-                if let Err(err) = scan_code(provider, addr, &opts).await {
+                if let Err(err) = scan_code(provider, addr, None, origin, &opts).await {
                     eprintln!("[deploy-scan] error: {err}");
                 }
             });
         }
         Ok(())
     }
+
+    fn on_contract_creation(&self, c: &ContractCreationRecord) -> Result<()> {
+        let provider = self.provider.clone();
+        let opts = self.opts.clone();
+        let creation = c.clone();
+        tokio::spawn(async move {
+            if let Err(err) = scan_code(provider, creation.contract_address, Some(&creation), (None, None), &opts).await {
+                eprintln!("[deploy-scan] error: {err}");
+            }
+        });
+        Ok(())
+    }
 }
 
 #[derive(Serialize)]
@@ -51,11 +85,31 @@ struct DeploymentRecord {
     eip1167_impl: Option<String>,
     eip1967_impl_slot_ref: bool,
     eip1967_admin_slot_ref: bool,
+    classification: bytecode::Classification,
+    dangerous_opcodes: Vec<&'static str>,
+    fingerprint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deployer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    create_kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    init_code_hash: Option<String>,
 }
 
 async fn scan_code(
     provider: Arc<RootProvider<BoxTransport>>,
     addr: Address,
+    creation: Option<&ContractCreationRecord>,
+    // 只有交易回执时的 (from, 区块)
+    origin: (Option<Address>, Option<u64>),
     opts: &DeploymentOptions,
 ) -> Result<()> {
     // Fetch runtime bytecode
@@ -76,9 +130,26 @@ async fn scan_code(
         &code,
         &hex::decode("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103")?,
     );
+    let mut class = bytecode::classify(&code);
+    if class.kind == ContractKind::Unknown && (is_min_proxy || eip1967_impl_ref) {
+        class.kind = ContractKind::Proxy;
+    }
+    let dangerous = class.dangerous_opcodes();
+    let fingerprint = bytecode::fingerprint(&code);
+    let deployer = creation.map(|c| c.deployer).or(origin.0);
+    let block_number = creation.map(|c| c.block_number).or(origin.1);
+
+    // 指纹索引：空代码（构造中自毁）不入索引；同一合约重复上报时只记一次
+    let observation = match &opts.fingerprints {
+        Some(index) if !is_empty => {
+            let member = Member { contract: addr, deployer, block_number, code_hash: hash, fingerprint, code_size: len };
+            index.observe(&member)
+        }
+        _ => None,
+    };
 
     println!(
-        "[deploy-scan] contract={} code_size={} code_keccak=0x{} head=0x{} empty={} min_proxy={} impl_addr={:?} eip1967_impl_ref={} eip1967_admin_ref={}",
+        "[deploy-scan] contract={} code_size={} code_keccak=0x{} head=0x{} empty={} min_proxy={} impl_addr={:?} eip1967_impl_ref={} eip1967_admin_ref={} kind={} selectors={} dangerous=[{}] fingerprint=0x{} cluster_size={}",
         addr,
         len,
         hex::encode(hash),
@@ -88,8 +159,40 @@ async fn scan_code(
         impl_addr,
         eip1967_impl_ref,
         eip1967_admin_ref,
+        class.kind,
+        class.selectors.len(),
+        dangerous.join(","),
+        hex::encode(fingerprint),
+        observation.as_ref().map(|o| o.cluster_size.to_string()).unwrap_or_else(|| "-".to_string()),
     );
 
+    if let Some(obs) = &observation
+        && let Some((label, matched)) = &obs.label
+    {
+        let line = format!(
+            "[deploy-scan][alert] contract={addr} matches known template label={label} match={} cluster_size={} deployer={} block={} fingerprint=0x{}",
+            matched.as_str(),
+            obs.cluster_size,
+            deployer.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+            block_number.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+            hex::encode(fingerprint),
+        );
+        println!("{line}");
+        if let Some(url) = &opts.webhook_url {
+            let _ = send_webhook(url, &line).await;
+        }
+    }
+
+    // 代理的实现自身常常没有初始化：交给 initscan 探测
+    let implementation = match impl_addr {
+        Some(a) => Some(a),
+        None if eip1967_impl_ref => crate::abi_registry::resolve_proxy(&provider, addr).await.map(|t| t.implementation),
+        None => None,
+    };
+    if let Some(i) = implementation {
+        initscan::probe_implementation(addr, i, creation.map(|c| c.block_number));
+    }
+
     if let Some(path) = &opts.output_filepath {
         let rec = DeploymentRecord {
             kind: "deployment",
@@ -102,6 +205,16 @@ async fn scan_code(
             eip1167_impl: impl_addr.map(|a| format!("0x{}", hex::encode(a.0))),
             eip1967_impl_slot_ref: eip1967_impl_ref,
             eip1967_admin_slot_ref: eip1967_admin_ref,
+            classification: class,
+            dangerous_opcodes: dangerous,
+            fingerprint: format!("0x{}", hex::encode(fingerprint)),
+            cluster_size: observation.as_ref().map(|o| o.cluster_size),
+            cluster_label: observation.and_then(|o| o.label).map(|(l, _)| l),
+            deployer: deployer.map(|d| format!("0x{}", hex::encode(d))),
+            creator: creation.and_then(|c| c.creator).map(|a| format!("0x{}", hex::encode(a))),
+            create_kind: creation.map(|c| c.kind.as_str()),
+            salt: creation.and_then(|c| c.salt).map(|s| format!("0x{}", hex::encode(s))),
+            init_code_hash: creation.and_then(|c| c.init_code_hash).map(|h| format!("0x{}", hex::encode(h))),
         };
         if let Ok(s) = serde_json::to_string(&rec) {
             let _ = append_line(path, &s);
@@ -110,7 +223,7 @@ async fn scan_code(
     Ok(())
 }
 
-fn detect_eip1167_minimal_proxy(code: &[u8]) -> (bool, Option<Address>) {
+pub fn detect_eip1167_minimal_proxy(code: &[u8]) -> (bool, Option<Address>) {
     // EIP-1167 minimal proxy runtime bytecode pattern:
     // 0x363d3d373d3d3d363d73 <20-byte implementation> 0x5af43d82803e903d91602b57fd5bf3
     const PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
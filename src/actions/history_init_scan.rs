@@ -31,7 +31,7 @@ pub async fn run(
     let initscan = Arc::new(InitscanAction::new(
         Arc::clone(&provider),
         opts.initscan.clone(),
-    ));
+    )?);
     let from = opts.from_block;
     let to = opts.to_block;
     let total = to.saturating_sub(from).saturating_add(1);
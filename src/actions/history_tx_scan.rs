@@ -145,7 +145,7 @@ mod tests {
     async fn test_fetch_first_20_traces() {
         let rpcurl = "ws://192.168.2.58:8646";
         let provider =
-            Arc::new(provider::connect_ws_provider(rpcurl).await.expect("Failed to connect to provider"));
+            Arc::new(provider::connect_auto(rpcurl).await.expect("Failed to connect to provider"));
 
         let file = std::fs::File::open("data/null.json").expect("Failed to open null.json");
         let reader = io::BufReader::new(file);
@@ -1,15 +1,28 @@
 use base64::Engine;
-use std::{collections::HashSet, fs, path::Path, sync::Arc, time::Duration};
+use std::{collections::{BTreeSet, HashMap, HashSet}, fs, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::Duration};
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256, Bytes, I256, U256, b256};
+use alloy_dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier};
+use alloy_json_abi::Function;
+use crate::{abi, abi_registry};
+use super::bytecode;
 use alloy_provider::{Provider, RootProvider};
 use alloy_transport::BoxTransport;
 use crate::error::AppError;
+use crate::findings::{self, FindingStatus, FindingStore, RecordOutcome};
+use crate::pricing::{self, PriceOracle};
+use crate::throttle;
+use crate::token_meta::{self, TokenMetaService};
+use alloy_rpc_types_eth::{BlockId, Filter, TransactionRequest, transaction::TransactionInput};
 type Result<T> = std::result::Result<T, AppError>;
 use serde::{Deserialize, Serialize};
 
-use super::{Action, TxRecord};
-use tokio::sync::{RwLock, Semaphore};
+use super::{Action, ContractCreationRecord, TxRecord};
+use tokio::sync::{OnceCell, RwLock, Semaphore};
+use std::str::FromStr;
+use crate::source::Source;
+use alloy_rpc_types::trace::geth::DiffMode;
+use alloy_rpc_types::trace::parity::{Delta, StateDiff as ParityStateDiff};
 
 #[derive(Clone, Debug, Default)]
 pub struct InitscanOptions {
@@ -17,7 +30,15 @@ pub struct InitscanOptions {
     pub check_addresses: Vec<Address>,
     pub init_after_delay_secs: u64,
     pub usd_threshold: f64,
+    // extra tokens to value besides pricing quote tokens / discovered ones
+    pub value_tokens: Vec<Address>,
+    // discover held tokens from incoming Transfer logs in the last N blocks; 0 => disabled
+    pub value_scan_blocks: u64,
     pub func_sigs: Vec<(String, Vec<u8>)>,
+    // also probe initializer-like functions from func_sigs.json found in the bytecode dispatcher
+    pub discover_init_functions: bool,
+    // write a reproduction artifact (JSON + Foundry test) per finding next to the known-contracts file
+    pub reproduction_artifacts: bool,
     pub webhook_url: Option<String>,
     // persistence + retry
     pub initializable_contracts_filepath: Option<String>,
@@ -26,6 +47,33 @@ pub struct InitscanOptions {
     pub max_inflight_inits: Option<usize>,
     // enable verbose debug logs
     pub debug: bool,
+    // how to obtain the stateDiff of a simulated init call
+    pub trace_backend: InitscanTraceBackend,
+}
+
+/// Source of the simulated call's state diff
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InitscanTraceBackend {
+    /// probe trace_call first, then debug_traceCall; remember the first that works
+    #[default]
+    Auto,
+    /// Parity/Erigon `trace_call` with `stateDiff`
+    TraceCall,
+    /// geth `debug_traceCall` with `prestateTracer` in diffMode
+    DebugTraceCall,
+}
+
+impl FromStr for InitscanTraceBackend {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "trace_call" | "trace" | "statediff" => Ok(Self::TraceCall),
+            "debug_tracecall" | "debug" | "prestate" => Ok(Self::DebugTraceCall),
+            other => Err(AppError::Config(format!("unknown initscan trace backend: {other}"))),
+        }
+    }
 }
 
 pub struct InitscanAction {
@@ -33,10 +81,59 @@ pub struct InitscanAction {
     opts: InitscanOptions,
     known: Arc<RwLock<Vec<KnownInit>>>,
     sem: Option<Arc<Semaphore>>,
+    pricing: Arc<PriceOracle>,
+    tokens: Arc<TokenMetaService>,
+    tracer: CallTracer,
+    // database-backed findings; the JSON known list is used when not configured
+    findings: Option<Arc<FindingStore>>,
+    // implementations already handed over by proxy / deployment detection
+    probed_impls: Arc<Mutex<HashSet<Address>>>,
+    // periodic retry loop; only the action handed to the ActionSet owns it and aborts it on drop
+    retry_task: Option<tokio::task::AbortHandle>,
+}
+
+impl Drop for InitscanAction {
+    fn drop(&mut self) {
+        if let Some(h) = &self.retry_task {
+            h.abort();
+        }
+    }
+}
+
+// Known lists by file path, shared by every action built for that path (config reloads rebuild the
+// action while tasks of the previous one may still be saving)
+type KnownList = Arc<RwLock<Vec<KnownInit>>>;
+static KNOWN_LISTS: Mutex<Option<HashMap<String, KnownList>>> = Mutex::new(None);
+
+fn shared_known_list(path: &str) -> KnownList {
+    let mut lists = KNOWN_LISTS.lock().unwrap_or_else(|e| e.into_inner());
+    lists
+        .get_or_insert_with(HashMap::new)
+        .entry(path.to_string())
+        .or_insert_with(|| Arc::new(RwLock::new(load_known_from_file(path).unwrap_or_default())))
+        .clone()
+}
+
+// The running initscan action, used to probe implementations reported by proxy / deployment detection;
+// replaced whenever a new action is built (config reload)
+static IMPL_PROBER: Mutex<Option<InitscanAction>> = Mutex::new(None);
+
+/// Outcome of re-checking a stored finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verification {
+    /// still initializable by anyone
+    Qualifies,
+    /// the call now reverts: someone initialized it
+    Reverts,
+    /// the call succeeds but no longer hands over a slot
+    NoLongerQualifies,
 }
 
+// cap on traces fetched when looking up who initialized a finding
+const TRACE_FILTER_MAX_RESULTS: u64 = 1000;
+
 impl InitscanAction {
-    pub fn new(provider: Arc<RootProvider<BoxTransport>>, mut opts: InitscanOptions) -> Self {
+    pub fn new(provider: Arc<RootProvider<BoxTransport>>, mut opts: InitscanOptions) -> Result<Self> {
         // Ensure from address appended to check_addresses if provided
         if let Some(f) = opts.from {
             if !opts.check_addresses.contains(&f) {
@@ -44,7 +141,7 @@ impl InitscanAction {
             }
         }
         let known = if let Some(p) = &opts.initializable_contracts_filepath {
-            Arc::new(RwLock::new(load_known_from_file(p).unwrap_or_default()))
+            shared_known_list(p)
         } else {
             Arc::new(RwLock::new(vec![]))
         };
@@ -53,107 +150,218 @@ impl InitscanAction {
             .max_inflight_inits
             .and_then(|n| if n > 0 { Some(Arc::new(Semaphore::new(n))) } else { None });
 
-        let action = Self { provider: provider.clone(), opts: opts.clone(), known: known.clone(), sem };
-
-        if let (Some(path), Some(freq)) = (
-            opts.initializable_contracts_filepath.clone(),
-            opts.init_known_contracts_frequency_secs,
-        ) {
-            if freq > 0 {
-                let this = action.clone_for_task();
-                tokio::spawn(async move {
-                    let dur = Duration::from_secs(freq);
-                    loop {
-                        tokio::time::sleep(dur).await;
-                        if let Err(e) = this.retry_known_and_save(&path).await {
-                            eprintln!("[initscan] periodic retry error: {e}");
-                        }
+        let pricing = pricing::shared()?;
+        let tokens = token_meta::shared()?;
+        let tracer = CallTracer::new(provider.clone(), opts.trace_backend, "initscan");
+        let findings = findings::global().cloned();
+        let has_store = findings.is_some() || opts.initializable_contracts_filepath.is_some();
+        let mut action = Self { provider: provider.clone(), opts: opts.clone(), known: known.clone(), sem, pricing, tokens, tracer, findings, probed_impls: Arc::new(Mutex::new(HashSet::new())), retry_task: None };
+        *IMPL_PROBER.lock().unwrap_or_else(|e| e.into_inner()) = Some(action.clone_for_task());
+
+        if let Some(freq) = opts.init_known_contracts_frequency_secs
+            && freq > 0
+            && has_store
+        {
+            let this = action.clone_for_task();
+            let task = tokio::spawn(async move {
+                let dur = Duration::from_secs(freq);
+                loop {
+                    tokio::time::sleep(dur).await;
+                    if let Err(e) = this.retry_known().await {
+                        eprintln!("[initscan] periodic retry error: {e}");
                     }
-                });
-            }
+                }
+            });
+            action.retry_task = Some(task.abort_handle());
         }
 
-        action
+        Ok(action)
     }
 
-    fn clone_for_task(&self) -> Self { Self { provider: self.provider.clone(), opts: self.opts.clone(), known: self.known.clone(), sem: self.sem.clone() } }
+    fn clone_for_task(&self) -> Self { Self { provider: self.provider.clone(), opts: self.opts.clone(), known: self.known.clone(), sem: self.sem.clone(), pricing: self.pricing.clone(), tokens: self.tokens.clone(), tracer: self.tracer.clone(), findings: self.findings.clone(), probed_impls: self.probed_impls.clone(), retry_task: None } }
     #[inline]
     fn dbg<S: AsRef<str>>(&self, s: S) { if self.opts.debug { println!("[initscan][debug] {}", s.as_ref()); } }
 
+    /// Re-verify stored findings: the database when configured, else the JSON known list
+    pub async fn retry_known(&self) -> Result<()> {
+        if let Some(store) = &self.findings {
+            return self.reverify_findings(store).await;
+        }
+        match &self.opts.initializable_contracts_filepath {
+            Some(path) => self.retry_known_and_save(path).await,
+            None => Ok(()),
+        }
+    }
+
     pub async fn retry_known_and_save(&self, path: &str) -> Result<()> {
         let snapshot = { self.known.read().await.clone() };
         if snapshot.is_empty() { return Ok(()); }
         if self.opts.debug { println!("[initscan][debug] retry_known_and_save: {} entries", snapshot.len()); }
-        let mut kept: Vec<KnownInit> = Vec::with_capacity(snapshot.len());
-        for item in snapshot.iter() {
-            match self.evaluate_once(item.contract, None, &item.calldata).await {
-                Ok(true) => kept.push(item.clone()),
-                Ok(false) => { /* drop */ }
-                Err(e) => { eprintln!("[initscan] retry error on {:?}: {e}", item.contract); kept.push(item.clone()); }
+        let mut closed = vec![];
+        // closed entries stay in the file so a rediscovery does not reopen them
+        for item in snapshot.iter().filter(|k| k.status == FindingStatus::Open) {
+            let status = match self.verify(item.contract, None, &item.calldata).await {
+                Ok(Verification::Qualifies) => continue,
+                Ok(Verification::Reverts) => FindingStatus::Initialized,
+                Ok(Verification::NoLongerQualifies) => FindingStatus::Expired,
+                Err(e) => { eprintln!("[initscan] retry error on {:?}: {e}", item.contract); continue }
+            };
+            closed.push((item.contract, item.calldata.clone(), status));
+        }
+        // update in place: entries added while verifying must survive
+        let mut w = self.known.write().await;
+        for (contract, calldata, status) in closed {
+            if let Some(k) = w.iter_mut().find(|k| k.contract == contract && k.calldata == calldata) {
+                k.status = status;
             }
         }
-        {
-            let mut w = self.known.write().await;
-            *w = kept.clone();
+        save_known_to_file(path, &w)?;
+        Ok(())
+    }
+
+    async fn reverify_findings(&self, store: &FindingStore) -> Result<()> {
+        let open = store.open_findings().await?;
+        self.dbg(format!("reverify_findings: {} open", open.len()));
+        for f in &open {
+            let tag = format!("0x{}", hex::encode(f.contract.0));
+            match self.verify(f.contract, None, &f.calldata).await {
+                Ok(Verification::Qualifies) => store.touch(f.contract, &f.calldata).await?,
+                Ok(Verification::Reverts) => {
+                    let by = self.find_initializer(f.contract, &f.calldata, f.block_number).await;
+                    println!("[initscan] {} initialized by {}", tag, by.map(|a| a.to_string()).unwrap_or_else(|| "unknown".to_string()));
+                    store.close(f.contract, &f.calldata, FindingStatus::Initialized, by).await?;
+                }
+                Ok(Verification::NoLongerQualifies) => {
+                    println!("[initscan] {} no longer initializable; expired", tag);
+                    store.close(f.contract, &f.calldata, FindingStatus::Expired, None).await?;
+                }
+                Err(e) => eprintln!("[initscan] retry error on {}: {e}", tag),
+            }
         }
-        save_known_to_file(path, &kept)?;
         Ok(())
     }
 
-    async fn add_known_and_save(&self, contract: Address, calldata: &[u8]) -> Result<()> {
+    /// Best-effort lookup (trace_filter) of the first successful call to `contract` with the finding's selector
+    async fn find_initializer(&self, contract: Address, calldata: &[u8], from_block: Option<u64>) -> Option<Address> {
+        let selector = format!("0x{}", hex::encode(calldata.get(..4)?));
+        let filter = serde_json::json!([{
+            "fromBlock": format!("0x{:x}", from_block.unwrap_or_default()),
+            "toBlock": "latest",
+            "toAddress": [format!("0x{}", hex::encode(contract.0))],
+            "count": TRACE_FILTER_MAX_RESULTS,
+        }]);
+        throttle::acquire().await;
+        let traces: Vec<serde_json::Value> = match self.provider.client().request("trace_filter", filter).await {
+            Ok(v) => v,
+            Err(e) => { self.dbg(format!("trace_filter unavailable: {e}")); return None; }
+        };
+        traces.iter()
+            .filter(|t| t.get("error").is_none())
+            .filter_map(|t| t.get("action"))
+            .find(|a| a.get("input").and_then(|v| v.as_str()).is_some_and(|i| i.to_ascii_lowercase().starts_with(&selector)))
+            .and_then(|a| a.get("from")?.as_str()?.parse().ok())
+    }
+
+    /// Persist the finding (always `New` without a store)
+    async fn add_known_and_save(&self, contract: Address, block_number: Option<u64>, calldata: &[u8], value_usd: f64) -> Result<RecordOutcome> {
+        if let Some(store) = &self.findings {
+            let outcome = store.record_open(contract, calldata, block_number, Some(value_usd)).await?;
+            if outcome == RecordOutcome::New {
+                println!("[initscan] recorded finding 0x{}", hex::encode(contract.0));
+            }
+            return Ok(outcome);
+        }
         if let Some(path) = &self.opts.initializable_contracts_filepath {
             let mut w = self.known.write().await;
-            if !w.iter().any(|k| k.contract == contract) {
-                w.push(KnownInit { contract, calldata: calldata.to_vec() });
-                save_known_to_file(path, &w)?;
+            let outcome = upsert_known(&mut w, contract, calldata);
+            if outcome == RecordOutcome::Closed { return Ok(outcome); }
+            save_known_to_file(path, &w)?;
+            if outcome == RecordOutcome::New {
                 println!("[initscan] added {} to known list", format!("0x{}", hex::encode(contract.0)));
-                self.dbg(format!("persisted to {} ({} entries)", path, w.len()));
             }
+            self.dbg(format!("persisted to {} ({} entries)", path, w.len()));
+            return Ok(outcome);
         }
-        Ok(())
+        Ok(RecordOutcome::New)
     }
 
-    async fn try_init_with_calldata(&self, contract: Address, block_number: Option<u64>, calldata: &[u8]) -> Result<()> {
+    /// `proxy`: set when `contract` is the implementation behind it; the value at risk is then what the proxy holds
+    async fn try_init_with_calldata(&self, contract: Address, proxy: Option<Address>, block_number: Option<u64>, calldata: &[u8]) -> Result<()> {
         // Eth call check
         self.dbg(format!("try_init_with_calldata: contract=0x{} block={:?} calldata_len={} head=0x{}", hex::encode(contract.0), block_number, calldata.len(), hex::encode(&calldata[..calldata.len().min(8)])));
         let ok = eth_call_ok(self.provider.as_ref(), self.opts.from, contract, calldata, block_number).await?;
         self.dbg(format!("eth_call ok={} (no revert)", ok));
         if !ok { return Ok(()); }
         // Trace
-        let tr = trace_call(self.provider.as_ref(), self.opts.from, contract, calldata, block_number).await?;
-        self.dbg(format!("trace_call returned: traces={} state_diff_len={}", tr.traces.len(), serde_json::to_string(&tr.state_diff).unwrap_or_default().len()));
-        if !trace_success(&tr) { self.dbg("trace_call had error in traces"); return Ok(()); }
-        let contains = state_diff_contains_any_addr(&tr, &self.opts.check_addresses);
-        self.dbg(format!("stateDiff contains check address = {}", contains));
-        if !contains { return Ok(()); }
+        let Some(diff) = self.tracer.state_diff(self.opts.from, contract, calldata, block_number).await? else { self.dbg("trace had error in traces"); return Ok(()); };
+        self.dbg(format!("state diff returned: accounts={}", diff.storage.len()));
+        let slots = slots_now_holding(&diff, contract, &self.opts.check_addresses);
+        self.dbg(format!("stateDiff slots of target now holding a check address = {}", slots.len()));
+        if slots.is_empty() { return Ok(()); }
         // Fallback sanity
         let random_sel = hex::decode("6fcb831b").unwrap_or_default();
-        let tr2 = trace_call(self.provider.as_ref(), self.opts.from, contract, &random_sel, block_number).await?;
-        let contains2 = trace_success(&tr2) && state_diff_contains_any_addr(&tr2, &self.opts.check_addresses);
+        let contains2 = self.tracer.state_diff(self.opts.from, contract, &random_sel, block_number).await?
+            .is_some_and(|d| !slots_now_holding(&d, contract, &self.opts.check_addresses).is_empty());
         self.dbg(format!("random selector check contains = {}", contains2));
-        if contains && !contains2 {
-            // Passed heuristics: alert + persist
-            let msg = format!(
-                "# Interesting contract\nAddress: 0x{}\ncalldataLen: {}\n",
-                hex::encode(contract.0), calldata.len()
-            );
+        if !contains2 {
+            // Passed heuristics: value what is at stake; persist and alert if >= usd_threshold.
+            // Findings below the threshold are not stored, so they count as new once they cross it
+            let (total_usd, holdings) = self.value_at_risk(proxy.unwrap_or(contract)).await;
+            self.dbg(format!("value_at_risk_usd={:.2} threshold={} holdings={}", total_usd, self.opts.usd_threshold, holdings.len()));
+            if total_usd < self.opts.usd_threshold {
+                return Ok(());
+            }
+            let outcome = match self.add_known_and_save(contract, block_number, calldata, total_usd).await {
+                Ok(outcome) => outcome,
+                Err(e) => { eprintln!("[initscan] failed to persist finding 0x{}: {e}", hex::encode(contract.0)); RecordOutcome::New }
+            };
+            // a closed finding was already initialized or expired: nothing to report
+            if outcome == RecordOutcome::Closed {
+                self.dbg("finding already closed, not alerting");
+                return Ok(());
+            }
+            // only new findings get an artifact; open ones already have one
+            let repro = if self.opts.reproduction_artifacts && outcome == RecordOutcome::New {
+                match self.write_repro(contract, block_number, calldata, &slots, &diff, total_usd).await {
+                    Ok(p) => Some(p),
+                    Err(e) => { eprintln!("[initscan] failed to write reproduction for 0x{}: {e}", hex::encode(contract.0)); None }
+                }
+            } else {
+                None
+            };
+            let mut msg = match proxy {
+                Some(p) => format!("# Uninitialized implementation\nProxy: 0x{}\nImplementation: 0x{}\n", hex::encode(p.0), hex::encode(contract.0)),
+                None => format!("# Interesting contract\nAddress: 0x{}\n", hex::encode(contract.0)),
+            };
+            msg.push_str(&format!("calldataLen: {}\nvalueAtRiskUsd: {:.2}\n", calldata.len(), total_usd));
+            msg.push_str("changedSlots:\n");
+            for c in &slots {
+                msg.push_str(&format!("  - {} {} -> {}\n", c.slot, fmt_slot_value(c.from), fmt_slot_value(c.to)));
+            }
+            if let Some(p) = &repro {
+                msg.push_str(&format!("reproduction: {}\n", p.display()));
+            }
+            msg.push_str("holdings:\n");
+            for h in &holdings {
+                match h.usd {
+                    Some(v) => msg.push_str(&format!("  - {} {} (~${:.2})\n", h.asset, h.amount, v)),
+                    None => msg.push_str(&format!("  - {} {} (unpriced)\n", h.asset, h.amount)),
+                }
+            }
             if let Some(url) = &self.opts.webhook_url { self.dbg(format!("sending webhook to {}", url)); let _ = send_webhook(url, &msg).await; } else { println!("[initscan] {}", msg.replace('\n', " ")); }
-            let _ = self.add_known_and_save(contract, calldata).await;
         }
         Ok(())
     }
 
-    async fn evaluate_once(&self, contract: Address, block_number: Option<u64>, calldata: &[u8]) -> Result<bool> {
+    async fn verify(&self, contract: Address, block_number: Option<u64>, calldata: &[u8]) -> Result<Verification> {
         let ok = eth_call_ok(self.provider.as_ref(), self.opts.from, contract, calldata, block_number).await?;
-        if !ok { return Ok(false); }
-        let tr = trace_call(self.provider.as_ref(), self.opts.from, contract, calldata, block_number).await?;
-        if !trace_success(&tr) { return Ok(false); }
-        let contains = state_diff_contains_any_addr(&tr, &self.opts.check_addresses);
-        if !contains { return Ok(false); }
+        if !ok { return Ok(Verification::Reverts); }
+        let Some(diff) = self.tracer.state_diff(self.opts.from, contract, calldata, block_number).await? else { return Ok(Verification::Reverts) };
+        if slots_now_holding(&diff, contract, &self.opts.check_addresses).is_empty() { return Ok(Verification::NoLongerQualifies); }
         let random_sel = hex::decode("6fcb831b").unwrap_or_default();
-        let tr2 = trace_call(self.provider.as_ref(), self.opts.from, contract, &random_sel, block_number).await?;
-        let contains2 = trace_success(&tr2) && state_diff_contains_any_addr(&tr2, &self.opts.check_addresses);
-        Ok(contains && !contains2)
+        let contains2 = self.tracer.state_diff(self.opts.from, contract, &random_sel, block_number).await?
+            .is_some_and(|d| !slots_now_holding(&d, contract, &self.opts.check_addresses).is_empty());
+        Ok(if contains2 { Verification::NoLongerQualifies } else { Verification::Qualifies })
     }
 
     // Public helper for external callers (e.g. history scanner)
@@ -163,57 +371,516 @@ impl InitscanAction {
             Some(s) => Some(s.clone().acquire_owned().await.expect("semaphore closed")),
             None => None,
         };
-        self.dbg(format!("try_init_for_contract: contract=0x{} block={:?} func_variants={}", hex::encode(contract.0), block_number, self.opts.func_sigs.len()));
         if self.opts.init_after_delay_secs > 0 {
             tokio::time::sleep(Duration::from_secs(self.opts.init_after_delay_secs)).await;
         }
-        for (_sig, calldata) in &self.opts.func_sigs {
+        let candidates = self.init_candidates(contract, block_number).await;
+        self.dbg(format!("try_init_for_contract: contract=0x{} block={:?} func_variants={}", hex::encode(contract.0), block_number, candidates.len()));
+        for (_sig, calldata) in &candidates {
             let _ = self
-                .try_init_with_calldata(contract, block_number, calldata)
+                .try_init_with_calldata(contract, None, block_number, calldata)
                 .await;
         }
         drop(_permit);
     }
 }
 
+// Init-function candidates
+// proxies have tiny runtime code; only then is it worth resolving an implementation
+const PROXY_CODE_MAX_LEN: usize = 2048;
+
+impl InitscanAction {
+    /// Calldata to probe: the configured `func_sigs` (always, even when the selector is not visible
+    /// in the bytecode) plus, if enabled, initializer-like functions from func_sigs.json whose
+    /// selectors appear in the contract's (or its implementation's) dispatcher.
+    async fn init_candidates(&self, contract: Address, block_number: Option<u64>) -> Vec<(String, Vec<u8>)> {
+        if !self.opts.discover_init_functions {
+            return self.opts.func_sigs.clone();
+        }
+        let selectors = implemented_selectors(self.provider.as_ref(), contract, block_number).await;
+        candidate_calls(&self.opts.func_sigs, &selectors, &abi::sigs().funcs, self.opts.from, |sig| {
+            self.dbg(format!("skip {sig}: unsupported argument types"))
+        })
+    }
+}
+
+/// `configured` unchanged, followed by initializer-like entries of `funcs` for the dispatcher
+/// `selectors` that are not configured already
+fn candidate_calls(
+    configured: &[(String, Vec<u8>)],
+    selectors: &BTreeSet<[u8; 4]>,
+    funcs: &abi::FuncSigMap,
+    from: Option<Address>,
+    mut unsupported: impl FnMut(&str),
+) -> Vec<(String, Vec<u8>)> {
+    let mut out = configured.to_vec();
+    for sel in selectors {
+        if out.iter().any(|(_, data)| data.starts_with(sel)) {
+            continue;
+        }
+        let Some(entry) = funcs.get(&format!("0x{}", hex::encode(sel))) else { continue };
+        if !looks_like_initializer(&entry.name) {
+            continue;
+        }
+        match synthesize_calldata(&entry.abi, from, U256::ZERO) {
+            Some(data) => out.push((entry.sig.clone(), data)),
+            None => unsupported(&entry.sig),
+        }
+    }
+    out
+}
+
+/// PUSH4 selectors of the contract's runtime code, plus those of its implementation when it looks like a proxy
+pub(crate) async fn implemented_selectors(provider: &RootProvider<BoxTransport>, contract: Address, block_number: Option<u64>) -> BTreeSet<[u8; 4]> {
+    throttle::acquire().await;
+    let code = match block_number {
+        Some(b) => provider.get_code_at(contract).block_id(BlockId::number(b)).await,
+        None => provider.get_code_at(contract).await,
+    };
+    let Ok(code) = code else { return BTreeSet::new() };
+    let mut selectors = bytecode::push4_selectors(&code);
+    if !code.is_empty() && code.len() <= PROXY_CODE_MAX_LEN
+        && let Some(target) = abi_registry::resolve_proxy(provider, contract).await
+    {
+        throttle::acquire().await;
+        if let Ok(impl_code) = provider.get_code_at(target.implementation).await {
+            tracing::debug!("0x{} proxies to {}", hex::encode(contract.0), target.implementation);
+            selectors.extend(bytecode::push4_selectors(&impl_code));
+        }
+    }
+    selectors
+}
+
+fn looks_like_initializer(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    n.starts_with("init") || n.starts_with("setup") || n == "setowner"
+}
+
+/// ABI-encode a call with placeholder arguments: `from` for addresses, `amount` for unsigned integers,
+/// zero / false / empty otherwise.
+/// Returns None for argument types that cannot be filled sensibly (tuples, fixed arrays, ...).
+pub(crate) fn synthesize_calldata(func: &Function, from: Option<Address>, amount: U256) -> Option<Vec<u8>> {
+    let values = func.inputs.iter()
+        .map(|p| p.resolve().ok().and_then(|ty| placeholder_value(&ty, from.unwrap_or_default(), amount)))
+        .collect::<Option<Vec<_>>>()?;
+    func.abi_encode_input(&values).ok()
+}
+
+fn placeholder_value(ty: &DynSolType, from: Address, amount: U256) -> Option<DynSolValue> {
+    Some(match ty {
+        DynSolType::Address => DynSolValue::Address(from),
+        DynSolType::Bool => DynSolValue::Bool(false),
+        DynSolType::Uint(bits) => DynSolValue::Uint(amount, *bits),
+        DynSolType::Int(bits) => DynSolValue::Int(I256::ZERO, *bits),
+        DynSolType::FixedBytes(size) => DynSolValue::FixedBytes(B256::ZERO, *size),
+        DynSolType::Bytes => DynSolValue::Bytes(vec![]),
+        DynSolType::String => DynSolValue::String(String::new()),
+        DynSolType::Array(inner) if **inner == DynSolType::Address => DynSolValue::Array(vec![DynSolValue::Address(from)]),
+        DynSolType::Array(_) => DynSolValue::Array(vec![]),
+        _ => return None,
+    })
+}
+
+// State diff backends
+/// Simulates calls and returns their state diff through the configured backend
+/// (Auto is probed once on first use); shared by initscan and privscan
+#[derive(Clone)]
+pub(crate) struct CallTracer {
+    provider: Arc<RootProvider<BoxTransport>>,
+    source: Source,
+    backend: Arc<OnceCell<InitscanTraceBackend>>,
+    tag: &'static str,
+}
+
+impl CallTracer {
+    pub(crate) fn new(provider: Arc<RootProvider<BoxTransport>>, backend: InitscanTraceBackend, tag: &'static str) -> Self {
+        let source = Source::from_provider((*provider).clone(), 0);
+        let backend = match backend {
+            InitscanTraceBackend::Auto => OnceCell::new(),
+            b => OnceCell::new_with(Some(b)),
+        };
+        Self { provider, source, backend: Arc::new(backend), tag }
+    }
+
+    /// State changes of simulating `calldata` against `to`; `None` if the trace reported an error
+    pub(crate) async fn state_diff(&self, from: Option<Address>, to: Address, calldata: &[u8], block_number: Option<u64>) -> Result<Option<StateDiff>> {
+        let backend = *self.backend.get_or_try_init(|| self.probe_backend(from, to, block_number)).await?;
+        match backend {
+            InitscanTraceBackend::DebugTraceCall => {
+                // the prestate diff carries no revert marker; ask callTracer for the top-level error
+                let error = self.source
+                    .geth_debug_trace_call_error(call_request(from, to, calldata), block_number)
+                    .await
+                    .map_err(|e| AppError::General(format!("debug_traceCall: {e}")))?;
+                if error.is_some() { return Ok(None); }
+                let diff = self.source
+                    .geth_debug_trace_call_diff(call_request(from, to, calldata), block_number)
+                    .await
+                    .map_err(|e| AppError::General(format!("debug_traceCall: {e}")))?;
+                Ok(Some(state_diff_from_geth(&diff)))
+            }
+            _ => {
+                let tr = trace_call(self.provider.as_ref(), from, to, calldata, block_number).await?;
+                Ok(trace_success(&tr).then(|| parse_state_diff(&tr.state_diff)))
+            }
+        }
+    }
+
+    /// Only a definite answer is returned as `Ok` (and cached); a transient error on either
+    /// method is returned as-is so the next call probes again
+    async fn probe_backend(&self, from: Option<Address>, to: Address, block_number: Option<u64>) -> Result<InitscanTraceBackend> {
+        match trace_call(self.provider.as_ref(), from, to, &[], block_number).await {
+            Ok(_) => {
+                println!("[{}] using trace_call stateDiff", self.tag);
+                return Ok(InitscanTraceBackend::TraceCall);
+            }
+            Err(e) if !e.is_method_unsupported() => return Err(e),
+            Err(_) => {}
+        }
+        match self.source.geth_debug_trace_call_diff(call_request(from, to, &[]), block_number).await {
+            Ok(_) => {
+                println!("[{}] using debug_traceCall prestate diffs", self.tag);
+                return Ok(InitscanTraceBackend::DebugTraceCall);
+            }
+            Err(e) if !e.is_method_unsupported() => return Err(AppError::from(e)),
+            Err(_) => {}
+        }
+        eprintln!("[{}] neither trace_call nor debug_traceCall is available; defaulting to trace_call", self.tag);
+        Ok(InitscanTraceBackend::TraceCall)
+    }
+
+    /// State changes of every transaction in `block` (tx hash, diff), in block order.
+    /// With Auto the backend is picked by whichever block-level method answers first; a method
+    /// is skipped only when the node says it is unsupported, other errors are returned for retry.
+    pub(crate) async fn block_state_diffs(&self, block: u64) -> Result<Vec<(Option<B256>, StateDiff)>> {
+        if let Some(backend) = self.backend.get() {
+            return self.block_state_diffs_with(*backend, block).await;
+        }
+        for backend in [InitscanTraceBackend::TraceCall, InitscanTraceBackend::DebugTraceCall] {
+            match self.block_state_diffs_with(backend, block).await {
+                Ok(out) => {
+                    if self.backend.set(backend).is_ok() {
+                        println!("[{}] using {} block state diffs", self.tag, block_method(backend));
+                    }
+                    return Ok(out);
+                }
+                Err(e) if !e.is_method_unsupported() => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Err(AppError::General(format!(
+            "[{}] neither trace_replayBlockTransactions nor debug_traceBlockByNumber is available",
+            self.tag
+        )))
+    }
+
+    async fn block_state_diffs_with(&self, backend: InitscanTraceBackend, block: u64) -> Result<Vec<(Option<B256>, StateDiff)>> {
+        match backend {
+            InitscanTraceBackend::DebugTraceCall => {
+                let (_, txs, diffs) = self.source.geth_debug_trace_block_diffs(block as u32, true).await.map_err(AppError::from)?;
+                Ok(diffs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| (txs.get(i).cloned().flatten().map(|h| B256::from_slice(&h)), state_diff_from_geth(d)))
+                    .collect())
+            }
+            _ => {
+                let (_, _, traces) = self.source.trace_block_state_diffs(block as u32, false).await.map_err(AppError::from)?;
+                Ok(traces
+                    .iter()
+                    .map(|t| (Some(t.transaction_hash), t.full_trace.state_diff.as_ref().map(state_diff_from_parity).unwrap_or_default()))
+                    .collect())
+            }
+        }
+    }
+}
+
+fn block_method(backend: InitscanTraceBackend) -> &'static str {
+    match backend {
+        InitscanTraceBackend::DebugTraceCall => "debug_traceBlockByNumber",
+        _ => "trace_replayBlockTransactions",
+    }
+}
+
+fn call_request(from: Option<Address>, to: Address, data: &[u8]) -> TransactionRequest {
+    let mut req = TransactionRequest::default()
+        .to(to)
+        .input(TransactionInput::new(Bytes::copy_from_slice(data)));
+    req.from = from;
+    req
+}
+
+// Reproduction artifacts
+const REPRO_DIR: &str = "initscan_repro";
+
+/// Everything needed to replay a finding, written as `<contract>.json` beside the generated test
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReproArtifact<'a> {
+    target: Address,
+    fork_block: u64,
+    sender: Address,
+    calldata: Bytes,
+    changed_slots: &'a [SlotChange],
+    state_diff: &'a StateDiff,
+    value_at_risk_usd: f64,
+    test_file: String,
+}
+
+impl InitscanAction {
+    /// `initscan_repro/` next to the known-contracts file (under ./data without one)
+    fn repro_dir(&self) -> PathBuf {
+        let base = self.opts.initializable_contracts_filepath.as_deref()
+            .and_then(|p| Path::new(p).parent())
+            .filter(|p| !p.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("./data"));
+        base.join(REPRO_DIR)
+    }
+
+    /// Write the JSON artifact and Foundry test for a finding; returns the test file path
+    async fn write_repro(&self, contract: Address, block_number: Option<u64>, calldata: &[u8], slots: &[SlotChange], diff: &StateDiff, value_usd: f64) -> Result<PathBuf> {
+        // the simulation ran against the state after `block_number`, which is what a fork at that block sees
+        let fork_block = match block_number {
+            Some(b) => b,
+            None => { throttle::acquire().await; self.provider.get_block_number().await? }
+        };
+        let sender = self.opts.from.unwrap_or_default();
+        let dir = self.repro_dir();
+        fs::create_dir_all(&dir)?;
+        let stem = format!("0x{}", hex::encode(contract.0));
+        let test_path = dir.join(format!("{stem}.t.sol"));
+        fs::write(&test_path, render_foundry_test(contract, fork_block, sender, calldata, slots))?;
+        let artifact = ReproArtifact {
+            target: contract,
+            fork_block,
+            sender,
+            calldata: Bytes::copy_from_slice(calldata),
+            changed_slots: slots,
+            state_diff: diff,
+            value_at_risk_usd: value_usd,
+            test_file: test_path.display().to_string(),
+        };
+        fs::write(dir.join(format!("{stem}.json")), serde_json::to_string_pretty(&artifact)?)?;
+        self.dbg(format!("reproduction written to {}", test_path.display()));
+        Ok(test_path)
+    }
+}
+
+/// Foundry test that forks at `fork_block`, replays the call as `sender` and asserts each slot change
+fn render_foundry_test(target: Address, fork_block: u64, sender: Address, calldata: &[u8], slots: &[SlotChange]) -> String {
+    let word = |v: Option<B256>| v.unwrap_or_default();
+    let mut before = String::new();
+    let mut after = String::new();
+    for c in slots {
+        before.push_str(&format!("        assertEq(vm.load(TARGET, bytes32({})), bytes32({}));\n", c.slot, word(c.from)));
+        after.push_str(&format!("        assertEq(vm.load(TARGET, bytes32({})), bytes32({}), \"slot {} not taken over\");\n", c.slot, word(c.to), c.slot));
+    }
+    format!(
+        r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import "forge-std/Test.sol";
+
+// Generated by evm-track initscan. Run with: RPC_URL=<archive node> forge test --match-contract InitscanRepro
+contract InitscanRepro_{name} is Test {{
+    address constant TARGET = {target};
+    address constant SENDER = {sender};
+    uint256 constant FORK_BLOCK = {fork_block};
+
+    function setUp() public {{
+        vm.createSelectFork(vm.envString("RPC_URL"), FORK_BLOCK);
+    }}
+
+    function testUnprotectedInitializer() public {{
+{before}
+        vm.prank(SENDER);
+        (bool ok,) = TARGET.call(hex"{data}");
+        assertTrue(ok, "call reverted");
+
+{after}    }}
+}}
+"#,
+        name = hex::encode(target.0),
+        data = hex::encode(calldata),
+    )
+}
+
+// Value-at-risk estimation
+const TRANSFER_TOPIC: B256 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+// cap on tokens discovered from Transfer history (each costs a balanceOf + pricing calls)
+const MAX_DISCOVERED_TOKENS: usize = 50;
+
+/// One asset held by a probed contract
+struct Holding {
+    asset: String,
+    amount: String,
+    usd: Option<f64>,
+}
+
+impl InitscanAction {
+    /// Current native balance plus balances of known / discovered tokens, valued in USD.
+    /// Returns the total of priced holdings and the per-asset breakdown.
+    async fn value_at_risk(&self, contract: Address) -> (f64, Vec<Holding>) {
+        let mut holdings = vec![];
+        throttle::acquire().await;
+        if let Ok(wei) = self.provider.get_balance(contract).await && !wei.is_zero() {
+            holdings.push(Holding {
+                asset: "native".to_string(),
+                amount: token_meta::format_units(wei, 18),
+                usd: self.pricing.native_value_usd(wei, None).await,
+            });
+        }
+        let mut tokens = self.pricing.quote_tokens();
+        tokens.extend(self.opts.value_tokens.iter().copied());
+        tokens.extend(self.discover_tokens(contract).await);
+        tokens.sort();
+        tokens.dedup();
+        for token in tokens {
+            let Some(raw) = balance_of(self.provider.as_ref(), token, contract).await else { continue };
+            if raw.is_zero() {
+                continue;
+            }
+            let meta = self.tokens.get(token).await;
+            holdings.push(Holding {
+                asset: format!("{}({})", meta.symbol_or_default(), token),
+                amount: meta.decimals.map(|d| token_meta::format_units(raw, d)).unwrap_or_else(|| raw.to_string()),
+                usd: self.pricing.value_usd(token, raw, None).await,
+            });
+        }
+        let total = holdings.iter().filter_map(|h| h.usd).sum();
+        (total, holdings)
+    }
+
+    /// Tokens that sent Transfer events to `contract` within the last `value_scan_blocks` blocks
+    async fn discover_tokens(&self, contract: Address) -> Vec<Address> {
+        if self.opts.value_scan_blocks == 0 {
+            return vec![];
+        }
+        throttle::acquire().await;
+        let Ok(latest) = self.provider.get_block_number().await else { return vec![] };
+        let filter = Filter::new()
+            .event_signature(TRANSFER_TOPIC)
+            .topic2(contract.into_word())
+            .from_block(latest.saturating_sub(self.opts.value_scan_blocks))
+            .to_block(latest);
+        throttle::acquire().await;
+        let logs = match self.provider.get_logs(&filter).await {
+            Ok(l) => l,
+            Err(e) => {
+                self.dbg(format!("transfer discovery for 0x{} failed: {e}", hex::encode(contract.0)));
+                return vec![];
+            }
+        };
+        let mut out: Vec<Address> = vec![];
+        for l in logs {
+            if !out.contains(&l.address()) {
+                out.push(l.address());
+                if out.len() >= MAX_DISCOVERED_TOKENS {
+                    break;
+                }
+            }
+        }
+        out
+    }
+}
+
+async fn balance_of(provider: &RootProvider<BoxTransport>, token: Address, holder: Address) -> Option<U256> {
+    let mut data = BALANCE_OF_SELECTOR.to_vec();
+    data.extend_from_slice(holder.into_word().as_slice());
+    let tx = TransactionRequest::default()
+        .to(token)
+        .input(TransactionInput::new(Bytes::from(data)));
+    throttle::acquire().await;
+    let out = provider.call(&tx).await.ok()?;
+    let word: [u8; 32] = out.get(..32)?.try_into().ok()?;
+    Some(U256::from_be_bytes(word))
+}
+
 // Move this block outside of the impl InitscanAction
 impl Action for InitscanAction {
     fn on_tx(&self, t: &TxRecord) -> Result<()> {
         // Only react to deployments (receipt has contract address)
         if let Some(contract) = t.contract_address {
-            let this = self.clone_for_task();
-            let block_number = t.block_number; // Option<u64>
-            tokio::spawn(async move {
-                // concurrency gate (optional)
-                let _permit = match &this.sem {
-                    Some(s) => Some(s.clone().acquire_owned().await.expect("semaphore closed")),
-                    None => None,
-                };
-                this.dbg(format!("on_tx: deployment detected contract=0x{} block={:?}", hex::encode(contract.0), block_number));
-                if this.opts.init_after_delay_secs > 0 {
-                    tokio::time::sleep(Duration::from_secs(this.opts.init_after_delay_secs)).await;
-                }
-                for (_sig, calldata) in &this.opts.func_sigs {
-                    if let Err(e) = this.try_init_with_calldata(contract, block_number, calldata).await {
-                        eprintln!("[initscan] error on {contract:?}: {e}");
-                    }
-                }
-                drop(_permit);
-            });
+            self.spawn_probe(contract, None, t.block_number);
         }
         Ok(())
     }
+
+    fn on_contract_creation(&self, c: &ContractCreationRecord) -> Result<()> {
+        // Includes contracts created by factories / CREATE2 deployers
+        self.spawn_probe(c.contract_address, None, Some(c.block_number));
+        Ok(())
+    }
+}
+
+impl InitscanAction {
+    fn spawn_probe(&self, contract: Address, proxy: Option<Address>, block_number: Option<u64>) {
+        let this = self.clone_for_task();
+        tokio::spawn(async move {
+            // concurrency gate (optional)
+            let _permit = match &this.sem {
+                Some(s) => Some(s.clone().acquire_owned().await.expect("semaphore closed")),
+                None => None,
+            };
+            match proxy {
+                Some(p) => this.dbg(format!("implementation 0x{} of proxy 0x{} block={:?}", hex::encode(contract.0), hex::encode(p.0), block_number)),
+                None => this.dbg(format!("on_tx: deployment detected contract=0x{} block={:?}", hex::encode(contract.0), block_number)),
+            }
+            if this.opts.init_after_delay_secs > 0 {
+                tokio::time::sleep(Duration::from_secs(this.opts.init_after_delay_secs)).await;
+            }
+            let candidates = this.init_candidates(contract, block_number).await;
+            this.dbg(format!("init candidates for 0x{}: {:?}", hex::encode(contract.0), candidates.iter().map(|(sig, _)| sig).collect::<Vec<_>>()));
+            for (_sig, calldata) in &candidates {
+                if let Err(e) = this.try_init_with_calldata(contract, proxy, block_number, calldata).await {
+                    eprintln!("[initscan] error on {contract:?}: {e}");
+                }
+            }
+            drop(_permit);
+        });
+    }
+}
+
+/// Probe the implementation behind `proxy` for an initializer anyone can call (the implementation itself is
+/// never initialized when only the proxy's storage was set up). Findings name the proxy and value what it holds.
+/// No-op unless an initscan action is running; each implementation is probed once.
+pub fn probe_implementation(proxy: Address, implementation: Address, block_number: Option<u64>) {
+    let Some(this) = IMPL_PROBER.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|a| a.clone_for_task()) else { return };
+    if !this.probed_impls.lock().unwrap_or_else(|e| e.into_inner()).insert(implementation) {
+        return;
+    }
+    this.spawn_probe(implementation, Some(proxy), block_number);
 }
 
 
 // Persistence types/helpers
 #[derive(Clone, Debug)]
-struct KnownInit { contract: Address, calldata: Vec<u8> }
+pub(crate) struct KnownInit { pub(crate) contract: Address, pub(crate) calldata: Vec<u8>, pub(crate) status: FindingStatus }
 
 #[derive(Serialize, Deserialize)]
-struct KnownInitSerde { contract: String, calldata: String }
+struct KnownInitSerde {
+    contract: String,
+    calldata: String,
+    #[serde(default, skip_serializing_if = "is_open")]
+    status: FindingStatus,
+}
+
+fn is_open(s: &FindingStatus) -> bool { *s == FindingStatus::Open }
 
-fn load_known_from_file(path: &str) -> Result<Vec<KnownInit>> {
+/// JSON counterpart of `FindingStore::record_open`: one entry per (contract, selector); an open entry
+/// takes the new calldata, a closed one is left alone.
+pub(crate) fn upsert_known(list: &mut Vec<KnownInit>, contract: Address, calldata: &[u8]) -> RecordOutcome {
+    let selector = |data: &[u8]| data[..data.len().min(4)].to_vec();
+    match list.iter_mut().find(|k| k.contract == contract && selector(&k.calldata) == selector(calldata)) {
+        Some(k) if k.status != FindingStatus::Open => RecordOutcome::Closed,
+        Some(k) => { k.calldata = calldata.to_vec(); RecordOutcome::Refreshed }
+        None => { list.push(KnownInit { contract, calldata: calldata.to_vec(), status: FindingStatus::Open }); RecordOutcome::New }
+    }
+}
+
+pub(crate) fn load_known_from_file(path: &str) -> Result<Vec<KnownInit>> {
     if !Path::new(path).exists() {
         return Ok(vec![]);
     }
@@ -235,22 +902,23 @@ fn load_known_from_file(path: &str) -> Result<Vec<KnownInit>> {
         } else {
             vec![]
         };
-        out.push(KnownInit { contract, calldata: data });
+        out.push(KnownInit { contract, calldata: data, status: it.status });
     }
     Ok(out)
 }
 
-fn save_known_to_file(path: &str, list: &Vec<KnownInit>) -> Result<()> {
+pub(crate) fn save_known_to_file(path: &str, list: &Vec<KnownInit>) -> Result<()> {
     let arr: Vec<KnownInitSerde> = list.iter().map(|k| KnownInitSerde {
         contract: format!("0x{}", hex::encode(k.contract.0)),
         calldata: format!("0x{}", hex::encode(&k.calldata)),
+        status: k.status,
     }).collect();
     let data = serde_json::to_string_pretty(&arr).map_err(|e| AppError::from(e))?;
     fs::write(path, data).map_err(|e| AppError::from(e))?;
     Ok(())
 }
 
-async fn send_webhook(url: &str, content: &str) -> Result<()> {
+pub(crate) async fn send_webhook(url: &str, content: &str) -> Result<()> {
     #[derive(Serialize)]
     struct Payload<'a> { content: &'a str }
     let client = reqwest::Client::new();
@@ -263,7 +931,7 @@ async fn send_webhook(url: &str, content: &str) -> Result<()> {
     Ok(())
 }
 
-async fn eth_call_ok(
+pub(crate) async fn eth_call_ok(
     provider: &RootProvider<BoxTransport>,
     from: Option<Address>,
     to: Address,
@@ -279,13 +947,16 @@ async fn eth_call_ok(
     let block = block_number
         .map(|n| format!("0x{:x}", n))
         .unwrap_or_else(|| "latest".to_string());
-    // If eth_call returns without RPC error, we treat as ok
-    let _: String = provider
+    // An error reply from the node (revert) means not ok; transport failures are errors
+    let res: std::result::Result<String, _> = provider
         .client()
         .request("eth_call", serde_json::json!([call, block]))
-        .await
-        .map_err(|e| AppError::from(e))?;
-    Ok(true)
+        .await;
+    match res {
+        Ok(_) => Ok(true),
+        Err(e) if e.as_error_resp().is_some() => Ok(false),
+        Err(e) => Err(AppError::from(e)),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -338,18 +1009,349 @@ fn trace_success(tr: &TraceCallResult) -> bool {
     tr.traces.iter().all(|t| t.error.is_empty())
 }
 
-fn state_diff_contains_any_addr(tr: &TraceCallResult, addrs: &[Address]) -> bool {
-    if addrs.is_empty() {
-        return false;
+/// One storage slot change from a Parity-style stateDiff; `None` means the slot did not exist
+/// before (`+`) or was cleared (`-`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct SlotChange {
+    pub(crate) slot: B256,
+    pub(crate) from: Option<B256>,
+    pub(crate) to: Option<B256>,
+}
+
+/// Per-account storage and native balance changes, independent of the trace backend
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct StateDiff {
+    pub(crate) storage: HashMap<Address, Vec<SlotChange>>,
+    /// (before, after); accounts created or removed by the call count as zero on the missing side
+    pub(crate) balances: HashMap<Address, (U256, U256)>,
+}
+
+/// Parse Parity stateDiff into per-account changes; unchanged (`"="`) entries are skipped
+fn parse_state_diff(state_diff: &serde_json::Value) -> StateDiff {
+    let mut out = StateDiff::default();
+    let Some(accounts) = state_diff.as_object() else { return out };
+    for (addr, diff) in accounts {
+        let Ok(addr) = addr.parse::<Address>() else { continue };
+        if let Some((from, to)) = diff.get("balance").and_then(parse_change) {
+            let word = |w: Option<B256>| w.map(|w| U256::from_be_bytes(w.0)).unwrap_or_default();
+            let (from, to) = (word(from), word(to));
+            if from != to {
+                out.balances.insert(addr, (from, to));
+            }
+        }
+        let Some(storage) = diff.get("storage").and_then(|v| v.as_object()) else { continue };
+        for (slot, change) in storage {
+            let Some(slot) = parse_word(slot) else { continue };
+            let Some((from, to)) = parse_change(change) else { continue };
+            out.storage.entry(addr).or_default().push(SlotChange { slot, from, to });
+        }
+    }
+    out
+}
+
+/// One Parity diff entry: `{"*": {from, to}}`, `{"+": v}` or `{"-": v}`; `"="` yields None
+fn parse_change(change: &serde_json::Value) -> Option<(Option<B256>, Option<B256>)> {
+    let word = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).and_then(parse_word);
+    if let Some(c) = change.get("*") {
+        Some((word(c.get("from")), word(c.get("to"))))
+    } else if let Some(v) = change.get("+") {
+        Some((None, word(Some(v))))
+    } else {
+        change.get("-").map(|v| (word(Some(v)), None))
+    }
+}
+
+/// geth prestate diffMode: `pre` holds the old values of modified fields, `post` the new ones;
+/// a slot missing from `post` was cleared
+fn state_diff_from_geth(diff: &DiffMode) -> StateDiff {
+    let mut out = StateDiff::default();
+    for (addr, pre) in &diff.pre {
+        let post = diff.post.get(addr);
+        for (slot, from) in &pre.storage {
+            let to = post.and_then(|p| p.storage.get(slot)).copied();
+            if to != Some(*from) {
+                out.storage.entry(*addr).or_default().push(SlotChange { slot: *slot, from: Some(*from), to });
+            }
+        }
+    }
+    for (addr, post) in &diff.post {
+        let pre = diff.pre.get(addr);
+        for (slot, to) in &post.storage {
+            if pre.is_none_or(|p| !p.storage.contains_key(slot)) {
+                out.storage.entry(*addr).or_default().push(SlotChange { slot: *slot, from: None, to: Some(*to) });
+            }
+        }
+        // post only carries a balance when it changed
+        if let Some(to) = post.balance {
+            let from = pre.and_then(|p| p.balance).unwrap_or_default();
+            if from != to {
+                out.balances.insert(*addr, (from, to));
+            }
+        }
     }
-    let s = serde_json::to_string(&tr.state_diff).unwrap_or_default().to_lowercase();
-    let mut set = HashSet::new();
-    for a in addrs {
-        // search address hex (without 0x) in state diff JSON
-        let needle = hex::encode(a.0).to_lowercase();
-        if !needle.is_empty() {
-            set.insert(needle);
+    out
+}
+
+/// Typed Parity stateDiff (as returned by `trace_replayBlockTransactions`) into per-account changes
+fn state_diff_from_parity(diff: &ParityStateDiff) -> StateDiff {
+    let mut out = StateDiff::default();
+    for (addr, acc) in diff.iter() {
+        let (from, to) = match &acc.balance {
+            Delta::Unchanged => (U256::ZERO, U256::ZERO),
+            Delta::Added(v) => (U256::ZERO, *v),
+            Delta::Removed(v) => (*v, U256::ZERO),
+            Delta::Changed(c) => (c.from, c.to),
+        };
+        if from != to {
+            out.balances.insert(*addr, (from, to));
+        }
+        for (slot, delta) in &acc.storage {
+            let (from, to) = match delta {
+                Delta::Unchanged => continue,
+                Delta::Added(v) => (None, Some(*v)),
+                Delta::Removed(v) => (Some(*v), None),
+                Delta::Changed(c) => (Some(c.from), Some(c.to)),
+            };
+            out.storage.entry(*addr).or_default().push(SlotChange { slot: *slot, from, to });
         }
     }
-    set.into_iter().any(|n| s.contains(&n))
+    out
+}
+
+fn parse_word(s: &str) -> Option<B256> {
+    U256::from_str_radix(s.trim_start_matches("0x"), 16).ok().map(B256::from)
+}
+
+/// Whether `addr` sits in `word` at any packed (right-aligned, byte-granular) offset
+pub(crate) fn word_holds_addr(word: &B256, addr: &Address) -> bool {
+    word.0.windows(20).any(|w| w == addr.as_slice())
+}
+
+/// Storage slots of `target` whose new value holds a check address that was not there before
+pub(crate) fn slots_now_holding(diff: &StateDiff, target: Address, addrs: &[Address]) -> Vec<SlotChange> {
+    let Some(changes) = diff.storage.get(&target) else { return vec![] };
+    changes
+        .iter()
+        .filter(|c| {
+            addrs.iter().any(|a| {
+                c.to.is_some_and(|w| word_holds_addr(&w, a)) && !c.from.is_some_and(|w| word_holds_addr(&w, a))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn fmt_slot_value(v: Option<B256>) -> String {
+    v.map(|w| w.to_string()).unwrap_or_else(|| "(empty)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn state_diff_requires_target_slot_to_now_hold_check_address() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        let other = address!("00000000000000000000000000000000000000bb");
+        let tr = TraceCallResult {
+            traces: vec![],
+            state_diff: serde_json::json!({
+                // balance change mentioning our address elsewhere must not count
+                format!("{me:#x}"): { "balance": { "*": { "from": "0x1", "to": "0x0" } }, "storage": {} },
+                format!("{other:#x}"): { "balance": "=", "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000": { "+": "0x0000000000000000000000004b20993bc481177ec7e8f571cecae8a9e22c02db" }
+                } },
+                format!("{target:#x}"): { "balance": "=", "storage": {
+                    // owner packed with an `initialized` flag
+                    "0x0000000000000000000000000000000000000000000000000000000000000000": { "*": {
+                        "from": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "to": "0x00000000000000000000014b20993bc481177ec7e8f571cecae8a9e22c02db"
+                    } },
+                    // already held the address before: not a new takeover
+                    "0x0000000000000000000000000000000000000000000000000000000000000001": { "*": {
+                        "from": "0x0000000000000000000000004b20993bc481177ec7e8f571cecae8a9e22c02db",
+                        "to": "0x0000000000000000000000014b20993bc481177ec7e8f571cecae8a9e22c02db"
+                    } },
+                    "0x0000000000000000000000000000000000000000000000000000000000000002": { "-": "0x01" },
+                    "0x0000000000000000000000000000000000000000000000000000000000000003": "="
+                } }
+            }),
+        };
+        let diff = parse_state_diff(&tr.state_diff);
+        assert_eq!(diff.storage[&target].len(), 3);
+        assert!(!diff.storage.contains_key(&me));
+        assert_eq!(diff.balances[&me], (U256::from(1), U256::ZERO));
+        assert!(!diff.balances.contains_key(&target));
+
+        let slots = slots_now_holding(&diff, target, &[me]);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].slot, B256::ZERO);
+        assert_eq!(slots[0].from, Some(B256::ZERO));
+        assert!(slots_now_holding(&diff, target, &[other]).is_empty());
+        assert!(slots_now_holding(&diff, other, &[target]).is_empty());
+    }
+
+    #[test]
+    fn geth_prestate_diff_maps_to_same_structure() {
+        use alloy_rpc_types::trace::geth::AccountState;
+        use std::collections::BTreeMap;
+
+        let target = address!("00000000000000000000000000000000000000aa");
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        let word = |b: u8| B256::with_last_byte(b);
+        let owner = B256::left_padding_from(me.as_slice());
+        let account = |slots: &[(B256, B256)]| AccountState {
+            storage: slots.iter().copied().collect(),
+            ..Default::default()
+        };
+        let diff = DiffMode {
+            // slot 0: 0 -> owner, slot 2: 1 -> cleared
+            pre: BTreeMap::from([(target, account(&[(word(0), B256::ZERO), (word(2), word(1))]))]),
+            // slot 1: newly written
+            // `me` received 5 wei
+            post: BTreeMap::from([
+                (target, account(&[(word(0), owner), (word(1), word(7))])),
+                (me, AccountState { balance: Some(U256::from(5)), ..Default::default() }),
+            ]),
+        };
+        let parsed = state_diff_from_geth(&diff);
+        let mut changes = parsed.storage[&target].clone();
+        changes.sort_by_key(|c| c.slot);
+        assert_eq!(changes, vec![
+            SlotChange { slot: word(0), from: Some(B256::ZERO), to: Some(owner) },
+            SlotChange { slot: word(1), from: None, to: Some(word(7)) },
+            SlotChange { slot: word(2), from: Some(word(1)), to: None },
+        ]);
+        assert_eq!(parsed.balances[&me], (U256::ZERO, U256::from(5)));
+        assert!(!parsed.balances.contains_key(&target));
+        let slots = slots_now_holding(&parsed, target, &[me]);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].slot, word(0));
+        assert_eq!("debug".parse::<InitscanTraceBackend>().unwrap(), InitscanTraceBackend::DebugTraceCall);
+        assert!("nope".parse::<InitscanTraceBackend>().is_err());
+    }
+
+    #[test]
+    fn typed_parity_diff_matches_raw_parsing() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        let raw = serde_json::json!({
+            format!("{me:#x}"): { "balance": { "*": { "from": "0x1", "to": "0x0" } }, "code": "=", "nonce": "=", "storage": {} },
+            format!("{target:#x}"): { "balance": "=", "code": "=", "nonce": "=", "storage": {
+                "0x0000000000000000000000000000000000000000000000000000000000000000": { "*": {
+                    "from": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "to": "0x0000000000000000000000004b20993bc481177ec7e8f571cecae8a9e22c02db"
+                } },
+                "0x0000000000000000000000000000000000000000000000000000000000000001": { "+": "0x0000000000000000000000000000000000000000000000000000000000000007" },
+                "0x0000000000000000000000000000000000000000000000000000000000000002": { "-": "0x0000000000000000000000000000000000000000000000000000000000000001" },
+                "0x0000000000000000000000000000000000000000000000000000000000000003": "="
+            } }
+        });
+        let typed: ParityStateDiff = serde_json::from_value(raw.clone()).unwrap();
+        let (a, b) = (state_diff_from_parity(&typed), parse_state_diff(&raw));
+        let sorted = |d: &StateDiff| {
+            let mut v = d.storage[&target].clone();
+            v.sort_by_key(|c| c.slot);
+            v
+        };
+        assert_eq!(sorted(&a), sorted(&b));
+        assert_eq!(sorted(&a).len(), 3);
+        assert_eq!(a.balances, b.balances);
+    }
+
+    #[test]
+    fn renders_foundry_reproduction() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        let owner = B256::left_padding_from(me.as_slice());
+        let slots = vec![SlotChange { slot: B256::ZERO, from: None, to: Some(owner) }];
+        let src = render_foundry_test(target, 123, me, &[0x81, 0x29, 0xfc, 0x1c], &slots);
+        assert!(src.contains("contract InitscanRepro_00000000000000000000000000000000000000aa is Test"));
+        // address literals must be checksummed for solc
+        assert!(src.contains(&format!("address constant SENDER = {};", me.to_checksum(None))));
+        assert!(src.contains("uint256 constant FORK_BLOCK = 123;"));
+        assert!(src.contains(r#"TARGET.call(hex"8129fc1c")"#));
+        assert!(src.contains(&format!("assertEq(vm.load(TARGET, bytes32({})), bytes32({}));", B256::ZERO, B256::ZERO)));
+        assert!(src.contains(&format!("bytes32({owner}), \"slot")));
+
+        let mut diff = StateDiff::default();
+        diff.storage.insert(target, slots.clone());
+        diff.balances.insert(me, (U256::from(1), U256::ZERO));
+        let v = serde_json::to_value(&diff).unwrap();
+        assert_eq!(v["storage"][format!("{target:#x}")][0]["to"], serde_json::json!(owner));
+    }
+
+    #[test]
+    fn synthesizes_initializer_calldata() {
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        let f = Function::parse("function initialize(address owner, uint256 fee, string name, address[] admins)").unwrap();
+        let data = synthesize_calldata(&f, Some(me), U256::ZERO).unwrap();
+        assert_eq!(data[..4], f.selector()[..]);
+        let decoded = f.abi_decode_input(&data[4..], true).unwrap();
+        assert_eq!(decoded[0], DynSolValue::Address(me));
+        assert_eq!(decoded[1], DynSolValue::Uint(U256::ZERO, 256));
+        assert_eq!(decoded[2], DynSolValue::String(String::new()));
+        assert_eq!(decoded[3], DynSolValue::Array(vec![DynSolValue::Address(me)]));
+
+        let tuple = Function::parse("function initialize((address,uint256) cfg)").unwrap();
+        assert!(synthesize_calldata(&tuple, Some(me), U256::ZERO).is_none());
+
+        for name in ["initialize", "init", "__init_v2", "setUp", "setupPool", "setOwner"] {
+            assert_eq!(looks_like_initializer(name), !name.starts_with("__"), "{name}");
+        }
+        assert!(!looks_like_initializer("transferOwnership"));
+    }
+
+    #[test]
+    fn known_list_keys_by_selector_and_keeps_closed_entries() {
+        let contract = Address::repeat_byte(0xaa);
+        let init = |arg: u8| [&[0x81, 0x29, 0xfc, 0x1c][..], &[arg; 32]].concat();
+        let setup = [0x0a, 0xe9, 0xd9, 0x3c];
+        let mut list = vec![];
+        assert_eq!(upsert_known(&mut list, contract, &init(1)), RecordOutcome::New);
+        // a second initializer of the same contract is its own entry
+        assert_eq!(upsert_known(&mut list, contract, &setup), RecordOutcome::New);
+        // same selector again refreshes the calldata
+        assert_eq!(upsert_known(&mut list, contract, &init(2)), RecordOutcome::Refreshed);
+        assert_eq!((list.len(), &list[0].calldata), (2, &init(2)));
+
+        list[0].status = FindingStatus::Initialized;
+        assert_eq!(upsert_known(&mut list, contract, &init(3)), RecordOutcome::Closed);
+        assert_eq!((list[0].status, &list[0].calldata), (FindingStatus::Initialized, &init(2)));
+
+        let path = std::env::temp_dir().join(format!("initscan_known_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_known_to_file(path, &list).unwrap();
+        let loaded = load_known_from_file(path).unwrap();
+        let _ = fs::remove_file(path);
+        assert_eq!(loaded.iter().map(|k| k.status).collect::<Vec<_>>(), [FindingStatus::Initialized, FindingStatus::Open]);
+    }
+
+    #[test]
+    fn configured_sigs_are_probed_even_when_not_in_dispatcher() {
+        let entry = |sig: &str| {
+            let abi = Function::parse(&format!("function {sig}")).unwrap();
+            (format!("0x{}", hex::encode(abi.selector())), abi::FuncSigEntry { name: abi.name.clone(), sig: sig.to_string(), abi, collisions: vec![] })
+        };
+        let funcs: abi::FuncSigMap = [entry("initialize(address)"), entry("setUp()"), entry("transfer(address,uint256)"), entry("init((address,uint256))")]
+            .into_iter()
+            .collect();
+        let sel = |sig: &str| funcs.values().find(|e| e.sig == sig).unwrap().abi.selector().0;
+        // configured selector routed through a fallback, absent from the PUSH4 set
+        let configured = vec![("initialize(uint256)".to_string(), Function::parse("function initialize(uint256)").unwrap().abi_encode_input(&[DynSolValue::Uint(U256::ZERO, 256)]).unwrap())];
+        let selectors: BTreeSet<[u8; 4]> = ["initialize(address)", "setUp()", "transfer(address,uint256)", "init((address,uint256))"].map(sel).into();
+
+        let mut unsupported = Vec::new();
+        let out = candidate_calls(&configured, &selectors, &funcs, None, |s| unsupported.push(s.to_string()));
+        let mut sigs: Vec<&str> = out.iter().map(|(s, _)| s.as_str()).collect();
+        sigs.sort();
+        assert_eq!(sigs, ["initialize(address)", "initialize(uint256)", "setUp()"]);
+        assert_eq!(out[0], configured[0]);
+        assert_eq!(unsupported, ["init((address,uint256))"]);
+
+        // nothing readable from the bytecode: only the configured list
+        assert_eq!(candidate_calls(&configured, &BTreeSet::new(), &funcs, None, |_| {}), configured);
+    }
 }
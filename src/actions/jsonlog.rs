@@ -1,9 +1,10 @@
 use super::{Action, BlockRecord, EventRecord, TxRecord};
+use crate::abi::{DecodedField, RevertReason};
 use crate::error::Result;
 use serde::Serialize;
 
 #[derive(Serialize)]
-struct JsonEvent {
+struct JsonEvent<'a> {
     kind: &'static str,
     address: String,
     tx_hash: Option<String>,
@@ -12,7 +13,7 @@ struct JsonEvent {
     decode_ok: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     decode_error: Option<String>,
-    fields: Vec<(String, String)>,
+    fields: &'a [DecodedField],
     tx_index: Option<u64>,
     log_index: Option<u64>,
     topics: Vec<String>,
@@ -20,12 +21,14 @@ struct JsonEvent {
 }
 
 #[derive(Serialize)]
-struct JsonTx {
+struct JsonTx<'a> {
     kind: &'static str,
     hash: String,
     from: Option<String>,
     to: Option<String>,
     func: Option<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    args: &'a [DecodedField],
     #[serde(skip_serializing_if = "Option::is_none")]
     decode_ok: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,6 +43,19 @@ struct JsonTx {
     tx_index: Option<u64>,
     contract_address: Option<String>,
     receipt_logs: Option<Vec<JsonReceiptLog>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<JsonTxFailure<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonTxFailure<'a> {
+    /// empty / error / panic / custom / unknown
+    kind: &'static str,
+    reason: String,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    fields: &'a [DecodedField],
+    revert_data: String,
+    source: &'static str,
 }
 
 #[derive(Serialize)]
@@ -58,29 +74,10 @@ struct JsonBlock {
     number: u64,
 }
 
-fn value_to_string(v: &crate::abi::DecodedValue) -> String {
-    use crate::abi::DecodedValue::*;
-    match v {
-        Address(a) => format!("0x{}", hex::encode(a.0)),
-        Uint(u) => u.to_string(),
-        Int(i) => i.to_string(),
-        Bool(b) => b.to_string(),
-        Bytes32(b) => format!("0x{}", hex::encode(b)),
-        Bytes(b) => format!("0x{}", hex::encode(b)),
-        String(s) => s.clone(),
-        Array(arr) => format!(
-            "[{}]",
-            arr.iter().map(value_to_string).collect::<Vec<_>>().join(",")
-        ),
-        Unsupported(s) => format!("<unsupported:{}>", s),
-    }
-}
-
 pub struct JsonLogAction;
 
 impl Action for JsonLogAction {
     fn on_event(&self, e: &EventRecord) -> Result<()> {
-        let fields = e.fields.iter().map(|f| (f.name.clone(), value_to_string(&f.value))).collect();
         let j = JsonEvent {
             kind: "event",
             address: format!("0x{}", hex::encode(e.address.0)),
@@ -89,7 +86,7 @@ impl Action for JsonLogAction {
             name: e.name.clone(),
             decode_ok: e.name.is_some(),
             decode_error: if e.name.is_none() { Some("unknown_topic0".to_string()) } else { None },
-            fields,
+            fields: &e.fields,
             tx_index: e.tx_index,
             log_index: e.log_index,
             topics: e.topics.iter().map(|t| format!("0x{}", hex::encode(t))).collect(),
@@ -106,6 +103,7 @@ impl Action for JsonLogAction {
             from: t.from.map(|a| format!("0x{}", hex::encode(a.0))),
             to: t.to.map(|a| format!("0x{}", hex::encode(a.0))),
             func: t.func_name.clone(),
+            args: &t.func_args,
             decode_ok: Some(t.func_name.is_some()).filter(|_| t.input_selector.is_some()),
             decode_error: if t.input_selector.is_some() && t.func_name.is_none() {
                 Some("unknown_selector".to_string())
@@ -132,6 +130,22 @@ impl Action for JsonLogAction {
                     })
                     .collect()
             }),
+            failure: t.failure.as_ref().map(|f| {
+                let (kind, fields): (_, &[DecodedField]) = match &f.reason {
+                    RevertReason::Empty => ("empty", &[]),
+                    RevertReason::Error(_) => ("error", &[]),
+                    RevertReason::Panic { .. } => ("panic", &[]),
+                    RevertReason::Custom { fields, .. } => ("custom", fields),
+                    RevertReason::Unknown { .. } => ("unknown", &[]),
+                };
+                JsonTxFailure {
+                    kind,
+                    reason: f.reason.describe(),
+                    fields,
+                    revert_data: format!("0x{}", hex::encode(&f.revert_data)),
+                    source: f.source,
+                }
+            }),
         };
         println!("{}", serde_json::to_string(&j)?);
         Ok(())
@@ -1,6 +1,9 @@
 use super::{Action, EventRecord};
 use crate::error::Result;
-use alloy_primitives::{Address, U256};
+use crate::pricing::PriceOracle;
+use crate::token_meta::TokenMetaService;
+use alloy_primitives::{Address, B256, U256};
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +13,10 @@ pub struct LargeTransferOptions {
     
     #[serde(rename = "decimals-default")]
     pub decimals_default: u8,
+
+    /// 按链上价格估算的 USD 价值下限
+    #[serde(rename = "min-usd", default)]
+    pub min_usd: Option<f64>,
     
     #[serde(skip)]
     pub verbose: bool,
@@ -17,51 +24,39 @@ pub struct LargeTransferOptions {
 
 pub struct LargeTransferAction {
     opts: LargeTransferOptions,
+    tokens: Arc<TokenMetaService>,
+    pricing: Arc<PriceOracle>,
 }
 
 impl LargeTransferAction {
-    pub fn new(opts: LargeTransferOptions, verbose: bool) -> Self {
+    pub fn new(
+        opts: LargeTransferOptions,
+        verbose: bool,
+        tokens: Arc<TokenMetaService>,
+        pricing: Arc<PriceOracle>,
+    ) -> Self {
         let mut opts = opts;
         opts.verbose = verbose;
-        Self { opts }
-    }
-    
-    /// 根据合约地址获取正确的代币精度
-    fn get_token_decimals(&self, address: &Address) -> u8 {
-        let formatted_addr = format!("{:#x}", address).to_lowercase();
-        
-        // 使用BSC主网上已知代币的精度
-        let decimals = match formatted_addr.as_str() {
-            // NOTE: USDT on BSC uses 18 decimals (align with on-chain contract response)
-            "0x55d398326f99059ff775485246999027b3197955" => 18,  // USDT
-            "0x8ac76a51cc950d9822d68b83fe1ad97b32cd580d" => 6,  // USDC  
-            "0xe9e7cea3dedca5984780bafc599bd69add087d56" => 18, // BUSD
-            "0x2170ed0880ac9a755fd29b2688956bd959f933f8" => 18, // ETH
-            "0x7130d2a12b9bcbfae4f2634d864a1ee1ce3ead9c" => 18, // BTCB
-            "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c" => 18, // WBNB
-            _ => self.opts.decimals_default,
-        };
-        
-        decimals
+        Self { opts, tokens, pricing }
     }
+}
+
+/// 格式化数值为人类可读格式
+fn format_amount(amount: U256, decimals: u8) -> String {
+    let divisor = pow10_u256(decimals as usize);
+    let integer_part = amount / divisor;
+    let fractional_part = amount % divisor;
     
-    /// 格式化数值为人类可读格式
-    fn format_amount(&self, amount: U256, decimals: u8) -> String {
-        let divisor = pow10_u256(decimals as usize);
-        let integer_part = amount / divisor;
-        let fractional_part = amount % divisor;
-        
-        if fractional_part == U256::ZERO {
+    if fractional_part == U256::ZERO {
+        integer_part.to_string()
+    } else {
+        // 格式化小数部分，去除尾随零
+        let frac_str = format!("{:0width$}", fractional_part, width = decimals as usize);
+        let frac_trimmed = frac_str.trim_end_matches('0');
+        if frac_trimmed.is_empty() {
             integer_part.to_string()
         } else {
-            // 格式化小数部分，去除尾随零
-            let frac_str = format!("{:0width$}", fractional_part, width = decimals as usize);
-            let frac_trimmed = frac_str.trim_end_matches('0');
-            if frac_trimmed.is_empty() {
-                integer_part.to_string()
-            } else {
-                format!("{}.{}", integer_part, frac_trimmed)
-            }
+            format!("{}.{}", integer_part, frac_trimmed)
         }
     }
 }
@@ -101,6 +96,80 @@ fn parse_human_to_u256(s: &str, decimals: u8) -> Option<U256> {
     }
 }
 
+/// 按代币精度与 USD 价值检查阈值并输出
+fn report(
+    opts: &LargeTransferOptions,
+    token: Address,
+    amount: U256,
+    decimals: u8,
+    usd: Option<f64>,
+    block_number: Option<u64>,
+    tx_hash: Option<B256>,
+) {
+    // 如果设置了最小金额阈值，检查是否超过阈值
+    if let Some(min_amount_str) = &opts.min_amount_human {
+        if let Some(min_amount) = parse_human_to_u256(min_amount_str, decimals) {
+            if opts.verbose {
+                println!("DEBUG: Transfer amount: {}, threshold: {}, decimals: {}", amount, min_amount, decimals);
+            }
+            if amount < min_amount {
+                return;
+            }
+        } else {
+            if opts.verbose {
+                println!("DEBUG: Failed to parse threshold: {}", min_amount_str);
+            }
+            return;
+        }
+    } else {
+        if opts.verbose {
+            println!("DEBUG: No threshold configured");
+        }
+    }
+
+    // 设置了 min-usd 时，无法定价的代币不告警
+    if let Some(min_usd) = opts.min_usd {
+        match usd {
+            Some(v) if v >= min_usd => {}
+            _ => {
+                if opts.verbose {
+                    println!("DEBUG: Transfer value usd: {:?}, min-usd: {}", usd, min_usd);
+                }
+                return;
+            }
+        }
+    }
+
+    // 异常数值检测（例如远超常规供应量的可疑转账）: 默认 > 1e13 直接标记异常并忽略
+    // 1e13 以人类单位（decimals 之后）表示，这里转换成整数判断
+    if let Some(anomaly_threshold) = parse_human_to_u256("10000000000000", decimals) { // 10,000,000,000,000
+        if amount > anomaly_threshold {
+            let formatted_amount = format_amount(amount, decimals);
+            if opts.verbose {
+                println!("ANOMALY Large Transfer (ignored): {} tokens at {:?} (decimals: {})", 
+                    formatted_amount, token, decimals);
+                println!("  Block: {}, Tx: {:?}", 
+                    block_number.unwrap_or(0), tx_hash);
+            }
+            return;
+        }
+    }
+    
+    // 格式化金额显示
+    let formatted_amount = format_amount(amount, decimals);
+    
+    if opts.verbose {
+        match usd {
+            Some(v) => println!("Large Transfer: {} tokens (~${:.2}) at {:?} (decimals: {})",
+                formatted_amount, v, token, decimals),
+            None => println!("Large Transfer: {} tokens at {:?} (decimals: {})",
+                formatted_amount, token, decimals),
+        }
+        println!("  Block: {}, Tx: {:?}", 
+            block_number.unwrap_or(0), tx_hash);
+    }
+}
+
 impl Action for LargeTransferAction {
     fn on_event(&self, record: &EventRecord) -> Result<()> {        
         if self.opts.verbose {
@@ -111,8 +180,8 @@ impl Action for LargeTransferAction {
             }
         }
         
-        // 检查是否为 Transfer 事件
-        if record.topics.len() >= 3 {
+        // 检查是否为 ERC-20 Transfer 事件（ERC-721 Transfer 的 tokenId 为 indexed，共 4 个 topics）
+        if record.topics.len() == 3 {
             let transfer_sig = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
             let actual_topic = record.topics[0].to_string();
             if self.opts.verbose {
@@ -120,63 +189,33 @@ impl Action for LargeTransferAction {
             }
             if actual_topic == transfer_sig {
                 
-                // 从字段中提取 Transfer 金额 (value 是第三个字段，索引为2)
-                let amount = if let Some(field) = record.fields.get(2) {
-                    match &field.value {
-                        crate::abi::DecodedValue::Uint(val) => *val,
-                        _ => return Ok(()),
-                    }
-                } else {
-                    return Ok(());
+                // 从字段中提取 Transfer 金额（唯一的非 indexed 字段）
+                let amount = match record.fields.iter().find(|f| !f.indexed).map(|f| &f.value) {
+                    Some(crate::abi::DecodedValue::Uint(val)) => *val,
+                    _ => return Ok(()),
                 };
                 
-                // 获取正确的代币精度
-                let decimals = self.get_token_decimals(&record.address);
-                
-                // 如果设置了最小金额阈值，检查是否超过阈值
-                if let Some(min_amount_str) = &self.opts.min_amount_human {
-                    if let Some(min_amount) = parse_human_to_u256(min_amount_str, decimals) {
-                        if self.opts.verbose {
-                            println!("DEBUG: Transfer amount: {}, threshold: {}, decimals: {}", amount, min_amount, decimals);
-                        }
-                        if amount < min_amount {
-                            return Ok(());
-                        }
-                    } else {
-                        if self.opts.verbose {
-                            println!("DEBUG: Failed to parse threshold: {}", min_amount_str);
-                        }
-                        return Ok(());
-                    }
+                // 代币精度来自元数据服务；未缓存或需要 USD 估值时异步查询后再判断
+                let token = record.address;
+                let (block_number, tx_hash) = (record.block_number, record.tx_hash);
+                if let Some(meta) = self.tokens.cached(token)
+                    && self.opts.min_usd.is_none()
+                {
+                    let decimals = meta.decimals.unwrap_or(self.opts.decimals_default);
+                    report(&self.opts, token, amount, decimals, None, block_number, tx_hash);
                 } else {
-                    if self.opts.verbose {
-                        println!("DEBUG: No threshold configured");
-                    }
-                }
-
-                // 异常数值检测（例如远超常规供应量的可疑转账）: 默认 > 1e13 直接标记异常并忽略
-                // 1e13 以人类单位（decimals 之后）表示，这里转换成整数判断
-                if let Some(anomaly_threshold) = parse_human_to_u256("10000000000000", decimals) { // 10,000,000,000,000
-                    if amount > anomaly_threshold {
-                        let formatted_amount = self.format_amount(amount, decimals);
-                        if self.opts.verbose {
-                            println!("ANOMALY Large Transfer (ignored): {} tokens at {:?} (decimals: {})", 
-                                formatted_amount, record.address, decimals);
-                            println!("  Block: {}, Tx: {:?}", 
-                                record.block_number.unwrap_or(0), record.tx_hash);
-                        }
-                        return Ok(());
-                    }
-                }
-                
-                // 格式化金额显示
-                let formatted_amount = self.format_amount(amount, decimals);
-                
-                if self.opts.verbose {
-                    println!("Large Transfer: {} tokens at {:?} (decimals: {})", 
-                        formatted_amount, record.address, decimals);
-                    println!("  Block: {}, Tx: {:?}", 
-                        record.block_number.unwrap_or(0), record.tx_hash);
+                    let opts = self.opts.clone();
+                    let tokens = self.tokens.clone();
+                    let pricing = self.pricing.clone();
+                    tokio::spawn(async move {
+                        let meta = tokens.get(token).await;
+                        let decimals = meta.decimals.unwrap_or(opts.decimals_default);
+                        let usd = match opts.min_usd {
+                            Some(_) => pricing.value_usd(token, amount, block_number).await,
+                            None => None,
+                        };
+                        report(&opts, token, amount, decimals, usd, block_number, tx_hash);
+                    });
                 }
             }
         }
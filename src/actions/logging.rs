@@ -58,7 +58,7 @@ impl Action for LoggingAction {
                 println!("  [decode] unknown_topic0 (未匹配到事件签名)");
             }
             for f in &e.fields {
-                println!("  {} = {:?}", f.name, f.value);
+                println!("  {} ({}) = {}", f.name, f.ty, f.value);
             }
         }
         if self.opts.enable_discord_logs && self.opts.log_events {
@@ -87,13 +87,24 @@ impl Action for LoggingAction {
             if t.input_selector.is_some() && t.func_name.is_none() {
                 println!("  [decode] unknown_selector (未匹配到函数签名)");
             }
+            if let Some(f) = &t.failure {
+                println!(
+                    "  [revert] reason={} source={} data=0x{}",
+                    f.reason.describe(),
+                    f.source,
+                    hex::encode(&f.revert_data)
+                );
+            }
         }
         if self.opts.enable_discord_logs && self.opts.log_transactions {
             if let (Some(client), Some(url)) = (&self.http, &self.opts.discord_webhook_url) {
-                let s = format!(
+                let mut s = format!(
                     "[tx] hash={:?} to={:?} from={:?} func={:?}",
                     t.hash, t.to, t.from, t.func_name
                 );
+                if let Some(f) = &t.failure {
+                    s.push_str(&format!(" revert={}", f.reason.describe()));
+                }
                 let client = client.clone();
                 let url = url.clone();
                 tokio::spawn(async move {
@@ -126,8 +137,8 @@ impl Action for LoggingAction {
     fn on_contract_creation(&self, c: &super::ContractCreationRecord) -> Result<()> {
         if self.opts.enable_terminal_logs {
             println!(
-                "[contract-creation] addr={} deployer={} tx={} block={} gas_used={:?}",
-                c.contract_address, c.deployer, c.tx_hash, c.block_number, c.gas_used
+                "[contract-creation] addr={} deployer={} tx={} block={} gas_used={:?} kind={} creator={:?} salt={:?}",
+                c.contract_address, c.deployer, c.tx_hash, c.block_number, c.gas_used, c.kind.as_str(), c.creator, c.salt
             );
         }
         if self.opts.enable_discord_logs {
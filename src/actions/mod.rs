@@ -1,7 +1,8 @@
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
+use std::sync::{RwLock, RwLockReadGuard};
 
-use crate::abi::{DecodedField, DecodedValue};
+use crate::abi::{DecodedField, RevertReason};
 use alloy_primitives::{Address, B256, U256};
 
 #[derive(Debug, Clone)]
@@ -34,7 +35,7 @@ pub struct TxRecord {
     pub to: Option<Address>,
     pub input_selector: Option<[u8; 4]>,
     pub func_name: Option<String>,
-    pub func_args: Vec<DecodedValue>,
+    pub func_args: Vec<DecodedField>,
     // receipt/fee info
     pub gas: Option<u64>,
     pub gas_price: Option<U256>,
@@ -46,6 +47,17 @@ pub struct TxRecord {
     pub tx_index: Option<u64>,
     pub contract_address: Option<Address>,
     pub receipt_logs: Option<Vec<SimpleLog>>,
+    /// 失败交易（status == 0）重放得到的 revert 信息
+    pub failure: Option<TxFailure>,
+}
+
+/// 失败交易的 revert 数据及其解码结果
+#[derive(Debug, Clone)]
+pub struct TxFailure {
+    pub revert_data: Vec<u8>,
+    pub reason: RevertReason,
+    /// revert 数据来源：`eth_call` 或 `debug_traceTransaction`
+    pub source: &'static str,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +65,21 @@ pub struct BlockRecord {
     pub number: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateKind {
+    Create,
+    Create2,
+}
+
+impl CreateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreateKind::Create => "CREATE",
+            CreateKind::Create2 => "CREATE2",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ContractCreationRecord {
     pub tx_hash: B256,
@@ -62,6 +89,11 @@ pub struct ContractCreationRecord {
     pub tx_index: u64,
     pub gas_used: Option<u64>,
     pub constructor_args: Option<Vec<u8>>,
+    // factory / CREATE2 deployments: contract that executed CREATE (None for top-level deployments)
+    pub creator: Option<Address>,
+    pub kind: CreateKind,
+    pub salt: Option<B256>,
+    pub init_code_hash: Option<B256>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -86,45 +118,60 @@ pub trait Action: Send + Sync {
     }
 }
 
+/// 一组 Action。列表可以在运行中整体替换（配置热重载），
+/// 持有同一个 `Arc<ActionSet>` 的订阅循环无需重建。
 pub struct ActionSet {
-    pub list: Vec<Box<dyn Action>>,
+    list: RwLock<Vec<Box<dyn Action>>>,
 }
 
 impl ActionSet {
     pub fn new() -> Self {
-        Self { list: vec![] }
+        Self {
+            list: RwLock::new(vec![]),
+        }
     }
     pub fn add<A: Action + 'static>(&mut self, a: A) {
-        self.list.push(Box::new(a));
+        self.add_boxed(Box::new(a));
     }
     pub fn add_boxed(&mut self, a: Box<dyn Action>) {
-        self.list.push(a);
+        self.list.get_mut().unwrap_or_else(|e| e.into_inner()).push(a);
     }
     pub fn len(&self) -> usize {
-        self.list.len()
+        self.actions().len()
+    }
+    /// 用新构建的集合替换当前全部 Action，返回替换后的数量
+    pub fn replace(&self, other: ActionSet) -> usize {
+        let list = other.list.into_inner().unwrap_or_else(|e| e.into_inner());
+        let n = list.len();
+        *self.list.write().unwrap_or_else(|e| e.into_inner()) = list;
+        n
+    }
+    fn actions(&self) -> RwLockReadGuard<'_, Vec<Box<dyn Action>>> {
+        self.list.read().unwrap_or_else(|e| e.into_inner())
     }
     pub fn on_event(&self, e: &EventRecord) {
-        for a in &self.list {
+        for a in self.actions().iter() {
             let _ = a.on_event(e);
         }
     }
     pub fn on_tx(&self, t: &TxRecord) {
-        for a in &self.list {
+        for a in self.actions().iter() {
             let _ = a.on_tx(t);
         }
     }
     pub fn on_block(&self, b: &BlockRecord) {
-        for a in &self.list {
+        for a in self.actions().iter() {
             let _ = a.on_block(b);
         }
     }
     pub fn on_contract_creation(&self, c: &ContractCreationRecord) {
-        for a in &self.list {
+        for a in self.actions().iter() {
             let _ = a.on_contract_creation(c);
         }
     }
 }
 
+pub mod bytecode;
 pub mod deployment;
 pub mod jsonlog;
 pub mod logging;
@@ -134,7 +181,9 @@ pub mod tornado;
 pub mod transfer;
 pub mod large_transfer;
 pub mod initscan;
+pub mod privscan;
 pub mod history_init_scan;
 pub mod selector_scan;
 pub mod history_tx_scan;
 pub mod db_log;
+pub mod storage_watch;
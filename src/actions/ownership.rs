@@ -1,38 +1,489 @@
 use super::{Action, EventRecord};
-use crate::error::Result;
-use alloy_primitives::Address;
-pub struct OwnershipAction;
+use super::initscan::send_webhook;
+use crate::abi::DecodedValue;
+use crate::error::{AppError, Result};
+use crate::persist;
+use crate::throttle;
+use alloy_primitives::{Address, B256, U256, b256, keccak256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types_eth::BlockId;
+use alloy_transport::BoxTransport;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
-impl Action for OwnershipAction {
-    fn on_event(&self, e: &EventRecord) -> Result<()> {
-        if e.name.as_deref() != Some("OwnershipTransferred") {
-            return Ok(());
+// 访问控制追踪
+// - Ownable / Ownable2Step：OwnershipTransferred、OwnershipTransferStarted
+// - AccessControl：RoleGranted、RoleRevoked、RoleAdminChanged
+// - Safe：AddedOwner、RemovedOwner、ChangedThreshold
+// 维护每个合约当前的控制者（可持久化为 JSON），并对放弃所有权、转给全新 EOA、关键角色新增成员等告警
+// - 被重组移除的事件（removed=true）按内存中最近 REORG_KEEP_BLOCKS 个区块的快照回滚；已发出的告警不撤回
+
+const OWNERSHIP_TRANSFERRED: B256 = b256!("8be0079c531659141344cd1fd0a4f28419497f9722a3daafe3b4186f6b6457e0");
+const OWNERSHIP_TRANSFER_STARTED: B256 = b256!("38d16b8cac22d99fc7c124b9cd0de2d3fa1faef420bfe791d8c362d765e22700");
+const ROLE_GRANTED: B256 = b256!("2f8788117e7eff1d82e926ec794901d17c78024a50270940304540a733656f0d");
+const ROLE_REVOKED: B256 = b256!("f6391f5c32d9c69d2a47ea670b442974b53935d1edc7fd64eb21e047a839171b");
+const ROLE_ADMIN_CHANGED: B256 = b256!("bd79b86ffe0ab8e8776151514217cd7cacd52c909f66475c3af44e129f0b00ff");
+const SAFE_ADDED_OWNER: B256 = b256!("9465fa0c962cc76958e6373a993326400c1c94f8be2fe3a952adfa7f60b2ea26");
+const SAFE_REMOVED_OWNER: B256 = b256!("f8d49fc529812e9a7c5c50e69c20f0dccc0db8fa95c98bc58cc9a4f1c1299eaf");
+const SAFE_CHANGED_THRESHOLD: B256 = b256!("610f7ff2b304ae8903c3de74c60c6ab1f7d6226b3f52c5161905bb5ad4039c93");
+
+/// 用于显示角色名的常见 AccessControl 角色（`DEFAULT_ADMIN_ROLE` 为 0）
+const KNOWN_ROLES: &[&str] = &[
+    "DEFAULT_ADMIN_ROLE", "ADMIN_ROLE", "MINTER_ROLE", "BURNER_ROLE", "PAUSER_ROLE", "UPGRADER_ROLE",
+    "OPERATOR_ROLE", "GOVERNOR_ROLE", "PROPOSER_ROLE", "EXECUTOR_ROLE", "CANCELLER_ROLE",
+];
+/// 保留应用事件前快照的区块数，更早区块上的重组无法回滚
+const REORG_KEEP_BLOCKS: u64 = 128;
+
+/// 未配置 `critical-roles` 时的关键角色
+const DEFAULT_CRITICAL_ROLES: &[&str] = &["DEFAULT_ADMIN_ROLE", "ADMIN_ROLE", "MINTER_ROLE", "UPGRADER_ROLE"];
+
+#[derive(Debug, Clone, Default)]
+pub struct OwnershipOptions {
+    /// 当前控制者注册表（JSON）；None 时只保存在内存
+    pub registry_filepath: Option<String>,
+    /// 新增成员 / 管理角色变化需要告警的角色
+    pub critical_roles: Vec<B256>,
+    pub webhook_url: Option<String>,
+}
+
+/// 解析 `Ownership` 的 options：`registry-filepath`、`critical-roles`（角色名或 32 字节哈希）、`webhook-url`
+pub fn parse_options(o: &serde_json::Value, default_webhook: Option<&String>) -> Result<OwnershipOptions> {
+    let critical_roles = match o.get("critical-roles").and_then(|v| v.as_array()) {
+        Some(arr) => arr.iter().filter_map(|v| v.as_str()).map(parse_role).collect::<Result<Vec<_>>>()?,
+        None => DEFAULT_CRITICAL_ROLES.iter().map(|r| role_hash(r)).collect(),
+    };
+    Ok(OwnershipOptions {
+        registry_filepath: o.get("registry-filepath").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        critical_roles,
+        webhook_url: o
+            .get("webhook-url")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .or_else(|| default_webhook.cloned()),
+    })
+}
+
+fn role_hash(name: &str) -> B256 {
+    if name == "DEFAULT_ADMIN_ROLE" { B256::ZERO } else { keccak256(name) }
+}
+
+fn parse_role(s: &str) -> Result<B256> {
+    if s.starts_with("0x") {
+        return s.parse().map_err(|e| AppError::Config(format!("invalid role hash {s}: {e}")));
+    }
+    Ok(role_hash(s))
+}
+
+fn role_name(role: &B256) -> String {
+    KNOWN_ROLES
+        .iter()
+        .find(|n| role_hash(n) == *role)
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| role.to_string())
+}
+
+/// 解码后的访问控制事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessEvent {
+    OwnershipTransferred { previous: Address, new: Address },
+    OwnershipTransferStarted { previous: Address, new: Address },
+    RoleGranted { role: B256, account: Address, sender: Address },
+    RoleRevoked { role: B256, account: Address, sender: Address },
+    RoleAdminChanged { role: B256, previous: B256, new: B256 },
+    SafeAddedOwner(Address),
+    SafeRemovedOwner(Address),
+    SafeChangedThreshold(u64),
+}
+
+impl fmt::Display for AccessEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessEvent::OwnershipTransferred { previous, new } => write!(f, "kind=ownership-transferred previous={previous} new={new}"),
+            AccessEvent::OwnershipTransferStarted { previous, new } => write!(f, "kind=ownership-transfer-started owner={previous} pending={new}"),
+            AccessEvent::RoleGranted { role, account, sender } => write!(f, "kind=role-granted role={} account={account} sender={sender}", role_name(role)),
+            AccessEvent::RoleRevoked { role, account, sender } => write!(f, "kind=role-revoked role={} account={account} sender={sender}", role_name(role)),
+            AccessEvent::RoleAdminChanged { role, previous, new } => write!(f, "kind=role-admin-changed role={} previous={} new={}", role_name(role), role_name(previous), role_name(new)),
+            AccessEvent::SafeAddedOwner(a) => write!(f, "kind=safe-added-owner owner={a}"),
+            AccessEvent::SafeRemovedOwner(a) => write!(f, "kind=safe-removed-owner owner={a}"),
+            AccessEvent::SafeChangedThreshold(t) => write!(f, "kind=safe-changed-threshold threshold={t}"),
         }
+    }
+}
+
+/// 按 topic0 解码；第 i 个参数优先取解码字段，缺失时取 topic（兼容 indexed 与非 indexed 的变体，如 Safe v1.3 / v1.4）
+pub fn decode_access_event(e: &EventRecord) -> Option<AccessEvent> {
+    let t0 = *e.topics.first()?;
+    let field = |i: usize| e.fields.get(i).map(|f| &f.value);
+    let word = |i: usize| match field(i) {
+        Some(DecodedValue::Bytes32(w)) => Some(B256::from(*w)),
+        Some(DecodedValue::Uint(v)) => Some(B256::from(*v)),
+        _ => e.topics.get(i + 1).copied(),
+    };
+    let addr = |i: usize| match field(i) {
+        Some(DecodedValue::Address(a)) => Some(*a),
+        _ => e.topics.get(i + 1).map(|t| Address::from_word(*t)),
+    };
+    Some(match t0 {
+        OWNERSHIP_TRANSFERRED => AccessEvent::OwnershipTransferred { previous: addr(0)?, new: addr(1)? },
+        OWNERSHIP_TRANSFER_STARTED => AccessEvent::OwnershipTransferStarted { previous: addr(0)?, new: addr(1)? },
+        ROLE_GRANTED => AccessEvent::RoleGranted { role: word(0)?, account: addr(1)?, sender: addr(2)? },
+        ROLE_REVOKED => AccessEvent::RoleRevoked { role: word(0)?, account: addr(1)?, sender: addr(2)? },
+        ROLE_ADMIN_CHANGED => AccessEvent::RoleAdminChanged { role: word(0)?, previous: word(1)?, new: word(2)? },
+        SAFE_ADDED_OWNER => AccessEvent::SafeAddedOwner(addr(0)?),
+        SAFE_REMOVED_OWNER => AccessEvent::SafeRemovedOwner(addr(0)?),
+        SAFE_CHANGED_THRESHOLD => AccessEvent::SafeChangedThreshold(U256::from_be_bytes(word(0)?.0).saturating_to()),
+        _ => return None,
+    })
+}
 
-        let mut previous_owner: Option<Address> = None;
-        let mut new_owner: Option<Address> = None;
+/// 单个合约当前的控制者。
+/// 重组移除的事件只能在最近 `REORG_KEEP_BLOCKS` 个区块内回滚（快照不落盘，重启后更早的事件无法回滚）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlState {
+    /// Ownable owner；放弃所有权后为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Address>,
+    /// Ownable2Step 待接受的新 owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_owner: Option<Address>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub roles: BTreeMap<B256, BTreeSet<Address>>,
+    /// 非默认的角色管理角色（未出现的角色由 DEFAULT_ADMIN_ROLE 管理）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub role_admins: BTreeMap<B256, B256>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub safe_owners: BTreeSet<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safe_threshold: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_block: Option<u64>,
+}
 
-        for field in &e.fields {
-            match field.name.as_str() {
-                "previousOwner" => {
-                    if let crate::abi::DecodedValue::Address(addr) = field.value {
-                        previous_owner = Some(addr);
+impl ControlState {
+    pub fn apply(&mut self, ev: &AccessEvent, block: Option<u64>) {
+        match ev {
+            AccessEvent::OwnershipTransferred { new, .. } => {
+                self.owner = (!new.is_zero()).then_some(*new);
+                self.pending_owner = None;
+            }
+            AccessEvent::OwnershipTransferStarted { new, .. } => self.pending_owner = (!new.is_zero()).then_some(*new),
+            AccessEvent::RoleGranted { role, account, .. } => {
+                self.roles.entry(*role).or_default().insert(*account);
+            }
+            AccessEvent::RoleRevoked { role, account, .. } => {
+                if let Some(members) = self.roles.get_mut(role) {
+                    members.remove(account);
+                    if members.is_empty() {
+                        self.roles.remove(role);
                     }
                 }
-                "newOwner" => {
-                    if let crate::abi::DecodedValue::Address(addr) = field.value {
-                        new_owner = Some(addr);
-                    }
+            }
+            AccessEvent::RoleAdminChanged { role, new, .. } => {
+                if new.is_zero() {
+                    self.role_admins.remove(role);
+                } else {
+                    self.role_admins.insert(*role, *new);
                 }
-                _ => {}
             }
+            AccessEvent::SafeAddedOwner(a) => {
+                self.safe_owners.insert(*a);
+            }
+            AccessEvent::SafeRemovedOwner(a) => {
+                self.safe_owners.remove(a);
+            }
+            AccessEvent::SafeChangedThreshold(t) => self.safe_threshold = Some(*t),
         }
+        if block.is_some() {
+            self.last_block = block;
+        }
+    }
+}
 
-        println!(
-            "[ownership] contract={} previous={:?} new={:?} tx={:?} block={:?}",
-            e.address, previous_owner, new_owner, e.tx_hash, e.block_number
-        );
+/// 每个区块中各合约应用第一个事件之前的状态，用于回滚被重组移除的事件
+#[derive(Debug, Default)]
+struct Journal(BTreeMap<u64, BTreeMap<Address, ControlState>>);
 
+impl Journal {
+    fn record(&mut self, contract: Address, block: u64, before: &ControlState) {
+        self.0.entry(block).or_default().entry(contract).or_insert_with(|| before.clone());
+        let keep_from = block.saturating_sub(REORG_KEEP_BLOCKS);
+        self.0 = self.0.split_off(&keep_from);
+    }
+
+    /// `block` 起的区块被重组：返回 `contract` 在 `block` 之前的状态，并丢弃它之后的快照。
+    /// 同一次重组的后续移除事件返回 None
+    fn rewind(&mut self, contract: Address, block: u64) -> Option<ControlState> {
+        let mut before = None;
+        for snapshots in self.0.range_mut(block..).map(|(_, m)| m) {
+            if let Some(s) = snapshots.remove(&contract) {
+                before.get_or_insert(s);
+            }
+        }
+        before
+    }
+
+    fn covers(&self, block: u64) -> bool {
+        self.0.first_key_value().is_some_and(|(&first, _)| block >= first)
+    }
+}
+
+/// 不需要 RPC 即可判断的告警；`before` 为应用事件前的状态
+fn immediate_alerts(ev: &AccessEvent, before: &ControlState, critical: &[B256]) -> Vec<String> {
+    let is_critical = |r: &B256| critical.contains(r);
+    let mut out = vec![];
+    match ev {
+        AccessEvent::OwnershipTransferred { previous, new } if new.is_zero() => {
+            out.push(format!("ownership renounced by {previous}"));
+        }
+        AccessEvent::RoleRevoked { role, account, sender } if account == sender && is_critical(role) => {
+            out.push(format!("{account} renounced {}", role_name(role)));
+        }
+        AccessEvent::RoleGranted { role, account, sender } if is_critical(role) => {
+            out.push(format!("new {} member {account} (granted by {sender})", role_name(role)));
+        }
+        AccessEvent::RoleAdminChanged { role, previous, new } if is_critical(role) => {
+            out.push(format!("admin of {} changed {} -> {}", role_name(role), role_name(previous), role_name(new)));
+        }
+        AccessEvent::SafeAddedOwner(a) => out.push(format!("new Safe owner {a}")),
+        AccessEvent::SafeChangedThreshold(t) if before.safe_threshold.is_some_and(|old| *t < old) => {
+            out.push(format!("Safe threshold lowered {} -> {t}", before.safe_threshold.unwrap_or_default()));
+        }
+        _ => {}
+    }
+    out
+}
+
+pub struct OwnershipAction {
+    provider: Arc<RootProvider<BoxTransport>>,
+    opts: OwnershipOptions,
+    registry: Arc<Mutex<BTreeMap<Address, ControlState>>>,
+    journal: Mutex<Journal>,
+}
+
+impl OwnershipAction {
+    pub fn new(provider: Arc<RootProvider<BoxTransport>>, opts: OwnershipOptions) -> Self {
+        let registry = opts.registry_filepath.as_deref().map(load_registry).unwrap_or_default();
+        Self { provider, opts, registry: Arc::new(Mutex::new(registry)), journal: Mutex::new(Journal::default()) }
+    }
+
+    /// 某合约当前的控制者快照
+    pub fn control_state(&self, contract: &Address) -> Option<ControlState> {
+        self.registry.lock().unwrap_or_else(|e| e.into_inner()).get(contract).cloned()
+    }
+
+    fn save(&self, reg: &BTreeMap<Address, ControlState>) {
+        if let Some(path) = &self.opts.registry_filepath
+            && let Err(err) = persist::write_json_atomic(path, reg)
+        {
+            eprintln!("[access] write {path} failed: {err}");
+        }
+    }
+
+    /// 事件所在区块被重组：把合约恢复到该区块之前的状态
+    fn rewind(&self, e: &EventRecord) {
+        let Some(block) = e.block_number else { return };
+        let mut reg = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+        let mut journal = self.journal.lock().unwrap_or_else(|e| e.into_inner());
+        match journal.rewind(e.address, block) {
+            Some(before) => {
+                println!("[access] reorg contract={} rewound to before block={}", e.address, block);
+                if before == ControlState::default() {
+                    reg.remove(&e.address);
+                } else {
+                    reg.insert(e.address, before);
+                }
+                self.save(&reg);
+            }
+            None if !journal.covers(block) => {
+                eprintln!("[access] reorg contract={} block={} is older than the kept snapshots; state not rewound", e.address, block);
+            }
+            None => {}
+        }
+    }
+
+    fn alert(&self, e: &EventRecord, msg: &str) {
+        let line = format!("[access][alert] contract={} {} tx={:?} block={:?}", e.address, msg, e.tx_hash, e.block_number);
+        println!("{line}");
+        if let Some(url) = self.opts.webhook_url.clone() {
+            tokio::spawn(async move {
+                let _ = send_webhook(&url, &line).await;
+            });
+        }
+    }
+
+    /// 所有权转给（或待转给）一个没有代码、nonce 为 0 的地址
+    fn check_fresh_owner(&self, e: &EventRecord, new_owner: Address) {
+        let provider = self.provider.clone();
+        let webhook = self.opts.webhook_url.clone();
+        let (contract, tx, block) = (e.address, e.tx_hash, e.block_number);
+        tokio::spawn(async move {
+            if !is_fresh_eoa(&provider, new_owner, block).await {
+                return;
+            }
+            let line = format!("[access][alert] contract={contract} ownership moving to fresh EOA {new_owner} tx={tx:?} block={block:?}");
+            println!("{line}");
+            if let Some(url) = webhook {
+                let _ = send_webhook(&url, &line).await;
+            }
+        });
+    }
+}
+
+impl Action for OwnershipAction {
+    fn on_event(&self, e: &EventRecord) -> Result<()> {
+        let Some(ev) = decode_access_event(e) else { return Ok(()) };
+        if e.removed == Some(true) {
+            self.rewind(e);
+            return Ok(());
+        }
+        println!("[access] contract={} {} tx={:?} block={:?}", e.address, ev, e.tx_hash, e.block_number);
+
+        let alerts = {
+            let mut reg = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+            let state = reg.entry(e.address).or_default();
+            let alerts = immediate_alerts(&ev, state, &self.opts.critical_roles);
+            if let Some(block) = e.block_number {
+                self.journal.lock().unwrap_or_else(|e| e.into_inner()).record(e.address, block, state);
+            }
+            state.apply(&ev, e.block_number);
+            self.save(&reg);
+            alerts
+        };
+        for a in &alerts {
+            self.alert(e, a);
+        }
+        match ev {
+            AccessEvent::OwnershipTransferred { new, .. } | AccessEvent::OwnershipTransferStarted { new, .. } if !new.is_zero() => {
+                self.check_fresh_owner(e, new);
+            }
+            _ => {}
+        }
         Ok(())
     }
 }
+
+async fn is_fresh_eoa(provider: &RootProvider<BoxTransport>, addr: Address, block: Option<u64>) -> bool {
+    let id = block.map(BlockId::number).unwrap_or(BlockId::latest());
+    throttle::acquire().await;
+    let Ok(code) = provider.get_code_at(addr).block_id(id).await else { return false };
+    if !code.is_empty() {
+        return false;
+    }
+    throttle::acquire().await;
+    provider.get_transaction_count(addr).block_id(id).await.is_ok_and(|n| n == 0)
+}
+
+fn load_registry(path: &str) -> BTreeMap<Address, ControlState> {
+    persist::read_json(path)
+        .unwrap_or_else(|e| {
+            eprintln!("[access] ignore unreadable registry {path}: {e}");
+            None
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn record(topics: Vec<B256>) -> EventRecord {
+        EventRecord {
+            address: address!("00000000000000000000000000000000000000aa"),
+            tx_hash: None,
+            block_number: Some(7),
+            topic0: topics.first().copied(),
+            name: None,
+            fields: vec![],
+            tx_index: None,
+            log_index: None,
+            topics,
+            removed: None,
+        }
+    }
+
+    #[test]
+    fn tracks_control_state_and_alerts() {
+        let alice = address!("00000000000000000000000000000000000000a1");
+        let bob = address!("00000000000000000000000000000000000000b0");
+        let word = |a: Address| a.into_word();
+        let critical = DEFAULT_CRITICAL_ROLES.iter().map(|r| role_hash(r)).collect::<Vec<_>>();
+        let minter = role_hash("MINTER_ROLE");
+        let mut st = ControlState::default();
+
+        let run = |topics: Vec<B256>, st: &mut ControlState| {
+            let ev = decode_access_event(&record(topics)).unwrap();
+            let alerts = immediate_alerts(&ev, st, &critical);
+            st.apply(&ev, Some(7));
+            alerts
+        };
+
+        assert!(run(vec![OWNERSHIP_TRANSFERRED, B256::ZERO, word(alice)], &mut st).is_empty());
+        run(vec![OWNERSHIP_TRANSFER_STARTED, word(alice), word(bob)], &mut st);
+        assert_eq!((st.owner, st.pending_owner), (Some(alice), Some(bob)));
+
+        let alerts = run(vec![ROLE_GRANTED, minter, word(bob), word(alice)], &mut st);
+        assert_eq!(alerts, vec![format!("new MINTER_ROLE member {bob} (granted by {alice})")]);
+        // renounceRole: account == sender
+        let alerts = run(vec![ROLE_REVOKED, minter, word(bob), word(bob)], &mut st);
+        assert_eq!(alerts, vec![format!("{bob} renounced MINTER_ROLE")]);
+        assert!(st.roles.is_empty());
+
+        let alerts = run(vec![OWNERSHIP_TRANSFERRED, word(alice), B256::ZERO], &mut st);
+        assert_eq!(alerts, vec![format!("ownership renounced by {alice}")]);
+        assert_eq!((st.owner, st.pending_owner), (None, None));
+
+        // Safe v1.4 (indexed owner)
+        assert_eq!(run(vec![SAFE_ADDED_OWNER, word(alice)], &mut st), vec![format!("new Safe owner {alice}")]);
+        // Safe v1.3: owner only in data, decoded into fields
+        let mut v13 = record(vec![SAFE_ADDED_OWNER]);
+        v13.fields = vec![crate::abi::DecodedField { name: "owner".into(), ty: "address".into(), value: DecodedValue::Address(bob), indexed: false }];
+        assert_eq!(decode_access_event(&v13), Some(AccessEvent::SafeAddedOwner(bob)));
+        run(vec![SAFE_CHANGED_THRESHOLD, B256::from(U256::from(2))], &mut st);
+        let alerts = run(vec![SAFE_CHANGED_THRESHOLD, B256::from(U256::from(1))], &mut st);
+        assert_eq!(alerts, vec!["Safe threshold lowered 2 -> 1".to_string()]);
+        assert_eq!(st.safe_owners, BTreeSet::from([alice]));
+
+        let json = serde_json::to_string(&BTreeMap::from([(alice, st.clone())])).unwrap();
+        let back: BTreeMap<Address, ControlState> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back[&alice], st);
+        assert!(parse_role("0x12").is_err());
+        assert_eq!(parse_role("DEFAULT_ADMIN_ROLE").unwrap(), B256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn removed_events_rewind_the_reorged_blocks() {
+        let provider = crate::provider::connect_auto("http://127.0.0.1:1").await.unwrap();
+        let action = OwnershipAction::new(Arc::new(provider), parse_options(&serde_json::json!({}), None).unwrap());
+        let contract = address!("00000000000000000000000000000000000000aa");
+        let (alice, bob) = (address!("00000000000000000000000000000000000000a1"), address!("00000000000000000000000000000000000000b0"));
+        let at = |block: u64, topics: Vec<B256>, removed: bool| EventRecord { block_number: Some(block), removed: removed.then_some(true), ..record(topics) };
+        let pauser = role_hash("PAUSER_ROLE");
+
+        action.on_event(&at(7, vec![SAFE_ADDED_OWNER, alice.into_word()], false)).unwrap();
+        action.on_event(&at(8, vec![ROLE_GRANTED, pauser, bob.into_word(), alice.into_word()], false)).unwrap();
+        action.on_event(&at(9, vec![SAFE_CHANGED_THRESHOLD, B256::from(U256::from(2))], false)).unwrap();
+        action.on_event(&at(9, vec![SAFE_ADDED_OWNER, bob.into_word()], false)).unwrap();
+
+        // blocks 8.. are reorged out; removed logs arrive in any order
+        action.on_event(&at(9, vec![SAFE_ADDED_OWNER, bob.into_word()], true)).unwrap();
+        action.on_event(&at(8, vec![ROLE_GRANTED, pauser, bob.into_word(), alice.into_word()], true)).unwrap();
+        let st = action.control_state(&contract).unwrap();
+        assert_eq!((st.safe_owners, st.safe_threshold, st.roles.len(), st.last_block), (BTreeSet::from([alice]), None, 0, Some(7)));
+
+        action.on_event(&at(7, vec![SAFE_ADDED_OWNER, alice.into_word()], true)).unwrap();
+        assert_eq!(action.control_state(&contract), None);
+
+        let mut journal = Journal::default();
+        journal.record(contract, 10, &ControlState::default());
+        journal.record(contract, 10 + REORG_KEEP_BLOCKS + 1, &ControlState::default());
+        assert!(!journal.covers(10));
+        assert_eq!(journal.rewind(contract, 10), Some(ControlState::default()));
+        assert_eq!(journal.rewind(contract, 10), None);
+    }
+}
@@ -0,0 +1,361 @@
+use std::{collections::HashMap, fmt, sync::{Arc, Mutex}, time::Duration};
+
+use alloy_primitives::{Address, B256, U256, address, b256, keccak256};
+use alloy_provider::RootProvider;
+use alloy_transport::BoxTransport;
+use tokio::sync::{RwLock, Semaphore};
+
+use super::initscan::{
+    CallTracer, InitscanTraceBackend, KnownInit, SlotChange, StateDiff, eth_call_ok, fmt_slot_value,
+    implemented_selectors, load_known_from_file, save_known_to_file, send_webhook, synthesize_calldata,
+    word_holds_addr,
+};
+use super::proxy::{eip1967_admin_slot, eip1967_beacon_slot, eip1967_implementation_slot};
+use super::{Action, ContractCreationRecord, TxRecord};
+use crate::abi;
+use crate::error::AppError;
+use crate::findings::FindingStatus;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// Default caller: an address with no relation to any deployer
+pub const DEFAULT_SENDER: Address = address!("00000000000000000000000000000000deadbeef");
+
+// Name prefixes (lowercased) of functions that should be restricted to an owner / admin
+const PRIVILEGED_PREFIXES: &[&str] = &[
+    "transferownership", "setowner", "changeowner", "setadmin", "changeadmin", "upgradeto",
+    "mint", "withdraw", "emergencywithdraw", "sweep", "rescue", "kill", "destroy", "selfdestruct", "suicide",
+];
+// selector nothing implements; effects it also produces come from the fallback, not the probed function
+const RANDOM_SELECTOR: [u8; 4] = [0x6f, 0xcb, 0x83, 0x1b];
+// balanceOf mappings are looked up at these declaration slots of the token's storage
+const BALANCE_MAPPING_SLOTS: u64 = 16;
+// ERC-7201 namespace of OpenZeppelin's upgradeable ERC20 (`_balances` is its first field)
+const OZ_ERC20_STORAGE: B256 = b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00");
+// ERC-7201 namespaces of OpenZeppelin's upgradeable Ownable (`_owner`) and Ownable2Step (`_pendingOwner`)
+const OZ_OWNABLE_STORAGE: B256 = b256!("9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300");
+const OZ_OWNABLE2STEP_STORAGE: B256 = b256!("237e158222e3e6968b72b9db0d8043aacf074ad9f650f0d1606b4d82ee432c00");
+// declared state variables sit at small sequential slots; anything above is keccak-derived
+// (mapping / dynamic array entries such as an ERC-721 `ownerOf[id]` written by a mint)
+const MAX_FIXED_SLOT: u64 = u32::MAX as u64;
+// blocks a probed contract is remembered for; both reports of a deployment arrive in the same block
+const PROBED_KEEP_BLOCKS: u64 = 256;
+
+#[derive(Clone, Debug)]
+pub struct PrivscanOptions {
+    /// caller of the simulated calls; also used for address arguments
+    pub from: Address,
+    /// value for unsigned integer arguments (amounts); non-zero so mints / withdrawals move something
+    pub amount: U256,
+    pub probe_after_delay_secs: u64,
+    // limit concurrent contract probes; None or 0 => unlimited
+    pub max_inflight: Option<usize>,
+    pub webhook_url: Option<String>,
+    // flagged (contract, calldata) pairs, persisted like initscan's known list
+    pub findings_filepath: Option<String>,
+    pub trace_backend: InitscanTraceBackend,
+    pub debug: bool,
+}
+
+impl Default for PrivscanOptions {
+    fn default() -> Self {
+        Self {
+            from: DEFAULT_SENDER,
+            amount: U256::from(1),
+            probe_after_delay_secs: 1,
+            max_inflight: None,
+            webhook_url: None,
+            findings_filepath: None,
+            trace_backend: InitscanTraceBackend::Auto,
+            debug: false,
+        }
+    }
+}
+
+/// A state change caused by an unprivileged call that only an owner should be able to cause
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Effect {
+    /// a fixed storage slot of the target now holds the sender (owner / admin takeover)
+    Ownership(SlotChange),
+    /// an EIP-1967 implementation / admin / beacon slot was rewritten
+    CriticalSlot(&'static str, SlotChange),
+    /// the sender's native balance grew or the target's shrank
+    NativeBalance { account: Address, from: U256, to: U256 },
+    /// the sender's ERC-20 balance slot in the target's storage grew
+    TokenBalance(SlotChange),
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Effect::Ownership(c) => write!(f, "ownership slot {} {} -> {}", c.slot, fmt_slot_value(c.from), fmt_slot_value(c.to)),
+            Effect::CriticalSlot(name, c) => write!(f, "{name} slot {} -> {}", fmt_slot_value(c.from), fmt_slot_value(c.to)),
+            Effect::NativeBalance { account, from, to } => write!(f, "native balance of {account} {from} -> {to}"),
+            Effect::TokenBalance(c) => write!(f, "token balance slot {} {} -> {}", c.slot, fmt_slot_value(c.from), fmt_slot_value(c.to)),
+        }
+    }
+}
+
+/// Simulates privileged functions of new deployments from an unrelated sender and alerts on those
+/// that succeed and take ownership, move balances or rewrite proxy slots
+pub struct PrivscanAction {
+    provider: Arc<RootProvider<BoxTransport>>,
+    opts: PrivscanOptions,
+    tracer: CallTracer,
+    known: Arc<RwLock<Vec<KnownInit>>>,
+    sem: Option<Arc<Semaphore>>,
+    // a top-level deployment reaches us through both on_tx and on_contract_creation; contract -> block
+    probed: Arc<Mutex<HashMap<Address, u64>>>,
+}
+
+impl PrivscanAction {
+    pub fn new(provider: Arc<RootProvider<BoxTransport>>, opts: PrivscanOptions) -> Self {
+        let known = opts
+            .findings_filepath
+            .as_deref()
+            .map(|p| load_known_from_file(p).unwrap_or_default())
+            .unwrap_or_default();
+        let sem = opts
+            .max_inflight
+            .and_then(|n| if n > 0 { Some(Arc::new(Semaphore::new(n))) } else { None });
+        let tracer = CallTracer::new(provider.clone(), opts.trace_backend, "privscan");
+        Self { provider, opts, tracer, known: Arc::new(RwLock::new(known)), sem, probed: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    fn clone_for_task(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            opts: self.opts.clone(),
+            tracer: self.tracer.clone(),
+            known: self.known.clone(),
+            sem: self.sem.clone(),
+            probed: self.probed.clone(),
+        }
+    }
+
+    #[inline]
+    fn dbg<S: AsRef<str>>(&self, s: S) { if self.opts.debug { println!("[privscan][debug] {}", s.as_ref()); } }
+
+    /// Privileged-looking functions of the contract's dispatcher (or its implementation's), with calldata
+    async fn candidates(&self, contract: Address, block_number: Option<u64>) -> Vec<(String, Vec<u8>)> {
+        let selectors = implemented_selectors(self.provider.as_ref(), contract, block_number).await;
+        let sigs = abi::sigs();
+        let mut out = vec![];
+        for sel in &selectors {
+            let Some(entry) = sigs.funcs.get(&format!("0x{}", hex::encode(sel))) else { continue };
+            if !looks_privileged(&entry.name) {
+                continue;
+            }
+            match synthesize_calldata(&entry.abi, Some(self.opts.from), self.opts.amount) {
+                Some(data) => out.push((entry.sig.clone(), data)),
+                None => self.dbg(format!("skip {}: unsupported argument types", entry.sig)),
+            }
+        }
+        out
+    }
+
+    /// Effects of calling `calldata` from the sender; empty when the call reverts or the trace fails
+    async fn effects(&self, contract: Address, calldata: &[u8], block_number: Option<u64>) -> Result<Vec<Effect>> {
+        let from = Some(self.opts.from);
+        if !eth_call_ok(self.provider.as_ref(), from, contract, calldata, block_number).await.unwrap_or(false) {
+            return Ok(vec![]);
+        }
+        let Some(diff) = self.tracer.state_diff(from, contract, calldata, block_number).await? else { return Ok(vec![]) };
+        Ok(classify_effects(&diff, contract, self.opts.from))
+    }
+
+    pub async fn probe_contract(&self, contract: Address, block_number: Option<u64>) {
+        let candidates = self.candidates(contract, block_number).await;
+        self.dbg(format!("candidates for 0x{}: {:?}", hex::encode(contract.0), candidates.iter().map(|(sig, _)| sig).collect::<Vec<_>>()));
+        if candidates.is_empty() {
+            return;
+        }
+        // effects the fallback produces for any selector are not attributable to a function
+        let baseline = match self.effects(contract, &RANDOM_SELECTOR, block_number).await {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("[privscan] error on {contract:?}: {e}");
+                return;
+            }
+        };
+        for (sig, calldata) in &candidates {
+            let effects: Vec<Effect> = match self.effects(contract, calldata, block_number).await {
+                Ok(v) => v.into_iter().filter(|e| !baseline.contains(e)).collect(),
+                Err(e) => {
+                    eprintln!("[privscan] error on {contract:?} {sig}: {e}");
+                    continue;
+                }
+            };
+            self.dbg(format!("{sig}: {} effects", effects.len()));
+            if !effects.is_empty() {
+                self.report(contract, sig, calldata, &effects).await;
+            }
+        }
+    }
+
+    async fn report(&self, contract: Address, sig: &str, calldata: &[u8], effects: &[Effect]) {
+        if let Some(path) = &self.opts.findings_filepath {
+            let mut w = self.known.write().await;
+            if w.iter().any(|k| k.contract == contract && k.calldata == calldata) {
+                return;
+            }
+            w.push(KnownInit { contract, calldata: calldata.to_vec(), status: FindingStatus::Open });
+            if let Err(e) = save_known_to_file(path, &w) {
+                eprintln!("[privscan] failed to save {path}: {e}");
+            }
+        }
+        let mut msg = format!(
+            "# Unprotected privileged function\nAddress: 0x{}\nfunction: {}\nsender: {}\ncalldata: 0x{}\neffects:\n",
+            hex::encode(contract.0), sig, self.opts.from, hex::encode(calldata)
+        );
+        for e in effects {
+            msg.push_str(&format!("  - {e}\n"));
+        }
+        match &self.opts.webhook_url {
+            Some(url) => { let _ = send_webhook(url, &msg).await; }
+            None => println!("[privscan] {}", msg.replace('\n', " ")),
+        }
+    }
+
+    fn spawn_probe(&self, contract: Address, block_number: Option<u64>) {
+        {
+            let mut probed = self.probed.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(bn) = block_number {
+                probed.retain(|_, seen| *seen + PROBED_KEEP_BLOCKS >= bn);
+            }
+            if probed.insert(contract, block_number.unwrap_or_default()).is_some() {
+                return;
+            }
+        }
+        let this = self.clone_for_task();
+        tokio::spawn(async move {
+            let _permit = match &this.sem {
+                Some(s) => Some(s.clone().acquire_owned().await.expect("semaphore closed")),
+                None => None,
+            };
+            if this.opts.probe_after_delay_secs > 0 {
+                tokio::time::sleep(Duration::from_secs(this.opts.probe_after_delay_secs)).await;
+            }
+            this.probe_contract(contract, block_number).await;
+        });
+    }
+}
+
+impl Action for PrivscanAction {
+    fn on_tx(&self, t: &TxRecord) -> Result<()> {
+        if let Some(contract) = t.contract_address {
+            self.spawn_probe(contract, t.block_number);
+        }
+        Ok(())
+    }
+
+    fn on_contract_creation(&self, c: &ContractCreationRecord) -> Result<()> {
+        self.spawn_probe(c.contract_address, Some(c.block_number));
+        Ok(())
+    }
+}
+
+fn looks_privileged(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    PRIVILEGED_PREFIXES.iter().any(|p| n.starts_with(p))
+}
+
+/// Storage slots of `holder`'s entry in the usual balanceOf mapping locations
+fn balance_slots(holder: Address) -> Vec<B256> {
+    let key = B256::left_padding_from(holder.as_slice());
+    (0..BALANCE_MAPPING_SLOTS)
+        .map(|n| B256::from(U256::from(n)))
+        .chain([OZ_ERC20_STORAGE])
+        .map(|base| keccak256([key, base].concat()))
+        .collect()
+}
+
+/// Slots an owner / admin variable can live at: declaration slots and the OpenZeppelin Ownable namespaces
+fn is_fixed_slot(slot: B256) -> bool {
+    U256::from_be_bytes(slot.0) <= U256::from(MAX_FIXED_SLOT)
+        || slot == OZ_OWNABLE_STORAGE
+        || slot == OZ_OWNABLE2STEP_STORAGE
+}
+
+fn classify_effects(diff: &StateDiff, target: Address, sender: Address) -> Vec<Effect> {
+    let proxy_slots = [
+        (eip1967_implementation_slot(), "eip1967 implementation"),
+        (eip1967_admin_slot(), "eip1967 admin"),
+        (eip1967_beacon_slot(), "eip1967 beacon"),
+    ];
+    let balance_slots = balance_slots(sender);
+    let word = |w: Option<B256>| w.map(|w| U256::from_be_bytes(w.0)).unwrap_or_default();
+    let mut out = vec![];
+    for c in diff.storage.get(&target).into_iter().flatten() {
+        if let Some((_, name)) = proxy_slots.iter().find(|(s, _)| *s == c.slot) {
+            out.push(Effect::CriticalSlot(name, c.clone()));
+        } else if is_fixed_slot(c.slot)
+            && c.to.is_some_and(|w| word_holds_addr(&w, &sender))
+            && !c.from.is_some_and(|w| word_holds_addr(&w, &sender))
+        {
+            out.push(Effect::Ownership(c.clone()));
+        } else if balance_slots.contains(&c.slot) && word(c.to) > word(c.from) {
+            out.push(Effect::TokenBalance(c.clone()));
+        }
+    }
+    for (account, &(from, to)) in &diff.balances {
+        if (*account == sender && to > from) || (*account == target && to < from) {
+            out.push(Effect::NativeBalance { account: *account, from, to });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_privileged_effects() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let other = address!("00000000000000000000000000000000000000bb");
+        let me = DEFAULT_SENDER;
+        let change = |slot: B256, from: Option<B256>, to: Option<B256>| SlotChange { slot, from, to };
+        let mut diff = StateDiff::default();
+        diff.storage.insert(target, vec![
+            // owner := me
+            change(B256::ZERO, Some(B256::with_last_byte(1)), Some(B256::left_padding_from(me.as_slice()))),
+            change(eip1967_implementation_slot(), None, Some(B256::with_last_byte(2))),
+            // balances[me] at mapping slot 0: 0 -> 1
+            change(keccak256([B256::left_padding_from(me.as_slice()), B256::ZERO].concat()), None, Some(B256::with_last_byte(1))),
+            // unrelated counter
+            change(B256::with_last_byte(9), Some(B256::with_last_byte(1)), Some(B256::with_last_byte(2))),
+            // ownerOf[7] := me at mapping slot 2 (an NFT mint, not a takeover)
+            change(keccak256([B256::with_last_byte(7), B256::with_last_byte(2)].concat()), None, Some(B256::left_padding_from(me.as_slice()))),
+        ]);
+        // writes to other accounts never count
+        diff.storage.insert(other, vec![change(B256::ZERO, None, Some(B256::left_padding_from(me.as_slice())))]);
+        diff.balances.insert(target, (U256::from(10), U256::ZERO));
+        diff.balances.insert(other, (U256::ZERO, U256::from(10)));
+
+        let effects = classify_effects(&diff, target, me);
+        assert_eq!(effects.len(), 4);
+        assert!(matches!(effects[0], Effect::Ownership(_)));
+        assert!(matches!(effects[1], Effect::CriticalSlot("eip1967 implementation", _)));
+        assert!(matches!(effects[2], Effect::TokenBalance(_)));
+        assert_eq!(effects[3], Effect::NativeBalance { account: target, from: U256::from(10), to: U256::ZERO });
+
+        // namespaced Ownable storage counts as a fixed slot
+        let namespace = |id: &str| {
+            let base = U256::from_be_bytes(keccak256(id).0) - U256::from(1);
+            B256::from(keccak256(B256::from(base)).0) & !B256::with_last_byte(0xff)
+        };
+        assert_eq!(namespace("openzeppelin.storage.Ownable"), OZ_OWNABLE_STORAGE);
+        assert_eq!(namespace("openzeppelin.storage.Ownable2Step"), OZ_OWNABLE2STEP_STORAGE);
+        let mut diff = StateDiff::default();
+        diff.storage.insert(target, vec![change(OZ_OWNABLE_STORAGE, None, Some(B256::left_padding_from(me.as_slice())))]);
+        assert!(matches!(classify_effects(&diff, target, me)[..], [Effect::Ownership(_)]));
+
+        for name in ["transferOwnership", "mint", "mintTo", "withdrawAll", "emergencyWithdraw", "upgradeToAndCall", "kill"] {
+            assert!(looks_privileged(name), "{name}");
+        }
+        for name in ["balanceOf", "transfer", "initialize", "deposit"] {
+            assert!(!looks_privileged(name), "{name}");
+        }
+    }
+}
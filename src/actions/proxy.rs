@@ -1,30 +1,315 @@
-use super::{Action, EventRecord};
-use crate::error::Result;
+use super::bytecode::{DELEGATECALL, SELFDESTRUCT, has_opcode, push4_selectors};
+use super::initscan::{self, CallTracer, InitscanTraceBackend};
+use super::{Action, BlockRecord, EventRecord};
+use crate::abi::DecodedValue;
+use crate::error::{AppError, Result};
+use crate::persist;
 use crate::throttle;
-use alloy_primitives::{Address, B256, U256};
+use alloy_primitives::{Address, B256, Bytes, U256, b256};
 use alloy_provider::Provider;
 use alloy_provider::RootProvider;
+use alloy_rpc_types_eth::{BlockId, TransactionRequest, transaction::TransactionInput};
 use alloy_transport::BoxTransport;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::mpsc;
 
 // Complete ERC-1967 Proxy Storage Slots detector
 // - Upgraded(address indexed implementation)
 // - ImplementationUpgraded(address indexed newImplementation)  
 // - AdminChanged(address previousAdmin, address newAdmin)
 // - BeaconUpgraded(address indexed beacon)
+// - DiamondCut((address,uint8,bytes4[])[] diamondCut, address init, bytes calldata)  (EIP-2535)
 
-pub struct ProxyUpgradeAction {
+// 每次升级都记入按代理划分的历史；实现变更时拉取新旧实现的字节码做对比
+// （selector 增删、代码大小变化、新出现的 DELEGATECALL / SELFDESTRUCT），结果附在检测行上。
+// 新实现若 `proxiableUUID()` 返回实现槽位则标记为 UUPS。
+// 开启 `watch-slots` 时逐块取存储 diff，实现 / beacon 槽位被改写却没有对应事件的也记为升级。
+
+/// keccak256("DiamondCut((address,uint8,bytes4[])[],address,bytes)")
+pub const DIAMOND_CUT_TOPIC: B256 = b256!("8faa70878671ccd212d20771b795c50af8fd3ff6cf27f4bde57e5d4de0aeb673");
+
+/// bytes4(keccak256("proxiableUUID()"))
+const PROXIABLE_UUID: [u8; 4] = [0x52, 0xd1, 0x90, 0x2d];
+
+// 已由事件处理过的 (代理, 交易) 保留的区块数，槽位监视据此跳过
+const EVENTED_KEEP_BLOCKS: u64 = 256;
+
+/// ProxyUpgrade 配置
+#[derive(Debug, Clone, Default)]
+pub struct ProxyOptions {
+    /// 升级历史 JSON 文件；未配置时只保存在内存中
+    pub history_filepath: Option<String>,
+    /// 逐块追踪存储 diff，发现没有发出事件的升级
+    pub watch_slots: bool,
+    pub trace_backend: InitscanTraceBackend,
+    /// 槽位监视只关注这些合约；为空时关注全部
+    pub watch_addresses: HashSet<Address>,
+}
+
+pub fn parse_options(o: &serde_json::Value, addresses: &HashMap<String, serde_json::Value>) -> Result<ProxyOptions> {
+    let trace_backend = match o.get("trace-backend").and_then(|v| v.as_str()) {
+        Some(s) => InitscanTraceBackend::from_str(s)?,
+        None => InitscanTraceBackend::Auto,
+    };
+    let watch_addresses = addresses
+        .keys()
+        .map(|s| s.parse().map_err(|e| AppError::Config(format!("invalid ProxyUpgrade address {s}: {e}"))))
+        .collect::<Result<_>>()?;
+    Ok(ProxyOptions {
+        history_filepath: o.get("history-filepath").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+        watch_slots: o.get("watch-slots").and_then(|v| v.as_bool()).unwrap_or(false),
+        trace_backend,
+        watch_addresses,
+    })
+}
+
+/// 新旧实现字节码的差异摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplDiff {
+    pub old_code_size: usize,
+    pub new_code_size: usize,
+    pub added_selectors: Vec<String>,
+    pub removed_selectors: Vec<String>,
+    /// 旧实现没有而新实现出现的 DELEGATECALL
+    pub new_delegatecall: bool,
+    /// 旧实现没有而新实现出现的 SELFDESTRUCT
+    pub new_selfdestruct: bool,
+}
+
+impl ImplDiff {
+    pub fn between(old: &[u8], new: &[u8]) -> Self {
+        let (old_sels, new_sels) = (push4_selectors(old), push4_selectors(new));
+        let fmt_sel = |s: &[u8; 4]| format!("0x{}", hex::encode(s));
+        Self {
+            old_code_size: old.len(),
+            new_code_size: new.len(),
+            added_selectors: new_sels.difference(&old_sels).map(fmt_sel).collect(),
+            removed_selectors: old_sels.difference(&new_sels).map(fmt_sel).collect(),
+            new_delegatecall: has_opcode(new, DELEGATECALL) && !has_opcode(old, DELEGATECALL),
+            new_selfdestruct: has_opcode(new, SELFDESTRUCT) && !has_opcode(old, SELFDESTRUCT),
+        }
+    }
+}
+
+impl fmt::Display for ImplDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "code_size={}->{}({:+}) selectors_added=[{}] selectors_removed=[{}] new_delegatecall={} new_selfdestruct={}",
+            self.old_code_size,
+            self.new_code_size,
+            self.new_code_size as i64 - self.old_code_size as i64,
+            self.added_selectors.join(","),
+            self.removed_selectors.join(","),
+            self.new_delegatecall,
+            self.new_selfdestruct
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpgradeKind {
+    Implementation,
+    Admin,
+    Beacon,
+    #[serde(rename = "diamondCut")]
+    DiamondCut,
+}
+
+/// 升级的发现途径
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Via {
+    /// 代理发出的升级事件
+    #[default]
+    Event,
+    /// 存储 diff 中 EIP-1967 槽位的改写（没有对应事件）
+    Slot,
+}
+
+impl Via {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Via::Event => "event",
+            Via::Slot => "slot",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FacetCutAction {
+    Add,
+    Replace,
+    Remove,
+}
+
+impl FacetCutAction {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Add),
+            1 => Some(Self::Replace),
+            2 => Some(Self::Remove),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            FacetCutAction::Add => "add",
+            FacetCutAction::Replace => "replace",
+            FacetCutAction::Remove => "remove",
+        }
+    }
+}
+
+/// `DiamondCut` 中的一项 facet 操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCut {
+    pub facet: Address,
+    pub action: FacetCutAction,
+    pub selectors: Vec<String>,
+}
+
+/// 一次升级记录；`old` / `new` 按 kind 分别是实现、管理员或 beacon 地址，
+/// DiamondCut 的 `new` 为 `_init` 合约
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeEntry {
+    pub kind: UpgradeKind,
+    pub old: Option<Address>,
+    pub new: Option<Address>,
+    /// 记录时的管理员
+    pub admin: Option<Address>,
+    pub block: Option<u64>,
+    pub tx: Option<B256>,
+    #[serde(default)]
+    pub via: Via,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ImplDiff>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cuts: Vec<FacetCut>,
+}
+
+/// 某代理的当前状态与升级历史
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyHistory {
+    pub implementation: Option<Address>,
+    pub admin: Option<Address>,
+    pub beacon: Option<Address>,
+    /// 当前实现的 `proxiableUUID()` 是否返回实现槽位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uups: Option<bool>,
+    /// Diamond 的 selector → facet 表
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: BTreeMap<String, Address>,
+    pub upgrades: Vec<UpgradeEntry>,
+}
+
+impl ProxyHistory {
+    /// 同一次升级（类型、区块、新地址相同）是否已有事件记录
+    fn has_event_record(&self, kind: UpgradeKind, block: Option<u64>, new: Option<Address>) -> bool {
+        self.upgrades.iter().any(|u| u.via == Via::Event && u.same_upgrade(kind, block, new))
+    }
+
+    /// 追加一条记录并更新当前状态；返回是否追加。
+    /// 事件与槽位监视可能以任意顺序报告同一次升级：已有事件记录时丢弃槽位记录；
+    /// 事件晚到时与先前的槽位记录合并，旧地址与代码 diff 以槽位记录为准（取自存储 diff 的原值，
+    /// 而事件路径此时从历史中读到的“旧实现”已是新地址）
+    fn apply(&mut self, mut entry: UpgradeEntry) -> bool {
+        if let Some(i) = self.upgrades.iter().position(|u| u.same_upgrade(entry.kind, entry.block, entry.new)) {
+            match (self.upgrades[i].via, entry.via) {
+                (Via::Event, Via::Slot) => return false,
+                (Via::Slot, Via::Event) => {
+                    let slot = self.upgrades.remove(i);
+                    entry.old = slot.old.or(entry.old);
+                    entry.diff = slot.diff.or(entry.diff);
+                }
+                _ => {}
+            }
+        }
+        match entry.kind {
+            UpgradeKind::Implementation => self.implementation = entry.new.or(self.implementation),
+            UpgradeKind::Admin => self.admin = entry.new.or(self.admin),
+            UpgradeKind::Beacon => self.beacon = entry.new.or(self.beacon),
+            UpgradeKind::DiamondCut => {
+                for cut in &entry.cuts {
+                    for sel in &cut.selectors {
+                        match cut.action {
+                            FacetCutAction::Add | FacetCutAction::Replace => {
+                                self.facets.insert(sel.clone(), cut.facet);
+                            }
+                            FacetCutAction::Remove => {
+                                self.facets.remove(sel);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if entry.admin.is_some() {
+            self.admin = entry.admin;
+        }
+        self.upgrades.push(entry);
+        true
+    }
+}
+
+impl UpgradeEntry {
+    fn same_upgrade(&self, kind: UpgradeKind, block: Option<u64>, new: Option<Address>) -> bool {
+        self.kind == kind && block.is_some() && self.block == block && self.new == new
+    }
+}
+
+type HistoryMap = Arc<Mutex<BTreeMap<Address, ProxyHistory>>>;
+
+/// 升级处理的共享状态，供事件与槽位监视两条路径在后台任务中使用
+#[derive(Clone)]
+struct Tracker {
     provider: Arc<RootProvider<BoxTransport>>,
+    history: HistoryMap,
+    path: Option<String>,
+}
+
+pub struct ProxyUpgradeAction {
+    tracker: Tracker,
+    /// 仅在 `watch-slots` 时存在
+    tracer: Option<CallTracer>,
+    watch_addresses: Arc<HashSet<Address>>,
+    /// 已由事件处理的 (代理, 交易) → 区块号
+    evented: Arc<Mutex<HashMap<(Address, B256), u64>>>,
+    /// 槽位监视任务的区块队列，首个区块到达时启动
+    slot_blocks: OnceLock<mpsc::UnboundedSender<u64>>,
 }
 
 impl ProxyUpgradeAction {
-    pub fn new(provider: Arc<RootProvider<BoxTransport>>) -> Self {
-        Self { provider }
+    pub fn new(provider: Arc<RootProvider<BoxTransport>>, opts: ProxyOptions) -> Self {
+        let history = opts.history_filepath.as_deref().map(load_history).unwrap_or_default();
+        let tracer = opts.watch_slots.then(|| CallTracer::new(provider.clone(), opts.trace_backend, "proxy-upgrade"));
+        Self {
+            tracker: Tracker { provider, history: Arc::new(Mutex::new(history)), path: opts.history_filepath },
+            tracer,
+            watch_addresses: Arc::new(opts.watch_addresses),
+            evented: Arc::new(Mutex::new(HashMap::new())),
+            slot_blocks: OnceLock::new(),
+        }
+    }
+
+    /// 某代理的升级历史
+    pub fn history(&self, proxy: &Address) -> Option<ProxyHistory> {
+        current(&self.tracker.history, proxy)
     }
 }
 
 // ERC-1967 Standard Storage Slots
-fn eip1967_implementation_slot() -> B256 {
+pub fn eip1967_implementation_slot() -> B256 {
     // keccak256("eip1967.proxy.implementation") - 1
     // 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc
     B256::from_slice(
@@ -32,7 +317,7 @@ fn eip1967_implementation_slot() -> B256 {
     )
 }
 
-fn eip1967_admin_slot() -> B256 {
+pub fn eip1967_admin_slot() -> B256 {
     // keccak256("eip1967.proxy.admin") - 1
     // 0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103
     B256::from_slice(
@@ -40,7 +325,7 @@ fn eip1967_admin_slot() -> B256 {
     )
 }
 
-fn eip1967_beacon_slot() -> B256 {
+pub fn eip1967_beacon_slot() -> B256 {
     // keccak256("eip1967.proxy.beacon") - 1
     // 0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50
     B256::from_slice(
@@ -48,7 +333,7 @@ fn eip1967_beacon_slot() -> B256 {
     )
 }
 
-fn eip1967_rollback_slot() -> B256 {
+pub fn eip1967_rollback_slot() -> B256 {
     // keccak256("eip1967.proxy.rollback") - 1 
     // 0x4910fdfa16fed3260ed0e7147f7cc6da11a60208b5b9406d12a635614ffd9143
     B256::from_slice(
@@ -62,159 +347,598 @@ fn right_most_20(bytes: &[u8]) -> Address {
     Address::from(a)
 }
 
+/// 读取存储槽中的地址（取低 20 字节）；RPC 失败返回 None，空槽返回零地址
+pub async fn read_slot_address(
+    provider: &RootProvider<BoxTransport>,
+    contract: Address,
+    slot: B256,
+) -> Option<Address> {
+    read_slot_address_at(provider, contract, slot, BlockId::latest()).await
+}
+
+/// 同 [`read_slot_address`]，读取指定区块的状态
+pub async fn read_slot_address_at(
+    provider: &RootProvider<BoxTransport>,
+    contract: Address,
+    slot: B256,
+    block: BlockId,
+) -> Option<Address> {
+    throttle::acquire().await;
+    let word = provider
+        .get_storage_at(contract, U256::from_be_slice(slot.as_slice()))
+        .block_id(block)
+        .await
+        .ok()?;
+    Some(right_most_20(&word.to_be_bytes::<32>()))
+}
+
+/// 调用 beacon 合约的 `implementation()`
+pub async fn beacon_implementation(
+    provider: &RootProvider<BoxTransport>,
+    beacon: Address,
+) -> Option<Address> {
+    // bytes4(keccak256("implementation()"))
+    let tx = TransactionRequest::default()
+        .to(beacon)
+        .input(TransactionInput::new(Bytes::from_static(&[0x5c, 0x60, 0xda, 0x1b])));
+    throttle::acquire().await;
+    let out = provider.call(&tx).await.ok()?;
+    (out.len() >= 32).then(|| right_most_20(&out[..32]))
+}
+
+fn non_zero(a: Option<Address>) -> Option<Address> {
+    a.filter(|a| *a != Address::ZERO)
+}
+
+/// 调用实现合约的 `proxiableUUID()`（ERC-1822）；UUPS 实现返回 EIP-1967 实现槽位
+pub async fn is_uups(provider: &RootProvider<BoxTransport>, implementation: Address, block: BlockId) -> Option<bool> {
+    let tx = TransactionRequest::default()
+        .to(implementation)
+        .input(TransactionInput::new(Bytes::from_static(&PROXIABLE_UUID)));
+    throttle::acquire().await;
+    match provider.call(&tx).block(block).await {
+        Ok(out) => Some(out.len() >= 32 && out[..32] == eip1967_implementation_slot()[..]),
+        // 节点明确回滚：没有实现该函数
+        Err(e) if e.as_error_resp().is_some() => Some(false),
+        Err(_) => None,
+    }
+}
+
+/// 解析 `DiamondCut` 事件为 facet 操作列表与 `_init` 地址
+pub fn decode_diamond_cut(e: &EventRecord) -> Option<(Vec<FacetCut>, Option<Address>)> {
+    let DecodedValue::Array(items) = &e.fields.first()?.value else { return None };
+    let mut cuts = Vec::with_capacity(items.len());
+    for item in items {
+        let DecodedValue::Tuple(parts) = item else { return None };
+        let [(_, DecodedValue::Address(facet)), (_, DecodedValue::Uint(action)), (_, DecodedValue::Array(sels))] = parts.as_slice() else {
+            return None;
+        };
+        let action = FacetCutAction::from_u8(u8::try_from(*action).ok()?)?;
+        let selectors = sels
+            .iter()
+            .filter_map(|v| match v {
+                DecodedValue::FixedBytes(b) if b.len() == 4 => Some(format!("0x{}", hex::encode(b))),
+                _ => None,
+            })
+            .collect();
+        cuts.push(FacetCut { facet: *facet, action, selectors });
+    }
+    let init = match e.fields.get(1).map(|f| &f.value) {
+        Some(DecodedValue::Address(a)) => non_zero(Some(*a)),
+        _ => None,
+    };
+    Some((cuts, init))
+}
+
 impl Action for ProxyUpgradeAction {
     fn on_event(&self, e: &EventRecord) -> Result<()> {
         if let Some(name) = &e.name {
             match name.as_str() {
                 "Upgraded" | "ImplementationUpgraded" => {
+                    self.mark_evented(e);
                     self.handle_implementation_upgrade(e);
                 }
                 "AdminChanged" => {
                     self.handle_admin_change(e);
                 }
                 "BeaconUpgraded" => {
+                    self.mark_evented(e);
                     self.handle_beacon_upgrade(e);
                 }
+                "DiamondCut" => {
+                    self.handle_diamond_cut(e);
+                }
                 _ => {}
             }
         }
         Ok(())
     }
+
+    fn on_block(&self, b: &BlockRecord) -> Result<()> {
+        let Some(tracer) = &self.tracer else { return Ok(()) };
+        self.evented
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, bn| *bn + EVENTED_KEEP_BLOCKS >= b.number);
+
+        // 区块按顺序交给同一个后台任务，历史中的“当前实现”不会被更晚的区块抢先改写
+        let blocks = self.slot_blocks.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let worker = SlotWorker {
+                tracer: tracer.clone(),
+                tracker: self.tracker.clone(),
+                watch: self.watch_addresses.clone(),
+                evented: self.evented.clone(),
+            };
+            tokio::spawn(worker.run(rx));
+            tx
+        });
+        let _ = blocks.send(b.number);
+        Ok(())
+    }
 }
 
-impl ProxyUpgradeAction {
-    fn handle_implementation_upgrade(&self, e: &EventRecord) {
-        let mut impl_addr = None;
-        for f in &e.fields {
-            let key = f.name.to_lowercase();
-            if key.contains("implementation") {
-                impl_addr = Some(format!("{:?}", f.value));
-                break;
+/// 槽位监视：逐块取存储 diff，记录没有对应事件的 EIP-1967 槽位改写
+struct SlotWorker {
+    tracer: CallTracer,
+    tracker: Tracker,
+    watch: Arc<HashSet<Address>>,
+    evented: Arc<Mutex<HashMap<(Address, B256), u64>>>,
+}
+
+impl SlotWorker {
+    /// Action 被替换（热重载）后发送端随之释放，任务退出
+    async fn run(self, mut blocks: mpsc::UnboundedReceiver<u64>) {
+        while let Some(n) = blocks.recv().await {
+            self.check_block(n).await;
+        }
+    }
+
+    // 实时轮询与历史回放都先调用 on_block、再分发该区块的事件，而 trace 在本任务中稍后完成：
+    // 同块事件通常已登记在 evented 中，但不保证；两条路径都可能先写入历史，由 ProxyHistory::apply 去重
+    async fn check_block(&self, n: u64) {
+        let diffs = match self.tracer.block_state_diffs(n).await {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("[proxy-upgrade] block {} state diff failed: {}", n, e);
+                return;
             }
+        };
+        let (impl_slot, beacon_slot) = (eip1967_implementation_slot(), eip1967_beacon_slot());
+        for (tx, diff) in diffs {
+            for (proxy, changes) in &diff.storage {
+                if !self.watch.is_empty() && !self.watch.contains(proxy) {
+                    continue;
+                }
+                if let Some(tx) = tx
+                    && self.evented.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&(*proxy, tx))
+                {
+                    continue;
+                }
+                for c in changes {
+                    let old = non_zero(c.from.map(|w| right_most_20(w.as_slice())));
+                    let new = non_zero(c.to.map(|w| right_most_20(w.as_slice())));
+                    if c.slot == impl_slot {
+                        self.tracker.implementation_changed(*proxy, old, new, tx, Some(n), Via::Slot).await;
+                    } else if c.slot == beacon_slot {
+                        self.tracker.beacon_changed(*proxy, old, new, tx, Some(n), Via::Slot).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ProxyUpgradeAction {
+    fn mark_evented(&self, e: &EventRecord) {
+        if let (Some(tx), Some(bn)) = (e.tx_hash, e.block_number) {
+            self.evented.lock().unwrap_or_else(|e| e.into_inner()).insert((e.address, tx), bn);
         }
-        
-        let provider = self.provider.clone();
+    }
+
+    fn handle_implementation_upgrade(&self, e: &EventRecord) {
+        let impl_addr = field_address(e, |k| k.contains("implementation")).or_else(|| topic_address(e, 1));
+
+        let tracker = self.tracker.clone();
         let proxy = e.address;
         let txh = e.tx_hash;
         let bn = e.block_number;
-        
+
         tokio::spawn(async move {
-            // Read all ERC-1967 slots for comprehensive proxy state
-            let impl_slot_u256 = U256::from_be_slice(eip1967_implementation_slot().as_slice());
-            let admin_slot_u256 = U256::from_be_slice(eip1967_admin_slot().as_slice());
-            let beacon_slot_u256 = U256::from_be_slice(eip1967_beacon_slot().as_slice());
-            
-            throttle::acquire().await;
-            
-            // Read implementation slot
-            let onchain_impl = match provider.get_storage_at(proxy, impl_slot_u256).await {
-                Ok(bytes) => {
-                    let be: [u8; 32] = bytes.to_be_bytes::<32>();
-                    Some(right_most_20(&be))
-                }
-                Err(_) => None,
-            };
-            
-            // Read admin slot 
-            let onchain_admin = match provider.get_storage_at(proxy, admin_slot_u256).await {
-                Ok(bytes) => {
-                    let be: [u8; 32] = bytes.to_be_bytes::<32>();
-                    let addr = right_most_20(&be);
-                    if addr == Address::ZERO { None } else { Some(addr) }
-                }
-                Err(_) => None,
+            let provider = &tracker.provider;
+            let new_impl = match impl_addr {
+                Some(a) => Some(a),
+                None => non_zero(read_slot_address(provider, proxy, eip1967_implementation_slot()).await),
             };
-            
-            // Read beacon slot
-            let onchain_beacon = match provider.get_storage_at(proxy, beacon_slot_u256).await {
-                Ok(bytes) => {
-                    let be: [u8; 32] = bytes.to_be_bytes::<32>();
-                    let addr = right_most_20(&be);
-                    if addr == Address::ZERO { None } else { Some(addr) }
-                }
-                Err(_) => None,
+            // 旧实现优先取历史记录（槽位监视已先记下本次升级时它就是新地址，不可用），
+            // 否则读升级前一个区块的实现槽位
+            let old_impl = match current(&tracker.history, &proxy).and_then(|h| h.implementation).filter(|a| Some(*a) != new_impl) {
+                Some(a) => Some(a),
+                None => match parent_block(bn) {
+                    Some(id) => non_zero(read_slot_address_at(provider, proxy, eip1967_implementation_slot(), id).await),
+                    None => None,
+                },
             };
-            
-            println!(
-                "[proxy-upgrade] proxy={} new_impl={:?} onchain_impl={:?} admin={:?} beacon={:?} tx={:?} block={:?}",
-                proxy, impl_addr, onchain_impl, onchain_admin, onchain_beacon, txh, bn
-            );
+            tracker.implementation_changed(proxy, old_impl, new_impl, txh, bn, Via::Event).await;
         });
     }
-    
+
     fn handle_admin_change(&self, e: &EventRecord) {
-        let mut prev = None;
-        let mut newa = None;
-        for f in &e.fields {
-            let key = f.name.to_lowercase();
-            match key.as_str() {
-                "previousadmin" | "previous_admin" | "from" => {
-                    prev = Some(format!("{:?}", f.value))
-                }
-                "newadmin" | "new_admin" | "to" => {
-                    newa = Some(format!("{:?}", f.value))
-                }
-                _ => {}
-            }
-        }
-        
-        let provider = self.provider.clone();
+        let prev = field_address(e, |k| matches!(k, "previousadmin" | "previous_admin" | "from"));
+        let newa = field_address(e, |k| matches!(k, "newadmin" | "new_admin" | "to"));
+
+        let tracker = self.tracker.clone();
         let proxy = e.address;
         let txh = e.tx_hash;
         let bn = e.block_number;
-        
+
         tokio::spawn(async move {
-            let admin_slot_u256 = U256::from_be_slice(eip1967_admin_slot().as_slice());
-            throttle::acquire().await;
-            
-            let onchain_admin = match provider.get_storage_at(proxy, admin_slot_u256).await {
-                Ok(bytes) => {
-                    let be: [u8; 32] = bytes.to_be_bytes::<32>();
-                    let addr = right_most_20(&be);
-                    if addr == Address::ZERO { None } else { Some(addr) }
-                }
-                Err(_) => None,
-            };
-            
+            let onchain_admin = non_zero(read_slot_address(&tracker.provider, proxy, eip1967_admin_slot()).await);
+
             println!(
                 "[proxy-admin-changed] proxy={} prev={:?} new={:?} onchain_admin={:?} tx={:?} block={:?}",
                 proxy, prev, newa, onchain_admin, txh, bn
             );
+
+            let new = newa.or(onchain_admin);
+            tracker.record(proxy, UpgradeEntry {
+                kind: UpgradeKind::Admin,
+                old: prev,
+                new,
+                admin: new,
+                block: bn,
+                tx: txh,
+                via: Via::Event,
+                diff: None,
+                cuts: vec![],
+            });
         });
     }
-    
+
     fn handle_beacon_upgrade(&self, e: &EventRecord) {
-        let mut beacon_addr = None;
-        for f in &e.fields {
-            let key = f.name.to_lowercase();
-            if key.contains("beacon") {
-                beacon_addr = Some(format!("{:?}", f.value));
-                break;
-            }
-        }
-        
-        let provider = self.provider.clone();
+        let beacon_addr = field_address(e, |k| k.contains("beacon")).or_else(|| topic_address(e, 1));
+
+        let tracker = self.tracker.clone();
         let proxy = e.address;
         let txh = e.tx_hash;
         let bn = e.block_number;
-        
+
         tokio::spawn(async move {
-            let beacon_slot_u256 = U256::from_be_slice(eip1967_beacon_slot().as_slice());
-            throttle::acquire().await;
-            
-            let onchain_beacon = match provider.get_storage_at(proxy, beacon_slot_u256).await {
-                Ok(bytes) => {
-                    let be: [u8; 32] = bytes.to_be_bytes::<32>();
-                    let addr = right_most_20(&be);
-                    if addr == Address::ZERO { None } else { Some(addr) }
-                }
-                Err(_) => None,
+            let provider = &tracker.provider;
+            let new_beacon = match beacon_addr {
+                Some(a) => Some(a),
+                None => non_zero(read_slot_address(provider, proxy, eip1967_beacon_slot()).await),
+            };
+            // 历史中已是新 beacon（槽位监视先记下了本次升级）时改读升级前的槽位
+            let old_beacon = match current(&tracker.history, &proxy).and_then(|h| h.beacon).filter(|a| Some(*a) != new_beacon) {
+                Some(a) => Some(a),
+                None => match parent_block(bn) {
+                    Some(id) => non_zero(read_slot_address_at(provider, proxy, eip1967_beacon_slot(), id).await),
+                    None => None,
+                },
             };
-            
+            tracker.beacon_changed(proxy, old_beacon, new_beacon, txh, bn, Via::Event).await;
+        });
+    }
+
+    fn handle_diamond_cut(&self, e: &EventRecord) {
+        let Some((cuts, init)) = decode_diamond_cut(e) else {
+            eprintln!("[proxy-diamond-cut] diamond={} undecodable DiamondCut tx={:?}", e.address, e.tx_hash);
+            return;
+        };
+        let diamond = e.address;
+        for cut in &cuts {
             println!(
-                "[proxy-beacon-upgrade] proxy={} new_beacon={:?} onchain_beacon={:?} tx={:?} block={:?}",
-                proxy, beacon_addr, onchain_beacon, txh, bn
+                "[proxy-diamond-cut] diamond={} action={} facet={} selectors=[{}] tx={:?} block={:?}",
+                diamond,
+                cut.action.as_str(),
+                cut.facet,
+                cut.selectors.join(","),
+                e.tx_hash,
+                e.block_number
             );
+        }
+        let entry = UpgradeEntry {
+            kind: UpgradeKind::DiamondCut,
+            old: None,
+            new: init,
+            admin: None,
+            block: e.block_number,
+            tx: e.tx_hash,
+            via: Via::Event,
+            diff: None,
+            cuts,
+        };
+        let state = self.tracker.record(diamond, entry);
+        let facets: HashSet<&Address> = state.facets.values().collect();
+        println!(
+            "[proxy-diamond-cut] diamond={} init={:?} facets={} selectors={} tx={:?} block={:?}",
+            diamond,
+            init,
+            facets.len(),
+            state.facets.len(),
+            e.tx_hash,
+            e.block_number
+        );
+    }
+}
+
+impl Tracker {
+    async fn implementation_changed(&self, proxy: Address, old_impl: Option<Address>, new_impl: Option<Address>, txh: Option<B256>, bn: Option<u64>, via: Via) {
+        if via == Via::Slot && self.event_recorded(proxy, UpgradeKind::Implementation, bn, new_impl) {
+            return;
+        }
+        let provider = &self.provider;
+        // Read all ERC-1967 slots for comprehensive proxy state
+        let onchain_impl = read_slot_address(provider, proxy, eip1967_implementation_slot()).await;
+        let onchain_admin = non_zero(read_slot_address(provider, proxy, eip1967_admin_slot()).await);
+        let onchain_beacon = non_zero(read_slot_address(provider, proxy, eip1967_beacon_slot()).await);
+
+        let diff = diff_implementations(provider, old_impl, new_impl, bn).await;
+        let uups = match new_impl {
+            Some(a) => is_uups(provider, a, block_or_latest(bn)).await,
+            None => None,
+        };
+
+        println!(
+            "[proxy-upgrade] proxy={} via={} old_impl={:?} new_impl={:?} onchain_impl={:?} admin={:?} beacon={:?} uups={:?} tx={:?} block={:?} {}",
+            proxy,
+            via.as_str(),
+            old_impl,
+            new_impl,
+            onchain_impl,
+            onchain_admin,
+            onchain_beacon,
+            uups,
+            txh,
+            bn,
+            diff.as_ref().map(|d| d.to_string()).unwrap_or_else(|| "diff=-".to_string())
+        );
+
+        if let Some(i) = new_impl {
+            initscan::probe_implementation(proxy, i, bn);
+        }
+
+        let admin = onchain_admin.or(current(&self.history, &proxy).and_then(|h| h.admin));
+        self.record_with(proxy, UpgradeEntry { kind: UpgradeKind::Implementation, old: old_impl, new: new_impl, admin, block: bn, tx: txh, via, diff, cuts: vec![] }, |h| {
+            if uups.is_some() {
+                h.uups = uups;
+            }
+        });
+    }
+
+    async fn beacon_changed(&self, proxy: Address, old_beacon: Option<Address>, new_beacon: Option<Address>, txh: Option<B256>, bn: Option<u64>, via: Via) {
+        if via == Via::Slot && self.event_recorded(proxy, UpgradeKind::Beacon, bn, new_beacon) {
+            return;
+        }
+        let provider = &self.provider;
+        let onchain_beacon = non_zero(read_slot_address(provider, proxy, eip1967_beacon_slot()).await);
+
+        let known = current(&self.history, &proxy);
+        // beacon 变化即实现变化：新旧实现分别取自 beacon 的 implementation()
+        let old_impl = match (known.as_ref().and_then(|h| h.implementation), old_beacon) {
+            (Some(a), _) => Some(a),
+            (None, Some(b)) => non_zero(beacon_implementation(provider, b).await),
+            (None, None) => None,
+        };
+        let new_impl = match new_beacon {
+            Some(b) => non_zero(beacon_implementation(provider, b).await),
+            None => None,
+        };
+        let diff = diff_implementations(provider, old_impl, new_impl, bn).await;
+
+        println!(
+            "[proxy-beacon-upgrade] proxy={} via={} old_beacon={:?} new_beacon={:?} onchain_beacon={:?} old_impl={:?} new_impl={:?} tx={:?} block={:?} {}",
+            proxy,
+            via.as_str(),
+            old_beacon,
+            new_beacon,
+            onchain_beacon,
+            old_impl,
+            new_impl,
+            txh,
+            bn,
+            diff.as_ref().map(|d| d.to_string()).unwrap_or_else(|| "diff=-".to_string())
+        );
+
+        if let Some(i) = new_impl {
+            initscan::probe_implementation(proxy, i, bn);
+        }
+
+        let admin = known.and_then(|h| h.admin);
+        self.record_with(proxy, UpgradeEntry { kind: UpgradeKind::Beacon, old: old_beacon, new: new_beacon, admin, block: bn, tx: txh, via, diff, cuts: vec![] }, |h| {
+            if new_impl.is_some() {
+                h.implementation = new_impl;
+            }
         });
     }
+
+    fn event_recorded(&self, proxy: Address, kind: UpgradeKind, bn: Option<u64>, new: Option<Address>) -> bool {
+        current(&self.history, &proxy).is_some_and(|h| h.has_event_record(kind, bn, new))
+    }
+
+    fn record(&self, proxy: Address, entry: UpgradeEntry) -> ProxyHistory {
+        self.record_with(proxy, entry, |_| {})
+    }
+
+    /// 追加一条记录，`update` 可再调整该代理的当前状态；返回更新后的状态（重复的槽位记录不写入）
+    fn record_with(&self, proxy: Address, entry: UpgradeEntry, update: impl FnOnce(&mut ProxyHistory)) -> ProxyHistory {
+        let mut h = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let state = h.entry(proxy).or_default();
+        if !state.apply(entry) {
+            return state.clone();
+        }
+        update(state);
+        let out = state.clone();
+        if let Some(p) = &self.path
+            && let Err(e) = persist::write_json_atomic(p, &*h)
+        {
+            eprintln!("[proxy-upgrade] failed to save history {p}: {e}");
+        }
+        out
+    }
+}
+
+/// 取第一个名称（小写）满足条件的地址字段
+fn field_address(e: &EventRecord, pred: impl Fn(&str) -> bool) -> Option<Address> {
+    e.fields.iter().find(|f| pred(&f.name.to_lowercase())).and_then(|f| match &f.value {
+        DecodedValue::Address(a) => Some(*a),
+        _ => None,
+    })
+}
+
+fn topic_address(e: &EventRecord, i: usize) -> Option<Address> {
+    e.topics.get(i).map(|t| right_most_20(t.as_slice()))
+}
+
+fn parent_block(bn: Option<u64>) -> Option<BlockId> {
+    bn.filter(|n| *n > 0).map(|n| BlockId::number(n - 1))
+}
+
+fn block_or_latest(bn: Option<u64>) -> BlockId {
+    bn.map(BlockId::number).unwrap_or(BlockId::latest())
+}
+
+async fn code_at(provider: &RootProvider<BoxTransport>, addr: Address, block: BlockId) -> Option<Bytes> {
+    throttle::acquire().await;
+    provider.get_code_at(addr).block_id(block).await.ok()
+}
+
+/// 旧实现取升级前一个区块的代码（之后可能已自毁），新实现取升级所在区块
+async fn diff_implementations(
+    provider: &RootProvider<BoxTransport>,
+    old_impl: Option<Address>,
+    new_impl: Option<Address>,
+    bn: Option<u64>,
+) -> Option<ImplDiff> {
+    let (old_impl, new_impl) = (old_impl?, new_impl?);
+    if old_impl == new_impl {
+        return None;
+    }
+    let old_code = code_at(provider, old_impl, parent_block(bn).unwrap_or(BlockId::latest())).await?;
+    let new_code = code_at(provider, new_impl, block_or_latest(bn)).await?;
+    Some(ImplDiff::between(&old_code, &new_code))
+}
+
+fn current(history: &HistoryMap, proxy: &Address) -> Option<ProxyHistory> {
+    history.lock().unwrap_or_else(|e| e.into_inner()).get(proxy).cloned()
+}
+
+fn load_history(path: &str) -> BTreeMap<Address, ProxyHistory> {
+    persist::read_json(path)
+        .unwrap_or_else(|e| {
+            eprintln!("[proxy-upgrade] ignore unreadable history {path}: {e}");
+            None
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    #[test]
+    fn diffs_selectors_size_and_dangerous_opcodes() {
+        // PUSH4 8129fc1c PUSH4 a9059cbb
+        let old = hex!("638129fc1c63a9059cbb");
+        // PUSH4 a9059cbb PUSH4 3659cfe6 GAS DELEGATECALL
+        let new = hex!("63a9059cbb633659cfe65af4");
+        let d = ImplDiff::between(&old, &new);
+        assert_eq!(d.added_selectors, vec!["0x3659cfe6"]);
+        assert_eq!(d.removed_selectors, vec!["0x8129fc1c"]);
+        assert!(d.new_delegatecall);
+        assert!(!d.new_selfdestruct);
+        assert!(d.to_string().starts_with("code_size=10->12(+2) "));
+
+        let mut h = ProxyHistory::default();
+        let a = Address::repeat_byte(0xaa);
+        h.apply(UpgradeEntry { kind: UpgradeKind::Implementation, old: None, new: Some(a), admin: None, block: Some(1), tx: None, via: Via::Event, diff: Some(d), cuts: vec![] });
+        assert_eq!(h.implementation, Some(a));
+        let json = serde_json::to_string(&h).unwrap();
+        let back: ProxyHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.upgrades[0].diff.as_ref().unwrap().new_code_size, 12);
+    }
+
+    #[test]
+    fn slot_and_event_records_of_one_upgrade_are_kept_once() {
+        let (a, b) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let entry = |new: Address, block: u64, via: Via| UpgradeEntry { kind: UpgradeKind::Implementation, old: None, new: Some(new), admin: None, block: Some(block), tx: None, via, diff: None, cuts: vec![] };
+
+        // event first: the slot watcher's report of the same upgrade is dropped
+        let mut h = ProxyHistory::default();
+        assert!(h.apply(entry(a, 5, Via::Event)));
+        assert!(h.has_event_record(UpgradeKind::Implementation, Some(5), Some(a)));
+        assert!(!h.apply(entry(a, 5, Via::Slot)));
+        // a different upgrade in the same block is still recorded
+        assert!(h.apply(entry(b, 5, Via::Slot)));
+        assert_eq!(h.upgrades.iter().map(|u| (u.new, u.via)).collect::<Vec<_>>(), [(Some(a), Via::Event), (Some(b), Via::Slot)]);
+
+        // slot first: the late event is merged into it
+        let mut h = ProxyHistory::default();
+        assert!(h.apply(entry(a, 5, Via::Slot)));
+        assert!(h.apply(entry(a, 5, Via::Event)));
+        assert_eq!(h.upgrades.len(), 1);
+        assert_eq!(h.upgrades[0].via, Via::Event);
+        assert_eq!(h.implementation, Some(a));
+
+        // the slot record knows the real old implementation; the late event read the already
+        // updated history (old == new) and could not diff, so the slot's old / diff are kept
+        let diff = ImplDiff::between(&[0x00], &[0x00, 0x00]);
+        let mut h = ProxyHistory::default();
+        assert!(h.apply(UpgradeEntry { old: Some(a), diff: Some(diff), ..entry(b, 6, Via::Slot) }));
+        assert!(h.apply(UpgradeEntry { old: Some(b), ..entry(b, 6, Via::Event) }));
+        assert_eq!(h.upgrades.len(), 1);
+        assert_eq!((h.upgrades[0].via, h.upgrades[0].old, h.upgrades[0].new), (Via::Event, Some(a), Some(b)));
+        assert_eq!(h.upgrades[0].diff.as_ref().map(|d| d.new_code_size), Some(2));
+    }
+
+    #[test]
+    fn decodes_diamond_cut_into_facet_table() {
+        use alloy_dyn_abi::DynSolValue;
+        use alloy_primitives::keccak256;
+
+        assert_eq!(keccak256("DiamondCut((address,uint8,bytes4[])[],address,bytes)"), DIAMOND_CUT_TOPIC);
+        let (f1, f2) = (Address::repeat_byte(0xf1), Address::repeat_byte(0xf2));
+        let cut = |facet: Address, action: u8, sels: &[[u8; 4]]| {
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(facet),
+                DynSolValue::Uint(U256::from(action), 8),
+                DynSolValue::Array(sels.iter().map(|s| DynSolValue::FixedBytes(B256::right_padding_from(s), 4)).collect()),
+            ])
+        };
+        let events = crate::abi::load_event_sigs("data/event_sigs.json").unwrap();
+        let decode = |cuts: Vec<DynSolValue>, init: Address| {
+            let data = DynSolValue::Tuple(vec![DynSolValue::Array(cuts), DynSolValue::Address(init), DynSolValue::Bytes(vec![])]).abi_encode_params();
+            let topic0 = format!("0x{}", hex::encode(DIAMOND_CUT_TOPIC));
+            let (name, fields) = crate::abi::try_decode_event(&topic0, &[DIAMOND_CUT_TOPIC], &data, &events).unwrap();
+            assert_eq!(name, "DiamondCut");
+            let e = EventRecord {
+                address: Address::repeat_byte(0xdd),
+                tx_hash: None,
+                block_number: Some(1),
+                topic0: Some(DIAMOND_CUT_TOPIC),
+                name: Some(name),
+                fields,
+                tx_index: None,
+                log_index: None,
+                topics: vec![DIAMOND_CUT_TOPIC],
+                removed: None,
+            };
+            decode_diamond_cut(&e).unwrap()
+        };
+        let entry = |cuts: Vec<FacetCut>| UpgradeEntry { kind: UpgradeKind::DiamondCut, old: None, new: None, admin: None, block: None, tx: None, via: Via::Event, diff: None, cuts };
+
+        let init = Address::repeat_byte(0x11);
+        let (cuts, got_init) = decode(vec![cut(f1, 0, &[hex!("01020304"), hex!("0a0b0c0d")])], init);
+        assert_eq!(got_init, Some(init));
+        assert_eq!(cuts, vec![FacetCut { facet: f1, action: FacetCutAction::Add, selectors: vec!["0x01020304".into(), "0x0a0b0c0d".into()] }]);
+        let mut h = ProxyHistory::default();
+        h.apply(entry(cuts));
+
+        let (cuts, got_init) = decode(vec![cut(f2, 1, &[hex!("01020304")]), cut(Address::ZERO, 2, &[hex!("0a0b0c0d")])], Address::ZERO);
+        assert_eq!(got_init, None);
+        h.apply(entry(cuts));
+        assert_eq!(h.facets.into_iter().collect::<Vec<_>>(), vec![("0x01020304".to_string(), f2)]);
+    }
 }
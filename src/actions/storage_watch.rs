@@ -0,0 +1,565 @@
+use super::{Action, BlockRecord};
+use crate::error::{AppError, Result};
+use crate::source::Source;
+use alloy_primitives::{Address, B256, U256, keccak256};
+use alloy_rpc_types::trace::{
+    geth::DiffMode,
+    parity::{Delta, TraceResultsWithTransactionHash},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+
+// Storage slot watcher
+// - 通过 prestateTracer(diffMode) 或 trace_replayBlockTransactions(stateDiff) 逐交易发现槽位变化
+// - 节点不支持 debug/trace API 时退回 eth_getStorageAt，每 N 个区块轮询一次
+
+/// 单个被监控的槽位
+#[derive(Debug, Clone)]
+pub struct WatchedSlot {
+    pub address: Address,
+    pub slot: B256,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageWatchMode {
+    /// 依次尝试 prestate diff / parity stateDiff / 轮询，记住第一个可用的后端
+    Auto,
+    Prestate,
+    StateDiff,
+    Poll,
+}
+
+impl FromStr for StorageWatchMode {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "prestate" | "debug" => Ok(Self::Prestate),
+            "statediff" | "trace" => Ok(Self::StateDiff),
+            "poll" => Ok(Self::Poll),
+            other => Err(AppError::Config(format!(
+                "unknown storage watch mode: {other}"
+            ))),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StorageWatchOptions {
+    pub slots: Vec<WatchedSlot>,
+    pub mode: StorageWatchMode,
+    /// 轮询模式下每 N 个区块读取一次
+    pub poll_interval_blocks: u64,
+    pub output_filepath: Option<String>,
+}
+
+/// 检测到的一次槽位变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotChange {
+    pub address: Address,
+    pub slot: B256,
+    pub label: String,
+    pub old: B256,
+    pub new: B256,
+    pub block_number: u64,
+    /// 轮询模式无法定位具体交易
+    pub tx_hash: Option<B256>,
+}
+
+struct WatchState {
+    /// Auto 模式解析后的实际后端
+    backend: Option<StorageWatchMode>,
+    last_values: HashMap<(Address, B256), B256>,
+    last_poll_block: Option<u64>,
+}
+
+pub struct StorageWatchAction {
+    source: Source,
+    opts: Arc<StorageWatchOptions>,
+    /// 单个后台任务按到达顺序逐块处理，保证 last_values 与输出按区块顺序；首个区块到达时启动
+    blocks: OnceLock<mpsc::UnboundedSender<u64>>,
+}
+
+impl StorageWatchAction {
+    pub fn new(source: Source, opts: StorageWatchOptions) -> Self {
+        Self { source, opts: Arc::new(opts), blocks: OnceLock::new() }
+    }
+}
+
+impl Action for StorageWatchAction {
+    fn on_block(&self, b: &BlockRecord) -> Result<()> {
+        if self.opts.slots.is_empty() {
+            return Ok(());
+        }
+        let tx = self.blocks.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(run_worker(self.source.clone(), self.opts.clone(), rx));
+            tx
+        });
+        let _ = tx.send(b.number);
+        Ok(())
+    }
+}
+
+/// Action 被替换（热重载）后发送端随之释放，任务退出
+async fn run_worker(source: Source, opts: Arc<StorageWatchOptions>, mut blocks: mpsc::UnboundedReceiver<u64>) {
+    let backend = match opts.mode {
+        StorageWatchMode::Auto => None,
+        m => Some(m),
+    };
+    let mut st = WatchState { backend, last_values: HashMap::new(), last_poll_block: None };
+    while let Some(block_number) = blocks.recv().await {
+        match check_block(&source, &opts, &mut st, block_number).await {
+            Ok(changes) => {
+                for c in changes {
+                    st.last_values.insert((c.address, c.slot), c.new);
+                    report(&opts, &c);
+                }
+            }
+            Err(e) => eprintln!("[storage-watch] block {block_number} error: {e}"),
+        }
+    }
+}
+
+async fn check_block(
+    source: &Source,
+    opts: &StorageWatchOptions,
+    st: &mut WatchState,
+    block_number: u64,
+) -> Result<Vec<SlotChange>> {
+    match st.backend {
+        Some(StorageWatchMode::Prestate) => prestate_changes(source, opts, block_number).await,
+        Some(StorageWatchMode::StateDiff) => statediff_changes(source, opts, block_number).await,
+        Some(_) => poll_changes(source, opts, st, block_number).await,
+        None => {
+            // 只有节点明确不支持时才换下一个后端；临时错误留到下个区块重试
+            match prestate_changes(source, opts, block_number).await {
+                Ok(changes) => {
+                    println!("[storage-watch] using debug_traceBlockByNumber prestate diffs");
+                    st.backend = Some(StorageWatchMode::Prestate);
+                    return Ok(changes);
+                }
+                Err(e) if !e.is_method_unsupported() => return Err(e),
+                Err(_) => {}
+            }
+            match statediff_changes(source, opts, block_number).await {
+                Ok(changes) => {
+                    println!("[storage-watch] using trace_replayBlockTransactions stateDiff");
+                    st.backend = Some(StorageWatchMode::StateDiff);
+                    return Ok(changes);
+                }
+                Err(e) if !e.is_method_unsupported() => return Err(e),
+                Err(_) => {}
+            }
+            println!(
+                "[storage-watch] no debug/trace API, polling eth_getStorageAt every {} blocks",
+                opts.poll_interval_blocks
+            );
+            st.backend = Some(StorageWatchMode::Poll);
+            poll_changes(source, opts, st, block_number).await
+        }
+    }
+}
+
+async fn prestate_changes(
+    source: &Source,
+    opts: &StorageWatchOptions,
+    block_number: u64,
+) -> Result<Vec<SlotChange>> {
+    let (_, txs, diffs) = source
+        .geth_debug_trace_block_diffs(block_number as u32, true)
+        .await
+        .map_err(AppError::from)?;
+    let mut out = Vec::new();
+    for (i, diff) in diffs.iter().enumerate() {
+        let tx_hash = txs.get(i).cloned().flatten().map(|h| B256::from_slice(&h));
+        out.extend(changes_from_geth_diff(
+            &opts.slots,
+            diff,
+            block_number,
+            tx_hash,
+        ));
+    }
+    Ok(out)
+}
+
+async fn statediff_changes(
+    source: &Source,
+    opts: &StorageWatchOptions,
+    block_number: u64,
+) -> Result<Vec<SlotChange>> {
+    let (_, _, traces) = source
+        .trace_block_state_diffs(block_number as u32, false)
+        .await
+        .map_err(AppError::from)?;
+    Ok(traces
+        .iter()
+        .flat_map(|t| changes_from_parity_diff(&opts.slots, t, block_number))
+        .collect())
+}
+
+async fn poll_changes(
+    source: &Source,
+    opts: &StorageWatchOptions,
+    st: &mut WatchState,
+    block_number: u64,
+) -> Result<Vec<SlotChange>> {
+    if !poll_due(st.last_poll_block, opts.poll_interval_blocks, block_number) {
+        return Ok(Vec::new());
+    }
+    let mut values = Vec::with_capacity(opts.slots.len());
+    for w in &opts.slots {
+        let value = source
+            .get_storage_at(w.address, U256::from_be_bytes(w.slot.0), block_number)
+            .await
+            .map_err(AppError::from)?;
+        values.push(B256::from(value.to_be_bytes::<32>()));
+    }
+    st.last_poll_block = Some(block_number);
+    Ok(changes_from_poll(&opts.slots, &values, &mut st.last_values, block_number))
+}
+
+fn poll_due(last_poll_block: Option<u64>, interval: u64, block_number: u64) -> bool {
+    last_poll_block.is_none_or(|last| block_number >= last + interval.max(1))
+}
+
+/// 轮询读到的值（与 `slots` 一一对应）与上次的值比较；首次读取只建立基线
+fn changes_from_poll(
+    slots: &[WatchedSlot],
+    values: &[B256],
+    last_values: &mut HashMap<(Address, B256), B256>,
+    block_number: u64,
+) -> Vec<SlotChange> {
+    let mut out = Vec::new();
+    for (w, new) in slots.iter().zip(values) {
+        match last_values.get(&(w.address, w.slot)) {
+            None => {
+                last_values.insert((w.address, w.slot), *new);
+            }
+            Some(old) if old != new => out.push(SlotChange {
+                address: w.address,
+                slot: w.slot,
+                label: w.label.clone(),
+                old: *old,
+                new: *new,
+                block_number,
+                tx_hash: None,
+            }),
+            Some(_) => {}
+        }
+    }
+    out
+}
+
+/// diffMode 中 pre/post 只包含被修改的槽位；post 中缺失表示被清零
+pub fn changes_from_geth_diff(
+    slots: &[WatchedSlot],
+    diff: &DiffMode,
+    block_number: u64,
+    tx_hash: Option<B256>,
+) -> Vec<SlotChange> {
+    let mut out = Vec::new();
+    for w in slots {
+        let pre = diff
+            .pre
+            .get(&w.address)
+            .and_then(|a| a.storage.get(&w.slot))
+            .copied();
+        let post = diff
+            .post
+            .get(&w.address)
+            .and_then(|a| a.storage.get(&w.slot))
+            .copied();
+        if pre.is_none() && post.is_none() {
+            continue;
+        }
+        let old = pre.unwrap_or_default();
+        let new = post.unwrap_or_default();
+        if old != new {
+            out.push(SlotChange {
+                address: w.address,
+                slot: w.slot,
+                label: w.label.clone(),
+                old,
+                new,
+                block_number,
+                tx_hash,
+            });
+        }
+    }
+    out
+}
+
+pub fn changes_from_parity_diff(
+    slots: &[WatchedSlot],
+    trace: &TraceResultsWithTransactionHash,
+    block_number: u64,
+) -> Vec<SlotChange> {
+    let Some(state_diff) = &trace.full_trace.state_diff else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for w in slots {
+        let Some(delta) = state_diff
+            .get(&w.address)
+            .and_then(|a| a.storage.get(&w.slot))
+        else {
+            continue;
+        };
+        let (old, new) = match delta {
+            Delta::Unchanged => continue,
+            Delta::Added(v) => (B256::ZERO, *v),
+            Delta::Removed(v) => (*v, B256::ZERO),
+            Delta::Changed(c) => (c.from, c.to),
+        };
+        out.push(SlotChange {
+            address: w.address,
+            slot: w.slot,
+            label: w.label.clone(),
+            old,
+            new,
+            block_number,
+            tx_hash: Some(trace.transaction_hash),
+        });
+    }
+    out
+}
+
+/// mapping(key => ...) 位于 base_slot 时的存储位置：keccak256(pad32(key) ++ pad32(base_slot))
+pub fn mapping_slot(key: B256, base_slot: B256) -> B256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(key.as_slice());
+    buf[32..].copy_from_slice(base_slot.as_slice());
+    keccak256(buf)
+}
+
+/// 解析 `addresses` 中单个合约的槽位配置：
+/// `{"slots": [{"label": "owner", "slot": "0x0"}, {"preset": "eip1967-implementation"},
+///   {"label": "balances[alice]", "mapping-slot": "0x1", "key": "0x..."}]}`
+pub fn parse_watched_slots(
+    address: Address,
+    value: &serde_json::Value,
+) -> Result<Vec<WatchedSlot>> {
+    let Some(list) = value.get("slots").and_then(|v| v.as_array()) else {
+        return Ok(Vec::new());
+    };
+    let mut out = Vec::new();
+    for spec in list {
+        let (slot, default_label) =
+            if let Some(preset) = spec.get("preset").and_then(|v| v.as_str()) {
+                (preset_slot(preset)?, preset.to_string())
+            } else if let Some(base) = spec.get("mapping-slot") {
+                let key = spec.get("key").ok_or_else(|| {
+                    AppError::Config(format!("storage watch {address}: mapping-slot needs key"))
+                })?;
+                let base = parse_word(base)?;
+                (
+                    mapping_slot(parse_word(key)?, base),
+                    format!("mapping[{base}]"),
+                )
+            } else if let Some(slot) = spec.get("slot") {
+                let slot = parse_word(slot)?;
+                (slot, slot.to_string())
+            } else {
+                return Err(AppError::Config(format!(
+                    "storage watch {address}: slot spec needs slot/preset/mapping-slot"
+                )));
+            };
+        let label = spec
+            .get("label")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or(default_label);
+        out.push(WatchedSlot {
+            address,
+            slot,
+            label,
+        });
+    }
+    Ok(out)
+}
+
+fn preset_slot(name: &str) -> Result<B256> {
+    let hex = match name {
+        "owner" => "0000000000000000000000000000000000000000000000000000000000000000",
+        "eip1967-implementation" => {
+            "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc"
+        }
+        "eip1967-admin" => "b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103",
+        "eip1967-beacon" => "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50",
+        other => {
+            return Err(AppError::Config(format!(
+                "unknown storage slot preset: {other}"
+            )));
+        }
+    };
+    Ok(B256::from_str(hex).expect("valid preset slot"))
+}
+
+/// 32 字节字：数字（十进制 / 0x 十六进制）或地址（左侧补零）
+fn parse_word(v: &serde_json::Value) -> Result<B256> {
+    if let Some(n) = v.as_u64() {
+        return Ok(B256::from(U256::from(n)));
+    }
+    let s = v
+        .as_str()
+        .ok_or_else(|| AppError::Config(format!("invalid storage word: {v}")))?;
+    if s.len() == 42
+        && let Ok(a) = Address::from_str(s)
+    {
+        return Ok(a.into_word());
+    }
+    U256::from_str(s)
+        .map(B256::from)
+        .map_err(|e| AppError::Config(format!("invalid storage word {s}: {e}")))
+}
+
+fn report(opts: &StorageWatchOptions, c: &SlotChange) {
+    println!(
+        "[storage-watch] addr={} label={} slot={} old={} new={} block={} tx={:?}",
+        c.address, c.label, c.slot, c.old, c.new, c.block_number, c.tx_hash
+    );
+    if let Some(path) = &opts.output_filepath {
+        match serde_json::to_string(c) {
+            Ok(line) => {
+                if let Err(e) = append_line(path, &line) {
+                    eprintln!("[storage-watch] write {path} failed: {e}");
+                }
+            }
+            Err(e) => eprintln!("[storage-watch] serialize failed: {e}"),
+        }
+    }
+}
+
+fn append_line(path: &str, s: &str) -> std::io::Result<()> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(f, "{}", s)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+    use alloy_rpc_types::trace::geth::AccountState;
+
+    #[test]
+    fn geth_diff_reports_changed_and_cleared_slots() {
+        let token = address!("00000000000000000000000000000000000000aa");
+        let owner = WatchedSlot {
+            address: token,
+            slot: B256::ZERO,
+            label: "owner".into(),
+        };
+        let paused = WatchedSlot {
+            address: token,
+            slot: B256::with_last_byte(1),
+            label: "paused".into(),
+        };
+        let untouched = WatchedSlot {
+            address: token,
+            slot: B256::with_last_byte(2),
+            label: "x".into(),
+        };
+
+        let new_owner = b256!("000000000000000000000000000000000000000000000000000000000000beef");
+        let mut pre = AccountState::default();
+        pre.storage.insert(owner.slot, B256::with_last_byte(0x11));
+        pre.storage.insert(paused.slot, B256::with_last_byte(1));
+        let mut post = AccountState::default();
+        post.storage.insert(owner.slot, new_owner);
+        let diff = DiffMode {
+            pre: [(token, pre)].into(),
+            post: [(token, post)].into(),
+        };
+
+        let changes = changes_from_geth_diff(&[owner, paused, untouched], &diff, 7, None);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            (changes[0].label.as_str(), changes[0].new),
+            ("owner", new_owner)
+        );
+        assert_eq!(
+            (changes[1].label.as_str(), changes[1].new),
+            ("paused", B256::ZERO)
+        );
+    }
+
+    #[test]
+    fn parity_diff_reports_added_removed_and_changed_slots() {
+        let token = address!("00000000000000000000000000000000000000aa");
+        let slot = |n: u8| WatchedSlot { address: token, slot: B256::with_last_byte(n), label: format!("s{n}") };
+        let trace: TraceResultsWithTransactionHash = serde_json::from_value(serde_json::json!({
+            "output": "0x",
+            "stateDiff": {
+                "0x00000000000000000000000000000000000000aa": {
+                    "balance": "=",
+                    "code": "=",
+                    "nonce": "=",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000000": {
+                            "*": {
+                                "from": "0x0000000000000000000000000000000000000000000000000000000000000011",
+                                "to": "0x000000000000000000000000000000000000000000000000000000000000beef"
+                            }
+                        },
+                        "0x0000000000000000000000000000000000000000000000000000000000000001": {
+                            "-": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                        },
+                        "0x0000000000000000000000000000000000000000000000000000000000000002": {
+                            "+": "0x0000000000000000000000000000000000000000000000000000000000000005"
+                        }
+                    }
+                }
+            },
+            "trace": [],
+            "vmTrace": null,
+            "transactionHash": "0x00000000000000000000000000000000000000000000000000000000000000f0"
+        }))
+        .unwrap();
+
+        let changes = changes_from_parity_diff(&[slot(0), slot(1), slot(2), slot(3)], &trace, 9);
+        let got: Vec<_> = changes.iter().map(|c| (c.label.as_str(), c.old, c.new)).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("s0", B256::with_last_byte(0x11), b256!("000000000000000000000000000000000000000000000000000000000000beef")),
+                ("s1", B256::with_last_byte(1), B256::ZERO),
+                ("s2", B256::ZERO, B256::with_last_byte(5)),
+            ]
+        );
+        assert!(changes.iter().all(|c| c.tx_hash == Some(B256::with_last_byte(0xf0)) && c.block_number == 9));
+    }
+
+    #[test]
+    fn poll_sets_baseline_then_reports_changes_on_interval() {
+        let token = address!("00000000000000000000000000000000000000aa");
+        let slots = [
+            WatchedSlot { address: token, slot: B256::ZERO, label: "owner".into() },
+            WatchedSlot { address: token, slot: B256::with_last_byte(1), label: "paused".into() },
+        ];
+        let mut last = HashMap::new();
+
+        assert!(poll_due(None, 10, 100));
+        let first = changes_from_poll(&slots, &[B256::with_last_byte(1), B256::ZERO], &mut last, 100);
+        assert!(first.is_empty());
+        assert_eq!(last.len(), 2);
+
+        assert!(!poll_due(Some(100), 10, 109));
+        assert!(poll_due(Some(100), 10, 110));
+        assert!(poll_due(Some(100), 0, 101));
+        let second = changes_from_poll(&slots, &[B256::with_last_byte(2), B256::ZERO], &mut last, 110);
+        assert_eq!(second.len(), 1);
+        assert_eq!(
+            (second[0].label.as_str(), second[0].old, second[0].new, second[0].tx_hash),
+            ("owner", B256::with_last_byte(1), B256::with_last_byte(2), None)
+        );
+    }
+}
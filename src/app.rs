@@ -69,19 +69,34 @@ fn add_common_actions(set: &mut ActionSet, prov_arc: Arc<RootProvider<BoxTranspo
     //     .map(|ac| ac.enabled)
     //     .unwrap_or(true)
     // {
-    //     set.add(actions::transfer::TransferAction::new(prov_arc.clone()));
+    //     if let Ok(tokens) = crate::token_meta::shared() {
+    //         set.add(actions::transfer::TransferAction::new(tokens));
+    //     }
     // }
     // --- end TransferAction disabled ---
-    set.add(actions::ownership::OwnershipAction);
-    set.add(actions::proxy::ProxyUpgradeAction::new(prov_arc.clone()));
-    // Deployment output to file if configured
-    let dep_out = cfg
-        .actions
-        .get("Deployment")
-        .and_then(|ac| ac.options.get("output-filepath"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
-    let dep_opts = actions::deployment::DeploymentOptions { output_filepath: dep_out };
+    let own_cfg = cfg.actions.get("Ownership").map(|ac| &ac.options).unwrap_or(&serde_json::Value::Null);
+    let own_opts = actions::ownership::parse_options(own_cfg, cli.webhook_url.as_ref()).unwrap_or_else(|e| {
+        eprintln!("[access] invalid Ownership options: {e}");
+        actions::ownership::OwnershipOptions::default()
+    });
+    set.add(actions::ownership::OwnershipAction::new(prov_arc.clone(), own_opts));
+    let proxy_opts = match cfg.actions.get("ProxyUpgrade") {
+        Some(ac) => actions::proxy::parse_options(&ac.options, &ac.addresses),
+        None => actions::proxy::parse_options(&serde_json::Value::Null, &Default::default()),
+    };
+    let proxy_opts = proxy_opts.unwrap_or_else(|e| {
+        eprintln!("[proxy-upgrade] invalid ProxyUpgrade options: {e}");
+        actions::proxy::ProxyOptions::default()
+    });
+    set.add(actions::proxy::ProxyUpgradeAction::new(prov_arc.clone(), proxy_opts));
+    // Deployment output to file / fingerprint index if configured
+    let dep_opts = match cfg.actions.get("Deployment") {
+        Some(ac) => actions::deployment::parse_options(&ac.options, cli.webhook_url.as_ref()).unwrap_or_else(|e| {
+            eprintln!("[deploy-scan] invalid options, using defaults: {e}");
+            actions::deployment::DeploymentOptions::default()
+        }),
+        None => actions::deployment::DeploymentOptions::default(),
+    };
     set.add(actions::deployment::DeploymentScanAction::new(prov_arc.clone(), dep_opts));
 
     // LargeTransfer optional
@@ -98,10 +113,15 @@ fn add_common_actions(set: &mut ActionSet, prov_arc: Arc<RootProvider<BoxTranspo
             .and_then(|v| v.as_u64())
             .map(|v| v as u8)
             .unwrap_or(18);
-        set.add(actions::large_transfer::LargeTransferAction::new(
-            actions::large_transfer::LargeTransferOptions { min_amount_human: min_h, decimals_default: dec_default, verbose: cli.verbose },
-            cli.verbose,
-        ));
+        match crate::token_meta::shared().and_then(|t| crate::pricing::shared().map(|p| (t, p))) {
+            Ok((tokens, prices)) => set.add(actions::large_transfer::LargeTransferAction::new(
+                actions::large_transfer::LargeTransferOptions { min_amount_human: min_h, decimals_default: dec_default, min_usd: ac.options.get("min-usd").and_then(|v| v.as_f64()), verbose: cli.verbose },
+                cli.verbose,
+                tokens,
+                prices,
+            )),
+            Err(e) => eprintln!("[large-transfer] disabled: {e}"),
+        }
     }
 
     // Tornado optional - only add if explicitly configured
@@ -148,7 +168,19 @@ fn try_add_initscan(set: &mut ActionSet, prov_arc: Arc<RootProvider<BoxTransport
                 }
             }
             let init_after = o.get("init-after-delay").and_then(|v| v.as_u64()).unwrap_or(1);
-            let usd_threshold = o.get("alert-usd-threshold").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let usd_threshold = o.get("min-usd").or_else(|| o.get("alert-usd-threshold")).and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let value_tokens: Vec<alloy_primitives::Address> = o.get("value-tokens").and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|a| a.as_str()?.parse().ok()).collect()).unwrap_or_default();
+            let value_scan_blocks = o.get("value-scan-blocks").and_then(|v| v.as_u64()).unwrap_or(5000);
+            let discover_init_functions = o.get("discover-init-functions").and_then(|v| v.as_bool()).unwrap_or(true);
+            let reproduction_artifacts = o.get("reproduction-artifacts").and_then(|v| v.as_bool()).unwrap_or(false);
+            let trace_backend = match o.get("trace-backend").and_then(|v| v.as_str()).map(str::parse).transpose() {
+                Ok(b) => b.unwrap_or_default(),
+                Err(e) => {
+                    eprintln!("[initscan] disabled: {e}");
+                    return;
+                }
+            };
             let webhook_url = o
                 .get("webhook-url")
                 .and_then(|v| v.as_str())
@@ -163,14 +195,22 @@ fn try_add_initscan(set: &mut ActionSet, prov_arc: Arc<RootProvider<BoxTransport
                 check_addresses: check_addrs,
                 init_after_delay_secs: init_after,
                 usd_threshold,
+                value_tokens,
+                value_scan_blocks,
                 func_sigs,
+                discover_init_functions,
+                reproduction_artifacts,
                 webhook_url,
                 initializable_contracts_filepath: known_path,
                 init_known_contracts_frequency_secs: init_known_freq,
                 max_inflight_inits,
                 debug,
+                trace_backend,
             };
-            set.add(actions::initscan::InitscanAction::new(prov_arc.clone(), is_opts));
+            match actions::initscan::InitscanAction::new(prov_arc.clone(), is_opts) {
+                Ok(action) => set.add(action),
+                Err(e) => eprintln!("[initscan] disabled: {e}"),
+            }
         }
     }
 }
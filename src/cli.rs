@@ -37,6 +37,10 @@ pub enum Commands {
     InitScan(InitScanCmd),
     /// Scan transactions from null.json and get their traces
     HistoryTxScan(HistoryTxScanCmd),
+    /// List / query initscan findings stored in the database
+    Findings(FindingsCmd),
+    /// List deployment bytecode fingerprint clusters by size and deployer
+    Fingerprints(FingerprintsCmd),
 }
 
 #[derive(Debug, Args)]
@@ -76,6 +80,12 @@ pub struct RealtimeCmd {
     /// 监控合约创建/部署
     #[arg(long, default_value_t = false)]
     pub deployments: bool,
+    /// 配合 --deployments：通过 trace_block / debug_traceBlockByNumber 检测工厂合约内部的 CREATE/CREATE2
+    #[arg(long, default_value_t = false)]
+    pub trace_creations: bool,
+    /// 监视配置文件与签名文件，修改后自动重新加载（不断开连接、不丢失订阅位置）
+    #[arg(long, default_value_t = false)]
+    pub hot_reload: bool,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -117,6 +127,8 @@ pub enum DataWhichCmd {
     Event(EventArgs),
     /// Fetch contract ABI from a block scanner API
     FetchAbi(FetchAbiArgs),
+    /// Bulk import 4byte / openchain signature dumps into func/event/error sig files
+    ImportSigs(ImportSigsArgs),
 }
 
 #[derive(Debug, Args)]
@@ -145,6 +157,25 @@ pub struct FetchAbiArgs {
     pub output: PathBuf,
 }
 
+#[derive(Debug, Args)]
+pub struct ImportSigsArgs {
+    /// Dump file(s): text `selector,signature` lines / 4byte CSV, or 4byte / openchain JSON
+    #[arg(long = "input", required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// Force signature kind: function | event | error (default: openchain section or selector length)
+    #[arg(long)]
+    pub kind: Option<String>,
+    /// Function signatures output (default ./data/func_sigs.json)
+    #[arg(long, default_value = "./data/func_sigs.json")]
+    pub func_output: PathBuf,
+    /// Event signatures output (default ./data/event_sigs.json)
+    #[arg(long, default_value = "./data/event_sigs.json")]
+    pub event_output: PathBuf,
+    /// Error signatures output (default ./data/error_sigs.json)
+    #[arg(long, default_value = "./data/error_sigs.json")]
+    pub error_output: PathBuf,
+}
+
 #[derive(Debug, Args)]
 pub struct InitScanCmd {
     /// 配置路径（包含 Initscan 的配置项）
@@ -182,3 +213,53 @@ pub struct HistoryTxScanCmd {
     #[arg(long)]
     pub progress_percent: Option<u64>,
 }
+
+#[derive(Debug, Args)]
+pub struct FindingsCmd {
+    /// 配置路径（读取 `findings.database-url`）
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Postgres 连接串，优先于配置文件与环境变量 DATABASE_URL
+    #[arg(long)]
+    pub database_url: Option<String>,
+    /// 按状态过滤：open | initialized | expired
+    #[arg(long)]
+    pub status: Option<String>,
+    /// 只看某个合约
+    #[arg(long)]
+    pub contract: Option<String>,
+    /// 只看某条链
+    #[arg(long)]
+    pub chain_id: Option<u64>,
+    /// 风险价值下限（USD）
+    #[arg(long)]
+    pub min_usd: Option<f64>,
+    /// 最多返回条数（按首次发现时间倒序）
+    #[arg(long, default_value_t = 100)]
+    pub limit: i64,
+}
+
+#[derive(Debug, Args)]
+pub struct FingerprintsCmd {
+    /// 配置路径（读取 Deployment 的 `fingerprint-index-filepath` / `fingerprint-references-filepath`）
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// 指纹索引文件（JSONL），优先于配置文件
+    #[arg(long)]
+    pub index: Option<String>,
+    /// 带标签的参考指纹文件，优先于配置文件
+    #[arg(long)]
+    pub references: Option<String>,
+    /// 只看该地址部署过的簇
+    #[arg(long)]
+    pub deployer: Option<String>,
+    /// 只看带标签的簇
+    #[arg(long, default_value_t = false)]
+    pub labeled: bool,
+    /// 簇大小下限
+    #[arg(long, default_value_t = 2)]
+    pub min_size: usize,
+    /// 最多列出的簇数（按大小倒序）
+    #[arg(long, default_value_t = 50)]
+    pub limit: usize,
+}
@@ -0,0 +1,52 @@
+use crate::{cli, config, db, error::{AppError, Result}, findings::{self, FindingQuery, FindingsConfig}};
+
+/// 列出 / 查询数据库中的 initscan 发现记录
+pub async fn run(cli: &cli::Cli, cmd: &cli::FindingsCmd) -> Result<()> {
+    let url = match &cmd.database_url {
+        Some(u) => u.clone(),
+        None => {
+            let fc = match &cmd.config {
+                Some(p) => config::load_and_validate_config(p)?.findings.unwrap_or_default(),
+                None => FindingsConfig::default(),
+            };
+            fc.resolve_url()?
+        }
+    };
+    let q = FindingQuery {
+        chain_id: cmd.chain_id,
+        status: cmd.status.as_deref().map(str::parse).transpose()?,
+        contract: cmd
+            .contract
+            .as_deref()
+            .map(|s| s.parse().map_err(|e| AppError::Config(format!("invalid --contract {s}: {e}"))))
+            .transpose()?,
+        min_usd: cmd.min_usd,
+        limit: cmd.limit,
+    };
+
+    let db = db::connect(&url).await?;
+    findings::create_findings_table(&db).await?;
+    let list = findings::query(&db, &q).await?;
+    for f in &list {
+        if cli.json {
+            println!("{}", serde_json::to_string(f)?);
+            continue;
+        }
+        println!(
+            "[finding] chain={} contract={} status={} valueUsd={} block={} firstSeen={} lastVerified={} initializedBy={} calldata={}",
+            f.chain_id,
+            f.contract,
+            f.status,
+            f.value_usd.map(|v| format!("{v:.2}")).unwrap_or_else(|| "-".to_string()),
+            f.block_number.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+            f.first_seen.to_rfc3339(),
+            f.last_verified.to_rfc3339(),
+            f.initialized_by.map(|a| a.to_string()).unwrap_or_else(|| "-".to_string()),
+            f.calldata,
+        );
+    }
+    if !cli.json {
+        println!("[finding] total={}", list.len());
+    }
+    Ok(())
+}
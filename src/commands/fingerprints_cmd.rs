@@ -0,0 +1,58 @@
+use alloy_primitives::Address;
+
+use crate::{cli, config, error::{AppError, Result}, fingerprints::{self, References}};
+
+/// 按大小 / 部署者列出字节码指纹簇
+pub fn run(cli: &cli::Cli, cmd: &cli::FingerprintsCmd) -> Result<()> {
+    let opts = match &cmd.config {
+        Some(p) => config::load_and_validate_config(p)?.actions.get("Deployment").map(|ac| ac.options.clone()),
+        None => None,
+    };
+    let opt = |k: &str| opts.as_ref().and_then(|o| o.get(k)).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let index = cmd
+        .index
+        .clone()
+        .or_else(|| opt("fingerprint-index-filepath"))
+        .ok_or_else(|| AppError::Config("--index or Deployment fingerprint-index-filepath is required".to_string()))?;
+    let refs = match cmd.references.clone().or_else(|| opt("fingerprint-references-filepath")) {
+        Some(p) => References::load(&p)?,
+        None => References::default(),
+    };
+    let deployer: Option<Address> = cmd
+        .deployer
+        .as_deref()
+        .map(|s| s.parse().map_err(|e| AppError::Config(format!("invalid --deployer {s}: {e}"))))
+        .transpose()?;
+
+    let members = fingerprints::load_members(&index)?;
+    let all = fingerprints::clusters(&members, &refs);
+    let total = all.len();
+    let list: Vec<_> = all
+        .into_iter()
+        .filter(|c| c.size >= cmd.min_size)
+        .filter(|c| !cmd.labeled || c.label.is_some())
+        .filter(|c| deployer.is_none_or(|d| c.deployers.iter().any(|(a, _)| *a == d)))
+        .take(cmd.limit)
+        .collect();
+    for c in &list {
+        if cli.json {
+            println!("{}", serde_json::to_string(c)?);
+            continue;
+        }
+        println!(
+            "[cluster] fingerprint={} size={} label={} codeHashes={} deployers={} blocks={}..{} top=[{}]",
+            c.fingerprint,
+            c.size,
+            c.label.as_deref().unwrap_or("-"),
+            c.code_hashes.len(),
+            c.deployers.len(),
+            c.first_block.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+            c.last_block.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string()),
+            c.deployers.iter().take(5).map(|(a, n)| format!("{a}:{n}")).collect::<Vec<_>>().join(","),
+        );
+    }
+    if !cli.json {
+        println!("[cluster] deployments={} clusters={} listed={}", members.len(), total, list.len());
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{abi, actions, cli, config, context::RuntimeContext, error::{AppError, Result}, provider};
+use crate::{abi, actions, cli, config, context::RuntimeContext, error::{AppError, Result}, findings, pricing, provider, token_meta};
 use std::sync::Arc;
 
 pub async fn run(cli: &cli::Cli, cmd: &cli::InitScanCmd) -> Result<()> {
@@ -46,6 +46,9 @@ pub async fn run(cli: &cli::Cli, cmd: &cli::InitScanCmd) -> Result<()> {
     if cli.verbose {
         println!("[DEBUG] Provider connected successfully");
     }
+    token_meta::init_from_config(&cfg, &provider).await?;
+    pricing::init_from_config(&cfg, &provider).await?;
+    findings::init_from_config(&cfg, &provider).await?;
 
     let ac = cfg
         .actions
@@ -97,7 +100,16 @@ pub async fn run(cli: &cli::Cli, cmd: &cli::InitScanCmd) -> Result<()> {
     }
     
     let init_after = o.get("init-after-delay").and_then(|v| v.as_u64()).unwrap_or(1);
-    let usd_threshold = o.get("alert-usd-threshold").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let usd_threshold = o.get("min-usd").or_else(|| o.get("alert-usd-threshold")).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let value_tokens: Vec<alloy_primitives::Address> = o.get("value-tokens").and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|a| a.as_str()?.parse().ok()).collect()).unwrap_or_default();
+    let value_scan_blocks = o.get("value-scan-blocks").and_then(|v| v.as_u64()).unwrap_or(5000);
+    let discover_init_functions = o.get("discover-init-functions").and_then(|v| v.as_bool()).unwrap_or(true);
+    let reproduction_artifacts = o.get("reproduction-artifacts").and_then(|v| v.as_bool()).unwrap_or(false);
+    let trace_backend = match o.get("trace-backend").and_then(|v| v.as_str()) {
+        Some(s) => s.parse::<actions::initscan::InitscanTraceBackend>()?,
+        None => Default::default(),
+    };
     let webhook_url = o
         .get("webhook-url")
         .and_then(|v| v.as_str())
@@ -126,12 +138,17 @@ pub async fn run(cli: &cli::Cli, cmd: &cli::InitScanCmd) -> Result<()> {
         check_addresses: check_addrs,
         init_after_delay_secs: init_after,
         usd_threshold,
+        value_tokens,
+        value_scan_blocks,
         func_sigs,
+        discover_init_functions,
+        reproduction_artifacts,
         webhook_url,
         initializable_contracts_filepath: known_path,
         init_known_contracts_frequency_secs: init_known_freq,
         max_inflight_inits,
         debug: debug_enabled,
+        trace_backend,
     };
     
     let opts = actions::history_init_scan::HistoryInitScanOptions {
@@ -1,2 +1,4 @@
 pub mod track;
 pub mod init_scan_cmd;
+pub mod findings_cmd;
+pub mod fingerprints_cmd;
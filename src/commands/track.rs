@@ -2,13 +2,18 @@ use std::sync::Arc;
 
 use crate::{
     abi,
+    abi_registry,
     app,
     cli::{CommonFlags, HistoricalWhichCmd, RealtimeCmd, TrackWhichCmd},
     config,
     context::RuntimeContext,
     error::{AppError, Result},
+    findings,
+    pricing,
     provider,
+    reload,
     runtime,
+    token_meta,
 };
 
 pub async fn run(cli: &crate::cli::Cli, which: &TrackWhichCmd, common: &CommonFlags) -> Result<()> {
@@ -56,38 +61,59 @@ async fn run_realtime(cli: &crate::cli::Cli, rt: &RealtimeCmd, common: &CommonFl
     
     let addrs = config::collect_enabled_addresses(&cfg)?;
     track_ctx.verbose_log(&format!("Monitoring {} addresses", addrs.len()));
+    abi_registry::init_from_config(&cfg, &provider, &addrs).await?;
+    token_meta::init_from_config(&cfg, &provider).await?;
+    pricing::init_from_config(&cfg, &provider).await?;
+    findings::init_from_config(&cfg, &provider).await?;
     
     let set = Arc::new(app::build_actionset_v2(&provider, &cfg, &cli).await?);
     track_ctx.verbose_log(&format!("ActionSet built with {} actions", ctx.get_enabled_actions().len()));
     
-    if rt.deployments {
-        track_ctx.verbose_log("Running contract deployment tracking");
-        runtime::realtime::run_contract_deployments(provider, Some(set))
-            .await
-            .map_err(|e| AppError::General(e.to_string()))
-    } else if rt.blocks {
-        if rt.pending_blocks {
-            track_ctx.verbose_log("Running pending transactions tracking");
-            return runtime::realtime::run_pending_transactions(
-                provider,
-                addrs,
-                Some(set),
-                rt.pending_hashes_only,
-            )
-            .await
-            .map_err(|e| AppError::General(e.to_string()));
+    // 热重载需要与运行时共享同一个 provider / ActionSet
+    let watch_provider = provider.clone();
+    let watch_set = set.clone();
+    let watch_addrs = addrs.clone();
+
+    let run = async {
+        if rt.deployments {
+            track_ctx.verbose_log("Running contract deployment tracking");
+            runtime::realtime::run_contract_deployments(provider, Some(set), rt.trace_creations)
+                .await
+                .map_err(|e| AppError::General(e.to_string()))
+        } else if rt.blocks {
+            if rt.pending_blocks {
+                track_ctx.verbose_log("Running pending transactions tracking");
+                return runtime::realtime::run_pending_transactions(
+                    provider,
+                    addrs,
+                    Some(set),
+                    rt.pending_hashes_only,
+                )
+                .await
+                .map_err(|e| AppError::General(e.to_string()));
+            }
+            track_ctx.verbose_log("Running block tracking");
+            runtime::realtime::run_blocks(provider, addrs, Some(set))
+                .await
+                .map_err(|e| AppError::General(e.to_string()))
+        } else {
+            track_ctx.verbose_log("Running event tracking");
+            runtime::realtime::run_events(provider, addrs, Some(set))
+                .await
+                .map_err(|e| AppError::General(e.to_string()))
         }
-        // blocks path: rebuild set for blocks (same build function for now)
-        track_ctx.verbose_log("Running block tracking");
-        let set2 = app::build_actionset_v2(&provider, &cfg, &cli).await?;
-        runtime::realtime::run_blocks(provider, addrs, Some(Arc::new(set2)))
-            .await
-            .map_err(|e| AppError::General(e.to_string()))
-    } else {
-        track_ctx.verbose_log("Running event tracking");
-        runtime::realtime::run_events(provider, addrs, Some(set))
-            .await
-            .map_err(|e| AppError::General(e.to_string()))
+    };
+
+    if !rt.hot_reload {
+        return run.await;
+    }
+    track_ctx.verbose_log(&format!(
+        "Hot reload enabled: watching {} and signature files",
+        cfg_path.display()
+    ));
+    tokio::select! {
+        r = run => r,
+        _ = reload::watch(cfg_path, &watch_provider, cli, &watch_set, &watch_addrs) => Ok(()),
     }
 }
 
@@ -148,6 +174,10 @@ async fn run_historical(
             
             let addrs = config::collect_enabled_addresses(&cfg2)?;
             hist_ctx.verbose_log(&format!("Monitoring {} addresses for events", addrs.len()));
+            abi_registry::init_from_config(&cfg2, &provider, &addrs).await?;
+            token_meta::init_from_config(&cfg2, &provider).await?;
+            pricing::init_from_config(&cfg2, &provider).await?;
+            findings::init_from_config(&cfg2, &provider).await?;
             
             let set = app::build_actionset_v2(&provider, &cfg2, &cli).await?;
             hist_ctx.verbose_log("ActionSet built for historical events");
@@ -175,6 +205,10 @@ async fn run_historical(
             
             let addrs = config::collect_enabled_addresses(&cfg2)?;
             hist_ctx.verbose_log(&format!("Monitoring {} addresses for blocks", addrs.len()));
+            abi_registry::init_from_config(&cfg2, &provider, &addrs).await?;
+            token_meta::init_from_config(&cfg2, &provider).await?;
+            pricing::init_from_config(&cfg2, &provider).await?;
+            findings::init_from_config(&cfg2, &provider).await?;
             
             let set2 = app::build_actionset_v2(&provider, &cfg2, &cli).await?;
             hist_ctx.verbose_log("ActionSet built for historical blocks");
@@ -19,6 +19,20 @@ pub struct Config {
     pub max_requests_per_second: u32,
     #[serde(default)]
     pub output: Option<OutputConfig>,
+    /// 按合约地址的 ABI 注册表（浏览器自动拉取 + 磁盘缓存）
+    #[serde(rename = "contract-abi")]
+    #[serde(default)]
+    pub contract_abi: Option<crate::abi_registry::ContractAbiConfig>,
+    /// 代币元数据（name/symbol/decimals/totalSupply）的磁盘缓存
+    #[serde(rename = "token-metadata")]
+    #[serde(default)]
+    pub token_metadata: Option<crate::token_meta::TokenMetaConfig>,
+    /// 链上 USD 价格来源（Chainlink / Uniswap V2 交易对），覆盖当前链的内置预设
+    #[serde(default)]
+    pub pricing: Option<crate::pricing::PricingConfig>,
+    /// initscan 发现记录写入 Postgres（未配置时使用 JSON 文件）
+    #[serde(default)]
+    pub findings: Option<crate::findings::FindingsConfig>,
 }
 
 impl Default for Config {
@@ -30,6 +44,10 @@ impl Default for Config {
             func_sigs_path: None,
             max_requests_per_second: 10,
             output: None,
+            contract_abi: None,
+            token_metadata: None,
+            pricing: None,
+            findings: None,
         }
     }
 }
@@ -1,13 +1,14 @@
-use crate::error::Result;
-use alloy_json_abi::{AbiItem, Event};
+use crate::error::{AppError, Result};
+use alloy_dyn_abi::Specifier;
+use alloy_json_abi::{AbiItem, Error as AbiError, Event, Function};
 use alloy_primitives::{hex, keccak256, B256};
 use reqwest::Client;
 use serde_json::json;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // Merge event signatures from an ABI file into an output JSON map.
-// Format: { "0x<topic0>": { name, sig, abi } }
+// Format: { "0x<topic0>": { name, sig, abi } }，同一 topic0 有多个变体（indexed 布局不同）时为数组
 pub fn add_events_from_abi<P: AsRef<Path>>(abi_path: P, output_path: P) -> Result<()> {
     let abi_text = fs::read_to_string(&abi_path)?;
     let items: Vec<AbiItem<'_>> = serde_json::from_str(&abi_text)?;
@@ -37,7 +38,27 @@ pub fn add_events_from_abi<P: AsRef<Path>>(abi_path: P, output_path: P) -> Resul
                 "sig": sig,
                 "abi": ev,
             });
-            out_map.insert(key, entry);
+            // 保留已有变体（例如 ERC-20 与 ERC-721 的 Transfer），indexed 布局与 anonymous 都相同才算重复
+            let mut variants = match out_map.remove(&key) {
+                Some(serde_json::Value::Array(v)) => v,
+                Some(v) => vec![v],
+                None => Vec::new(),
+            };
+            let layout = event_layout(&ev);
+            let duplicate = variants.iter().any(|v| {
+                v.get("abi")
+                    .and_then(|a| serde_json::from_value::<Event>(a.clone()).ok())
+                    .is_some_and(|e| event_layout(&e) == layout)
+            });
+            if !duplicate {
+                variants.push(entry);
+            }
+            let value = if variants.len() == 1 {
+                variants.remove(0)
+            } else {
+                serde_json::Value::Array(variants)
+            };
+            out_map.insert(key, value);
         }
     }
 
@@ -49,6 +70,10 @@ pub fn add_events_from_abi<P: AsRef<Path>>(abi_path: P, output_path: P) -> Resul
     Ok(())
 }
 
+fn event_layout(ev: &Event) -> (Vec<bool>, bool) {
+    (ev.inputs.iter().map(|p| p.indexed).collect(), ev.anonymous)
+}
+
 pub async fn fetch_abi_from_scanner(
     address: &str,
     scanner_url: &str,
@@ -69,3 +94,394 @@ pub async fn fetch_abi_from_scanner(
     let text = resp.text().await?;
     Ok(text)
 }
+
+// ========== 签名数据库批量导入（4byte / openchain） ==========
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigKind {
+    Function,
+    Event,
+    Error,
+}
+
+impl std::str::FromStr for SigKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "function" | "func" => Ok(Self::Function),
+            "event" => Ok(Self::Event),
+            "error" => Ok(Self::Error),
+            other => Err(AppError::Config(format!("unknown signature kind: {other}"))),
+        }
+    }
+}
+
+/// 导入结果统计（按签名类型）
+#[derive(Debug, Default, Clone)]
+pub struct SigImportStats {
+    /// 新增的 selector
+    pub added: usize,
+    /// 已存在的相同签名
+    pub duplicates: usize,
+    /// 追加到已有 selector 的冲突签名
+    pub collisions: usize,
+    /// 无法解析为 ABI 的签名
+    pub invalid: usize,
+    /// 给定 selector 与签名哈希不一致
+    pub mismatched: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SigImportReport {
+    pub functions: SigImportStats,
+    pub events: SigImportStats,
+    pub errors: SigImportStats,
+    /// 没有识别出签名的行/条目
+    pub skipped: usize,
+}
+
+pub struct SigImportPaths {
+    pub func: PathBuf,
+    pub event: PathBuf,
+    pub error: PathBuf,
+}
+
+/// 从 dump 中读出的一条记录
+#[derive(Debug, PartialEq, Eq)]
+struct SigRecord {
+    selector: Option<String>,
+    signature: String,
+    kind: Option<SigKind>,
+}
+
+/// 导入一个或多个签名 dump（文本 `selector,signature` / 纯签名 / 4byte CSV，或 4byte、openchain JSON）
+pub fn import_sigs(
+    inputs: &[PathBuf],
+    kind: Option<SigKind>,
+    paths: &SigImportPaths,
+) -> Result<SigImportReport> {
+    let mut report = SigImportReport::default();
+    let mut records = Vec::new();
+    for input in inputs {
+        let text = fs::read_to_string(input)?;
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            let v: serde_json::Value = serde_json::from_str(trimmed)?;
+            records.extend(records_from_json(&v, None, &mut report.skipped));
+        } else {
+            for line in text.lines() {
+                match parse_sig_line(line) {
+                    Some(r) => records.push(r),
+                    None if line.trim().is_empty() => {}
+                    None => report.skipped += 1,
+                }
+            }
+        }
+    }
+
+    let mut funcs = load_json_map(&paths.func)?;
+    let mut events = load_json_map(&paths.event)?;
+    let mut errors = load_json_map(&paths.error)?;
+    for r in records {
+        let k = r.kind.or(kind).unwrap_or(match &r.selector {
+            Some(sel) if sel.len() == 66 => SigKind::Event,
+            _ => SigKind::Function,
+        });
+        let (map, stats) = match k {
+            SigKind::Function => (&mut funcs, &mut report.functions),
+            SigKind::Event => (&mut events, &mut report.events),
+            SigKind::Error => (&mut errors, &mut report.errors),
+        };
+        let Some((selector, sig, entry)) = build_sig_entry(k, &r.signature) else {
+            stats.invalid += 1;
+            continue;
+        };
+        if r.selector.as_ref().is_some_and(|s| s.to_ascii_lowercase() != selector) {
+            stats.mismatched += 1;
+            continue;
+        }
+        merge_sig(map, selector, sig, entry, stats);
+    }
+
+    for (path, map, stats) in [
+        (&paths.func, funcs, &report.functions),
+        (&paths.event, events, &report.events),
+        (&paths.error, errors, &report.errors),
+    ] {
+        if stats.added + stats.collisions > 0 {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).ok();
+            }
+            fs::write(path, serde_json::to_string_pretty(&map)?)?;
+        }
+    }
+    Ok(report)
+}
+
+fn load_json_map(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>> {
+    if !path.exists() {
+        return Ok(serde_json::Map::new());
+    }
+    let s = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&s)?)
+}
+
+/// 解析签名并生成 sig 文件条目，返回 (selector, 规范签名, 条目)
+fn build_sig_entry(kind: SigKind, signature: &str) -> Option<(String, String, serde_json::Value)> {
+    let sig = signature.trim();
+    match kind {
+        SigKind::Function => {
+            let f = Function::parse(sig).ok()?;
+            f.inputs.iter().all(|p| p.resolve().is_ok()).then_some(())?;
+            let canonical = f.signature();
+            let selector = format!("0x{}", hex::encode(f.selector()));
+            Some((selector, canonical.clone(), json!({ "name": f.name, "sig": canonical, "abi": f })))
+        }
+        SigKind::Event => {
+            let e = Event::parse(sig).ok()?;
+            e.inputs.iter().all(|p| p.resolve().is_ok()).then_some(())?;
+            let canonical = e.signature();
+            let selector = format!("0x{}", hex::encode(e.selector()));
+            let entry = json!({ "name": e.name, "sig": canonical, "abi": e, "unknown_indexed": true });
+            Some((selector, canonical, entry))
+        }
+        SigKind::Error => {
+            let e = AbiError::parse(sig).ok()?;
+            e.inputs.iter().all(|p| p.resolve().is_ok()).then_some(())?;
+            let canonical = e.signature();
+            let selector = format!("0x{}", hex::encode(e.selector()));
+            Some((selector, canonical.clone(), json!({ "name": e.name, "sig": canonical, "abi": e })))
+        }
+    }
+}
+
+/// 合并到 sig 文件：新 selector 直接写入；已有 selector 则把不同的签名记入 `collisions`
+fn merge_sig(
+    map: &mut serde_json::Map<String, serde_json::Value>,
+    selector: String,
+    sig: String,
+    entry: serde_json::Value,
+    stats: &mut SigImportStats,
+) {
+    let Some(existing) = map.get_mut(&selector) else {
+        map.insert(selector, entry);
+        stats.added += 1;
+        return;
+    };
+    // event_sigs.json 的值可能是变体数组，冲突记在第一个变体上
+    let known: Vec<&serde_json::Value> = match &*existing {
+        serde_json::Value::Array(v) => v.iter().collect(),
+        other => vec![other],
+    };
+    let seen = known.iter().any(|e| {
+        e.get("sig").and_then(|s| s.as_str()) == Some(sig.as_str())
+            || e.get("collisions")
+                .and_then(|c| c.as_array())
+                .is_some_and(|c| c.iter().any(|s| s.as_str() == Some(sig.as_str())))
+    });
+    if seen {
+        stats.duplicates += 1;
+        return;
+    }
+    let primary = match existing {
+        serde_json::Value::Array(v) => v.first_mut(),
+        other => Some(other),
+    };
+    if let Some(serde_json::Value::Object(obj)) = primary {
+        let list = obj.entry("collisions").or_insert_with(|| json!([]));
+        if let Some(arr) = list.as_array_mut() {
+            arr.push(json!(sig));
+            stats.collisions += 1;
+        }
+    }
+}
+
+/// 解析一行文本：`0xselector,sig`、`0xselector sig`、`sig` 或 4byte CSV（id,created_at,text_signature,hex_signature,...）
+fn parse_sig_line(line: &str) -> Option<SigRecord> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    // 签名本身含逗号，按括号配对定位，而不是按分隔符切分
+    let open = line.find('(')?;
+    let start = line[..open]
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    if start == open {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut end = None;
+    for (i, c) in line[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(open + i + 1);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let end = end?;
+    let signature = line[start..end].to_string();
+    let rest = format!("{} {}", &line[..start], &line[end..]);
+    let selector = rest
+        .split(|c: char| c == ',' || c == ';' || c == '\t' || c.is_whitespace())
+        .map(|t| t.trim_matches('"'))
+        .find(|t| {
+            t.starts_with("0x")
+                && (t.len() == 10 || t.len() == 66)
+                && t[2..].chars().all(|c| c.is_ascii_hexdigit())
+        })
+        .map(|t| t.to_ascii_lowercase());
+    Some(SigRecord { selector, signature, kind: None })
+}
+
+/// 支持 4byte API（`results[].text_signature/hex_signature`）、openchain（`result.function/event.{sel: [{name}]}`）
+/// 以及简单映射 `{sel: sig | [sig]}`
+fn records_from_json(v: &serde_json::Value, kind: Option<SigKind>, skipped: &mut usize) -> Vec<SigRecord> {
+    let mut out = Vec::new();
+    if let Some(results) = v.get("results") {
+        return records_from_json(results, kind, skipped);
+    }
+    if let Some(result) = v.get("result").filter(|r| r.is_object()) {
+        for (section, k) in [("function", SigKind::Function), ("event", SigKind::Event), ("error", SigKind::Error)] {
+            if let Some(sec) = result.get(section) {
+                out.extend(records_from_json(sec, Some(k), skipped));
+            }
+        }
+        return out;
+    }
+    match v {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                let sig = signature_of(item);
+                let selector = item
+                    .get("hex_signature")
+                    .and_then(|s| s.as_str())
+                    .map(|s| s.to_ascii_lowercase());
+                match sig {
+                    Some(signature) => out.push(SigRecord { selector, signature, kind }),
+                    None => *skipped += 1,
+                }
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (sel, val) in map {
+                let sigs: Vec<String> = match val {
+                    serde_json::Value::Array(list) => list.iter().filter_map(signature_of).collect(),
+                    other => signature_of(other).into_iter().collect(),
+                };
+                if sigs.is_empty() {
+                    *skipped += 1;
+                }
+                for signature in sigs {
+                    out.push(SigRecord { selector: Some(sel.to_ascii_lowercase()), signature, kind });
+                }
+            }
+        }
+        _ => *skipped += 1,
+    }
+    out
+}
+
+fn signature_of(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Object(o) => ["text_signature", "name", "signature", "sig"]
+            .iter()
+            .find_map(|k| o.get(*k).and_then(|s| s.as_str()))
+            .map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dump_lines_with_commas_in_signature() {
+        let r = parse_sig_line("0xa9059cbb,transfer(address,uint256)").unwrap();
+        assert_eq!(r.selector.as_deref(), Some("0xa9059cbb"));
+        assert_eq!(r.signature, "transfer(address,uint256)");
+        // 4byte CSV export
+        let r = parse_sig_line("145,2018-05-11,\"transfer(address,uint256)\",0xa9059cbb,\\xa9059cbb").unwrap();
+        assert_eq!(r.selector.as_deref(), Some("0xa9059cbb"));
+        assert_eq!(r.signature, "transfer(address,uint256)");
+        assert_eq!(parse_sig_line("swap((address,uint256)[],bytes)").unwrap().signature, "swap((address,uint256)[],bytes)");
+        assert!(parse_sig_line("id,created_at,text_signature").is_none());
+    }
+
+    #[test]
+    fn records_collisions_per_selector() {
+        let dir = std::env::temp_dir().join(format!("evm-track-sigs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dump = dir.join("dump.txt");
+        // 0x095ea7b3 与 sign_szabo_bytecode(bytes16,uint128) 是已知碰撞
+        fs::write(
+            &dump,
+            "0x095ea7b3,approve(address,uint256)\n0x095ea7b3,sign_szabo_bytecode(bytes16,uint128)\n\
+             0x095ea7b3,approve(address,uint256)\n0xdeadbeef,transfer(address,uint256)\nnot a signature\n",
+        )
+        .unwrap();
+        let paths = SigImportPaths {
+            func: dir.join("func_sigs.json"),
+            event: dir.join("event_sigs.json"),
+            error: dir.join("error_sigs.json"),
+        };
+        let report = import_sigs(&[dump], None, &paths).unwrap();
+        assert_eq!(report.functions.added, 1);
+        assert_eq!(report.functions.collisions, 1);
+        assert_eq!(report.functions.duplicates, 1);
+        assert_eq!(report.functions.mismatched, 1);
+        assert_eq!(report.skipped, 1);
+
+        let funcs = crate::abi::load_func_sigs(&paths.func).unwrap();
+        let entry = &funcs["0x095ea7b3"];
+        assert_eq!(entry.sig, "approve(address,uint256)");
+        assert_eq!(entry.collisions, vec!["sign_szabo_bytecode(bytes16,uint128)".to_string()]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keeps_every_transfer_variant_across_abi_imports() {
+        let dir = std::env::temp_dir().join(format!("evm-track-abi-events-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let transfer = |indexed: [bool; 3]| {
+            json!([{
+                "type": "event",
+                "name": "Transfer",
+                "anonymous": false,
+                "inputs": [
+                    { "name": "from", "type": "address", "indexed": indexed[0] },
+                    { "name": "to", "type": "address", "indexed": indexed[1] },
+                    { "name": "value", "type": "uint256", "indexed": indexed[2] }
+                ]
+            }])
+        };
+        let erc20 = dir.join("erc20.json");
+        let erc721 = dir.join("erc721.json");
+        fs::write(&erc20, transfer([true, true, false]).to_string()).unwrap();
+        fs::write(&erc721, transfer([true, true, true]).to_string()).unwrap();
+        let out = dir.join("event_sigs.json");
+
+        add_events_from_abi(&erc20, &out).unwrap();
+        add_events_from_abi(&erc721, &out).unwrap();
+        // importing the same ABI again adds nothing
+        add_events_from_abi(&erc20, &out).unwrap();
+
+        let sigs = crate::abi::load_event_sigs(&out).unwrap();
+        let variants = &sigs["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"];
+        let layouts: Vec<_> = variants.iter().map(|e| event_layout(&e.abi)).collect();
+        assert_eq!(layouts, vec![(vec![true, true, false], false), (vec![true, true, true], false)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -64,6 +64,35 @@ pub enum AppError {
 /// A convenience `Result` type for the application.
 pub type Result<T, E = AppError> = std::result::Result<T, E>;
 
+impl AppError {
+    /// 节点明确答复不支持该方法（而不是超时、限流等临时错误），可以据此切换后端
+    pub fn is_method_unsupported(&self) -> bool {
+        match self {
+            AppError::Rpc(e) => rpc_method_unsupported(e),
+            AppError::Collect(e) => e.is_method_unsupported(),
+            _ => false,
+        }
+    }
+}
+
+impl CollectError {
+    /// 同 [`AppError::is_method_unsupported`]
+    pub fn is_method_unsupported(&self) -> bool {
+        matches!(self, CollectError::ProviderError(e) if rpc_method_unsupported(e))
+    }
+}
+
+fn rpc_method_unsupported(e: &RpcError<TransportErrorKind>) -> bool {
+    e.as_error_resp().is_some_and(|r| method_unsupported(r.code, &r.message))
+}
+
+/// 仅认 -32601 及 "method not found" / "does not exist"（geth: "the method ... does not exist/is not available"）；
+/// "disabled"、"not supported" 之类的消息也可能来自限流或参数错误，不据此切换后端
+fn method_unsupported(code: i64, message: &str) -> bool {
+    let msg = message.to_ascii_lowercase();
+    code == -32601 || msg.contains("method not found") || msg.contains("does not exist")
+}
+
 //
 // Data Collection Errors (`source.rs`)
 //
@@ -125,4 +154,20 @@ pub enum ParseError {
 /// Using a transparent wrapper around sqlx::Error.
 #[derive(Error, Debug)]
 #[error(transparent)]
-pub struct DbError(pub sqlx::Error);
\ No newline at end of file
+pub struct DbError(pub sqlx::Error);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_missing_methods_count_as_unsupported() {
+        assert!(method_unsupported(-32601, "whatever"));
+        assert!(method_unsupported(-32000, "Method not found"));
+        assert!(method_unsupported(-32000, "the method trace_call does not exist/is not available"));
+        assert!(!method_unsupported(-32000, "tracer not found"));
+        assert!(!method_unsupported(-32000, "debug namespace disabled for this plan"));
+        assert!(!method_unsupported(-32602, "block tag not supported"));
+        assert!(!method_unsupported(-32005, "rate limit exceeded"));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::registry::ActionFactory;
-use crate::actions::{Action, deployment::{DeploymentScanAction, DeploymentOptions}};
+use crate::actions::{Action, deployment::{self, DeploymentScanAction}};
 use crate::config::ActionConfig;
 use crate::error::Result;
 use alloy_provider::RootProvider;
@@ -14,16 +14,10 @@ impl ActionFactory for DeploymentActionFactory {
         &self,
         config: &ActionConfig,
         provider: Arc<RootProvider<BoxTransport>>,
-        _cli: &crate::cli::Cli,
+        cli: &crate::cli::Cli,
         _output_manager: Option<crate::output::GlobalOutputManager>,
     ) -> Result<Box<dyn Action>> {
-        let options = &config.options;
-        
-        let output_filepath = options.get("output-filepath")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        let deployment_opts = DeploymentOptions { output_filepath };
+        let deployment_opts = deployment::parse_options(&config.options, cli.webhook_url.as_ref())?;
 
         Ok(Box::new(DeploymentScanAction::new(provider, deployment_opts)))
     }
@@ -41,7 +35,10 @@ impl ActionFactory for DeploymentActionFactory {
             "enabled": true,
             "addresses": {},
             "options": {
-                "output-filepath": "./deployments.json"
+                "output-filepath": "./deployments.json",
+                "fingerprint-index-filepath": "./data/fingerprints.jsonl",
+                "fingerprint-references-filepath": "./data/fingerprint_refs.json",
+                "webhook-url": ""
             }
         })
     }
@@ -53,16 +50,17 @@ pub struct OwnershipActionFactory;
 impl ActionFactory for OwnershipActionFactory {
     fn create_action(
         &self,
-        _config: &ActionConfig,
-        _provider: Arc<RootProvider<BoxTransport>>,
-        _cli: &crate::cli::Cli,
+        config: &ActionConfig,
+        provider: Arc<RootProvider<BoxTransport>>,
+        cli: &crate::cli::Cli,
         _output_manager: Option<crate::output::GlobalOutputManager>,
     ) -> Result<Box<dyn Action>> {
-        Ok(Box::new(crate::actions::ownership::OwnershipAction))
+        let opts = crate::actions::ownership::parse_options(&config.options, cli.webhook_url.as_ref())?;
+        Ok(Box::new(crate::actions::ownership::OwnershipAction::new(provider, opts)))
     }
 
     fn description(&self) -> &str {
-        "Monitor ownership changes in smart contracts"
+        "Track ownership, AccessControl roles and Safe owners; alert on renounces, fresh-EOA owners and critical role grants"
     }
 
     fn dependencies(&self) -> Vec<String> {
@@ -75,7 +73,11 @@ impl ActionFactory for OwnershipActionFactory {
             "addresses": {
                 "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984": {}
             },
-            "options": {}
+            "options": {
+                "registry-filepath": "./data/access_control.json",
+                "critical-roles": ["DEFAULT_ADMIN_ROLE", "MINTER_ROLE", "UPGRADER_ROLE"],
+                "webhook-url": ""
+            }
         })
     }
 }
@@ -86,16 +88,17 @@ pub struct ProxyUpgradeActionFactory;
 impl ActionFactory for ProxyUpgradeActionFactory {
     fn create_action(
         &self,
-        _config: &ActionConfig,
+        config: &ActionConfig,
         provider: Arc<RootProvider<BoxTransport>>,
         _cli: &crate::cli::Cli,
         _output_manager: Option<crate::output::GlobalOutputManager>,
     ) -> Result<Box<dyn Action>> {
-        Ok(Box::new(crate::actions::proxy::ProxyUpgradeAction::new(provider)))
+        let opts = crate::actions::proxy::parse_options(&config.options, &config.addresses)?;
+        Ok(Box::new(crate::actions::proxy::ProxyUpgradeAction::new(provider, opts)))
     }
 
     fn description(&self) -> &str {
-        "Monitor proxy contract upgrades and implementation changes"
+        "Monitor ERC-1967 / UUPS / beacon / Diamond proxy upgrades (including event-less slot writes), keep a per-proxy upgrade history and diff old vs new implementation bytecode"
     }
 
     fn dependencies(&self) -> Vec<String> {
@@ -108,7 +111,11 @@ impl ActionFactory for ProxyUpgradeActionFactory {
             "addresses": {
                 "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984": {}
             },
-            "options": {}
+            "options": {
+                "history-filepath": "./data/proxy_upgrades.json",
+                "watch-slots": false,
+                "trace-backend": "auto"
+            }
         })
     }
 }
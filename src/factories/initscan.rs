@@ -2,9 +2,10 @@ use crate::registry::ActionFactory;
 use crate::config::ActionConfig;
 use crate::error::{AppError, Result};
 use crate::output::GlobalOutputManager;
-use crate::actions::{Action, initscan::{InitscanAction, InitscanOptions}};
+use crate::actions::{Action, initscan::{InitscanAction, InitscanOptions, InitscanTraceBackend}};
 use alloy_provider::RootProvider;
 use alloy_transport::BoxTransport;
+use std::str::FromStr;
 use std::sync::Arc;
 use tracing::debug;
 
@@ -60,7 +61,16 @@ impl ActionFactory for InitscanActionFactory {
         
         // 解析其他配置选项
         let init_after = o.get("init-after-delay").and_then(|v| v.as_u64()).unwrap_or(1);
-        let usd_threshold = o.get("alert-usd-threshold").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let usd_threshold = o.get("min-usd").or_else(|| o.get("alert-usd-threshold")).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let value_tokens: Vec<alloy_primitives::Address> = o.get("value-tokens").and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|a| a.as_str()?.parse().ok()).collect()).unwrap_or_default();
+        let value_scan_blocks = o.get("value-scan-blocks").and_then(|v| v.as_u64()).unwrap_or(5000);
+        let discover_init_functions = o.get("discover-init-functions").and_then(|v| v.as_bool()).unwrap_or(true);
+        let reproduction_artifacts = o.get("reproduction-artifacts").and_then(|v| v.as_bool()).unwrap_or(false);
+        let trace_backend = match o.get("trace-backend").and_then(|v| v.as_str()) {
+            Some(s) => InitscanTraceBackend::from_str(s)?,
+            None => InitscanTraceBackend::Auto,
+        };
         let webhook_url = o
             .get("webhook-url")
             .and_then(|v| v.as_str())
@@ -80,17 +90,22 @@ impl ActionFactory for InitscanActionFactory {
             check_addresses: check_addrs,
             init_after_delay_secs: init_after,
             usd_threshold,
+            value_tokens,
+            value_scan_blocks,
             func_sigs,
+            discover_init_functions,
+            reproduction_artifacts,
             webhook_url,
             initializable_contracts_filepath: known_path,
             init_known_contracts_frequency_secs: init_known_freq,
             max_inflight_inits,
             debug,
+            trace_backend,
         };
         
         debug!("Creating InitscanAction with options: {:#?}", is_opts);
         
-        Ok(Box::new(InitscanAction::new(provider, is_opts)))
+        Ok(Box::new(InitscanAction::new(provider, is_opts)?))
     }
     
     fn description(&self) -> &str {
@@ -114,6 +129,11 @@ impl ActionFactory for InitscanActionFactory {
                 "init-after-delay": 3,
                 "init-concurrency": 20,
                 "alert-usd-threshold": 0,
+                "value-tokens": [],
+                "value-scan-blocks": 5000,
+                "trace-backend": "auto",
+                "discover-init-functions": true,
+                "reproduction-artifacts": false,
                 "function-signature-calldata": {
                     "initialize()": "0x8129fc1c",
                     "init()": "0xe1c7392a",
@@ -2,6 +2,7 @@ use crate::registry::ActionFactory;
 use crate::actions::{Action, large_transfer::{LargeTransferAction, LargeTransferOptions}};
 use crate::config::ActionConfig;
 use crate::error::Result;
+use crate::{pricing, token_meta};
 use alloy_provider::RootProvider;
 use alloy_transport::BoxTransport;
 use std::sync::Arc;
@@ -33,13 +34,21 @@ impl ActionFactory for LargeTransferActionFactory {
             .map(|v| v as u8)
             .unwrap_or(18);
 
+        let min_usd = options.get("min-usd").and_then(|v| v.as_f64());
+
         let opts = LargeTransferOptions {
             min_amount_human,
             decimals_default,
+            min_usd,
             verbose: false,
         };
 
-        Ok(Box::new(LargeTransferAction::new(opts, cli.verbose)))
+        Ok(Box::new(LargeTransferAction::new(
+            opts,
+            cli.verbose,
+            token_meta::shared()?,
+            pricing::shared()?,
+        )))
     }
 
     fn description(&self) -> &str {
@@ -56,7 +65,8 @@ impl ActionFactory for LargeTransferActionFactory {
             "addresses": {},
             "options": {
                 "min-amount": "1000000000000000000000",
-                "decimals-default": 18
+                "decimals-default": 18,
+                "min-usd": 100000.0
             }
         })
     }
@@ -8,6 +8,8 @@ pub mod large_transfer;
 pub mod deployment;
 pub mod selector_scan;
 pub mod initscan;
+pub mod privscan;
+pub mod storage_watch;
 
 // 重新导出所有工厂
 pub use logging::{LoggingActionFactory, JsonLogActionFactory};
@@ -16,6 +18,8 @@ pub use large_transfer::LargeTransferActionFactory;
 pub use deployment::{DeploymentActionFactory, OwnershipActionFactory, ProxyUpgradeActionFactory};
 pub use selector_scan::{SelectorScanActionFactory, TornadoActionFactory};
 pub use initscan::InitscanActionFactory;
+pub use privscan::PrivscanActionFactory;
+pub use storage_watch::StorageWatchActionFactory;
 
 use crate::registry::ActionRegistry;
 
@@ -34,6 +38,8 @@ pub fn create_default_registry() -> ActionRegistry {
     registry.register("SelectorScan", SelectorScanActionFactory);
     registry.register("TornadoCash", TornadoActionFactory); // 匹配配置文件中的名称
     registry.register("Initscan", InitscanActionFactory);
+    registry.register("Privscan", PrivscanActionFactory);
+    registry.register("StorageWatch", StorageWatchActionFactory);
     
     tracing::info!("🔧 Initialized action registry with {} factories", registry.list_actions().len());
     
@@ -0,0 +1,91 @@
+use crate::registry::ActionFactory;
+use crate::config::ActionConfig;
+use crate::error::{AppError, Result};
+use crate::output::GlobalOutputManager;
+use crate::actions::{Action, initscan::InitscanTraceBackend, privscan::{PrivscanAction, PrivscanOptions}};
+use alloy_primitives::U256;
+use alloy_provider::RootProvider;
+use alloy_transport::BoxTransport;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Privscan Action工厂
+pub struct PrivscanActionFactory;
+
+impl ActionFactory for PrivscanActionFactory {
+    fn create_action(
+        &self,
+        config: &ActionConfig,
+        provider: Arc<RootProvider<BoxTransport>>,
+        cli: &crate::cli::Cli,
+        _output_manager: Option<GlobalOutputManager>,
+    ) -> Result<Box<dyn Action>> {
+        debug!("Creating PrivscanAction with config: {:#?}", config);
+
+        if !config.enabled {
+            return Err(AppError::Config("Privscan action is not enabled".to_string()));
+        }
+
+        let o = &config.options;
+        let defaults = PrivscanOptions::default();
+
+        // 模拟调用的发起者，应与部署者无关
+        let from = match o.get("from-address").and_then(|v| v.as_str()) {
+            Some(s) => s.parse().map_err(|e| AppError::Config(format!("invalid privscan from-address {s}: {e}")))?,
+            None => defaults.from,
+        };
+        // 数量类参数（mint / withdraw 的 amount）
+        let amount = o.get("amount").and_then(|v| v.as_u64()).map(U256::from).unwrap_or(defaults.amount);
+        let trace_backend = match o.get("trace-backend").and_then(|v| v.as_str()) {
+            Some(s) => InitscanTraceBackend::from_str(s)?,
+            None => InitscanTraceBackend::Auto,
+        };
+        let webhook_url = o
+            .get("webhook-url")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .or_else(|| cli.webhook_url.clone());
+
+        let opts = PrivscanOptions {
+            from,
+            amount,
+            probe_after_delay_secs: o.get("probe-after-delay").and_then(|v| v.as_u64()).unwrap_or(defaults.probe_after_delay_secs),
+            max_inflight: o.get("probe-concurrency").and_then(|v| v.as_u64()).map(|v| v as usize),
+            webhook_url,
+            findings_filepath: o.get("findings-filepath").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            trace_backend,
+            debug: o.get("debug").and_then(|v| v.as_bool()).unwrap_or(false),
+        };
+
+        debug!("Creating PrivscanAction with options: {:#?}", opts);
+
+        Ok(Box::new(PrivscanAction::new(provider, opts)))
+    }
+
+    fn description(&self) -> &str {
+        "Simulates privileged functions of new deployments from an unrelated sender and flags unprotected ones"
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn config_example(&self) -> serde_json::Value {
+        serde_json::json!({
+            "enabled": true,
+            "addresses": {},
+            "options": {
+                "from-address": "0x00000000000000000000000000000000deadbeef",
+                "amount": 1,
+                "probe-after-delay": 3,
+                "probe-concurrency": 10,
+                "trace-backend": "auto",
+                "webhook-url": "",
+                "findings-filepath": "./data/eth_privileged_findings.json",
+                "debug": false
+            }
+        })
+    }
+}
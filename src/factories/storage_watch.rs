@@ -0,0 +1,74 @@
+use crate::registry::ActionFactory;
+use crate::actions::{Action, storage_watch::{parse_watched_slots, StorageWatchAction, StorageWatchMode, StorageWatchOptions}};
+use crate::config::ActionConfig;
+use crate::error::{AppError, Result};
+use crate::source::Source;
+use alloy_primitives::Address;
+use alloy_provider::RootProvider;
+use alloy_transport::BoxTransport;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Storage Watch Action 工厂
+pub struct StorageWatchActionFactory;
+
+impl ActionFactory for StorageWatchActionFactory {
+    fn create_action(
+        &self,
+        config: &ActionConfig,
+        provider: Arc<RootProvider<BoxTransport>>,
+        _cli: &crate::cli::Cli,
+        _output_manager: Option<crate::output::GlobalOutputManager>,
+    ) -> Result<Box<dyn Action>> {
+        let options = &config.options;
+
+        let mut slots = Vec::new();
+        for (addr, value) in &config.addresses {
+            let address = Address::from_str(addr)
+                .map_err(|e| AppError::Config(format!("storage watch address {addr}: {e}")))?;
+            slots.extend(parse_watched_slots(address, value)?);
+        }
+
+        let mode = match options.get("mode").and_then(|v| v.as_str()) {
+            Some(s) => StorageWatchMode::from_str(s)?,
+            None => StorageWatchMode::Auto,
+        };
+
+        let poll_interval_blocks = options.get("poll-interval-blocks")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(10);
+
+        let output_filepath = options.get("output-filepath")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let opts = StorageWatchOptions { slots, mode, poll_interval_blocks, output_filepath };
+        let source = Source::from_provider((*provider).clone(), 0);
+
+        Ok(Box::new(StorageWatchAction::new(source, opts)))
+    }
+
+    fn description(&self) -> &str {
+        "Alert when watched storage slots change (prestate/stateDiff tracing, eth_getStorageAt polling fallback)"
+    }
+
+    fn config_example(&self) -> serde_json::Value {
+        serde_json::json!({
+            "enabled": true,
+            "addresses": {
+                "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984": {
+                    "slots": [
+                        { "label": "owner", "slot": "0x0" },
+                        { "preset": "eip1967-implementation" },
+                        { "label": "balances[holder]", "mapping-slot": "0x1", "key": "0x000000000000000000000000000000000000dEaD" }
+                    ]
+                }
+            },
+            "options": {
+                "mode": "auto",
+                "poll-interval-blocks": 10,
+                "output-filepath": "./storage_changes.jsonl"
+            }
+        })
+    }
+}
@@ -2,6 +2,7 @@ use crate::registry::ActionFactory;
 use crate::actions::{Action, transfer::TransferAction};
 use crate::config::ActionConfig;
 use crate::error::Result;
+use crate::token_meta;
 use alloy_provider::RootProvider;
 use alloy_transport::BoxTransport;
 use std::sync::Arc;
@@ -13,11 +14,11 @@ impl ActionFactory for TransferActionFactory {
     fn create_action(
         &self,
         _config: &ActionConfig,
-        provider: Arc<RootProvider<BoxTransport>>,
+        _provider: Arc<RootProvider<BoxTransport>>,
         _cli: &crate::cli::Cli,
         _output_manager: Option<crate::output::GlobalOutputManager>,
     ) -> Result<Box<dyn Action>> {
-        Ok(Box::new(TransferAction::new(provider)))
+        Ok(Box::new(TransferAction::new(token_meta::shared()?)))
     }
 
     fn description(&self) -> &str {
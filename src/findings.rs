@@ -0,0 +1,378 @@
+//! initscan 发现记录的数据库存储（Postgres，复用 `db.rs` 的连接池）
+//!
+//! 每条记录对应 (chain_id, 合约, selector)：首次发现 / 最近一次复检时间、区块、calldata、风险价值、
+//! 状态（open / initialized / expired）以及完成初始化的地址。
+
+use std::{fmt, str::FromStr, sync::Arc};
+
+use alloy_primitives::{Address, Bytes};
+use alloy_provider::{Provider, RootProvider};
+use alloy_transport::BoxTransport;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use tracing::debug;
+
+use crate::config::Config;
+use crate::db::{self, Db};
+use crate::error::{AppError, DbError, Result};
+use crate::throttle;
+
+static STORE: OnceCell<Arc<FindingStore>> = OnceCell::new();
+
+/// 发现记录数据库配置（顶层 `findings`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FindingsConfig {
+    /// Postgres 连接串；缺省时读取环境变量 `DATABASE_URL`
+    #[serde(rename = "database-url", default)]
+    pub database_url: Option<String>,
+}
+
+impl FindingsConfig {
+    pub fn resolve_url(&self) -> Result<String> {
+        self.database_url
+            .clone()
+            .filter(|s| !s.is_empty())
+            .or_else(|| std::env::var("DATABASE_URL").ok())
+            .ok_or_else(|| AppError::Config("findings: database-url or DATABASE_URL is required".to_string()))
+    }
+}
+
+/// 发现记录的生命周期状态；initialized / expired 为终态，之后不会再被重新打开
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingStatus {
+    /// 仍可被任意地址初始化
+    #[default]
+    Open,
+    /// 调用已回滚：已被他人初始化
+    Initialized,
+    /// 调用仍成功但不再满足启发式（合约逻辑或状态已变化）
+    Expired,
+}
+
+impl FindingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FindingStatus::Open => "open",
+            FindingStatus::Initialized => "initialized",
+            FindingStatus::Expired => "expired",
+        }
+    }
+}
+
+impl fmt::Display for FindingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FindingStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "open" => Ok(Self::Open),
+            "initialized" | "initialized-by-someone" => Ok(Self::Initialized),
+            "expired" => Ok(Self::Expired),
+            other => Err(AppError::Config(format!("unknown finding status: {other}"))),
+        }
+    }
+}
+
+/// [`FindingStore::record_open`] 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordOutcome {
+    /// 新的发现
+    New,
+    /// 已有且仍为 open 的发现，已刷新
+    Refreshed,
+    /// 已有且已结束的发现，保持不变
+    Closed,
+}
+
+/// 一条发现记录
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub chain_id: u64,
+    pub contract: Address,
+    pub calldata: Bytes,
+    pub block_number: Option<u64>,
+    pub first_seen: DateTime<Utc>,
+    pub last_verified: DateTime<Utc>,
+    pub value_usd: Option<f64>,
+    pub status: FindingStatus,
+    pub initialized_by: Option<Address>,
+}
+
+impl TryFrom<PgRow> for Finding {
+    type Error = AppError;
+
+    fn try_from(row: PgRow) -> Result<Self> {
+        let parse_addr = |s: &str| s.parse::<Address>().map_err(|e| AppError::General(format!("bad address {s} in findings: {e}")));
+        let calldata: String = row.try_get("calldata").map_err(DbError)?;
+        let status: String = row.try_get("status").map_err(DbError)?;
+        let initialized_by: Option<String> = row.try_get("initialized_by").map_err(DbError)?;
+        Ok(Finding {
+            chain_id: row.try_get::<i64, _>("chain_id").map_err(DbError)? as u64,
+            contract: parse_addr(&row.try_get::<String, _>("contract").map_err(DbError)?)?,
+            calldata: Bytes::from(hex::decode(calldata.trim_start_matches("0x"))?),
+            block_number: row.try_get::<Option<i64>, _>("block_number").map_err(DbError)?.map(|n| n as u64),
+            first_seen: row.try_get("first_seen").map_err(DbError)?,
+            last_verified: row.try_get("last_verified").map_err(DbError)?,
+            value_usd: row.try_get("value_usd").map_err(DbError)?,
+            status: status.parse()?,
+            initialized_by: initialized_by.as_deref().map(parse_addr).transpose()?,
+        })
+    }
+}
+
+/// 查询条件；各字段为 None 时不过滤
+#[derive(Debug, Clone)]
+pub struct FindingQuery {
+    pub chain_id: Option<u64>,
+    pub status: Option<FindingStatus>,
+    pub contract: Option<Address>,
+    pub min_usd: Option<f64>,
+    pub limit: i64,
+}
+
+impl Default for FindingQuery {
+    fn default() -> Self {
+        Self { chain_id: None, status: None, contract: None, min_usd: None, limit: 100 }
+    }
+}
+
+/// 建表（幂等）；旧表（主键不含 selector）就地迁移
+pub async fn create_findings_table(db: &Db) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS initscan_findings (
+            chain_id BIGINT NOT NULL,
+            contract TEXT NOT NULL,
+            selector TEXT NOT NULL,
+            calldata TEXT NOT NULL,
+            block_number BIGINT,
+            first_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            last_verified TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            value_usd DOUBLE PRECISION,
+            status TEXT NOT NULL DEFAULT 'open',
+            initialized_by TEXT,
+            PRIMARY KEY (chain_id, contract, selector)
+        )
+        "#,
+    )
+    .execute(&db.pool)
+    .await
+    .map_err(DbError)?;
+    for stmt in [
+        "ALTER TABLE initscan_findings ADD COLUMN IF NOT EXISTS selector TEXT",
+        "UPDATE initscan_findings SET selector = LEFT(calldata, 10) WHERE selector IS NULL",
+        "ALTER TABLE initscan_findings ALTER COLUMN selector SET NOT NULL",
+        r#"
+        DO $$
+        BEGIN
+            IF NOT EXISTS (
+                SELECT 1 FROM information_schema.key_column_usage
+                WHERE table_name = 'initscan_findings' AND constraint_name = 'initscan_findings_pkey' AND column_name = 'selector'
+            ) THEN
+                ALTER TABLE initscan_findings DROP CONSTRAINT IF EXISTS initscan_findings_pkey;
+                ALTER TABLE initscan_findings ADD PRIMARY KEY (chain_id, contract, selector);
+            END IF;
+        END $$
+        "#,
+    ] {
+        sqlx::query(stmt).execute(&db.pool).await.map_err(DbError)?;
+    }
+    Ok(())
+}
+
+/// 按条件列出记录，最新发现的在前
+pub async fn query(db: &Db, q: &FindingQuery) -> Result<Vec<Finding>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT chain_id, contract, calldata, block_number, first_seen, last_verified, value_usd, status, initialized_by
+        FROM initscan_findings
+        WHERE ($1::BIGINT IS NULL OR chain_id = $1)
+          AND ($2::TEXT IS NULL OR status = $2)
+          AND ($3::TEXT IS NULL OR contract = $3)
+          AND ($4::DOUBLE PRECISION IS NULL OR value_usd >= $4)
+        ORDER BY first_seen DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(q.chain_id.map(|c| c as i64))
+    .bind(q.status.map(|s| s.as_str()))
+    .bind(q.contract.map(fmt_addr))
+    .bind(q.min_usd)
+    .bind(q.limit)
+    .fetch_all(&db.pool)
+    .await
+    .map_err(DbError)?;
+    rows.into_iter().map(Finding::try_from).collect()
+}
+
+/// 当前链的发现记录读写
+pub struct FindingStore {
+    db: Db,
+    chain_id: u64,
+}
+
+impl FindingStore {
+    pub async fn connect(database_url: &str, chain_id: u64) -> Result<Self> {
+        let db = db::connect(database_url).await?;
+        create_findings_table(&db).await?;
+        Ok(Self { db, chain_id })
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// 记录一条可初始化的发现，或刷新同一 (合约, selector) 仍为 open 的记录；
+    /// 已结束（initialized / expired）的记录保持不变
+    pub async fn record_open(&self, contract: Address, calldata: &[u8], block_number: Option<u64>, value_usd: Option<f64>) -> Result<RecordOutcome> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO initscan_findings (chain_id, contract, selector, calldata, block_number, value_usd, status)
+            VALUES ($1, $2, $3, $4, $5, $6, 'open')
+            ON CONFLICT (chain_id, contract, selector) DO UPDATE SET
+                calldata = EXCLUDED.calldata,
+                block_number = COALESCE(initscan_findings.block_number, EXCLUDED.block_number),
+                last_verified = NOW(),
+                value_usd = EXCLUDED.value_usd
+            WHERE initscan_findings.status = 'open'
+            RETURNING (xmax = 0) AS inserted
+            "#,
+        )
+        .bind(self.chain_id as i64)
+        .bind(fmt_addr(contract))
+        .bind(fmt_selector(calldata))
+        .bind(format!("0x{}", hex::encode(calldata)))
+        .bind(block_number.map(|n| n as i64))
+        .bind(value_usd)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(DbError)?;
+        // 冲突行已结束时 WHERE 不成立，不返回任何行
+        let Some(row) = row else {
+            return Ok(RecordOutcome::Closed);
+        };
+        let inserted: bool = row.try_get("inserted").map_err(DbError)?;
+        Ok(if inserted { RecordOutcome::New } else { RecordOutcome::Refreshed })
+    }
+
+    /// 复检仍然成立：刷新 last_verified
+    pub async fn touch(&self, contract: Address, calldata: &[u8]) -> Result<()> {
+        sqlx::query("UPDATE initscan_findings SET last_verified = NOW() WHERE chain_id = $1 AND contract = $2 AND selector = $3")
+            .bind(self.chain_id as i64)
+            .bind(fmt_addr(contract))
+            .bind(fmt_selector(calldata))
+            .execute(&self.db.pool)
+            .await
+            .map_err(DbError)?;
+        Ok(())
+    }
+
+    /// 结束一条 open 记录（initialized / expired）
+    pub async fn close(&self, contract: Address, calldata: &[u8], status: FindingStatus, initialized_by: Option<Address>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE initscan_findings SET status = $4, initialized_by = $5, last_verified = NOW()
+            WHERE chain_id = $1 AND contract = $2 AND selector = $3 AND status = 'open'
+            "#,
+        )
+        .bind(self.chain_id as i64)
+        .bind(fmt_addr(contract))
+        .bind(fmt_selector(calldata))
+        .bind(status.as_str())
+        .bind(initialized_by.map(fmt_addr))
+        .execute(&self.db.pool)
+        .await
+        .map_err(DbError)?;
+        Ok(())
+    }
+
+    /// 当前链上所有 open 记录（复检用）
+    pub async fn open_findings(&self) -> Result<Vec<Finding>> {
+        let q = FindingQuery { chain_id: Some(self.chain_id), status: Some(FindingStatus::Open), limit: i64::MAX, ..Default::default() };
+        query(&self.db, &q).await
+    }
+}
+
+/// 配置了 `findings` 时连接数据库并建表；未配置则 initscan 继续使用 JSON 文件
+pub async fn init_from_config(cfg: &Config, provider: &RootProvider<BoxTransport>) -> Result<()> {
+    let Some(fc) = &cfg.findings else { return Ok(()) };
+    if STORE.get().is_some() {
+        return Ok(());
+    }
+    let url = fc.resolve_url()?;
+    throttle::acquire().await;
+    let chain_id = provider.get_chain_id().await?;
+    let store = FindingStore::connect(&url, chain_id).await?;
+    debug!("[findings] connected, chain={}", chain_id);
+    let _ = STORE.set(Arc::new(store));
+    Ok(())
+}
+
+pub fn global() -> Option<&'static Arc<FindingStore>> {
+    STORE.get()
+}
+
+// 地址统一以小写 0x 十六进制存储，便于按字符串比较
+fn fmt_addr(a: Address) -> String {
+    format!("0x{}", hex::encode(a.0))
+}
+
+// calldata 的前 4 字节，与 `LEFT(calldata, 10)` 一致
+fn fmt_selector(calldata: &[u8]) -> String {
+    format!("0x{}", hex::encode(&calldata[..calldata.len().min(4)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn status_round_trips() {
+        for s in [FindingStatus::Open, FindingStatus::Initialized, FindingStatus::Expired] {
+            assert_eq!(s.as_str().parse::<FindingStatus>().unwrap(), s);
+        }
+        assert_eq!("initialized-by-someone".parse::<FindingStatus>().unwrap(), FindingStatus::Initialized);
+        assert!("closed".parse::<FindingStatus>().is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // This test requires a running PostgreSQL database and a DATABASE_URL env var.
+    async fn finding_lifecycle() -> Result<()> {
+        dotenv::dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set to run this test");
+        let store = FindingStore::connect(&url, 31337).await?;
+        let contract = address!("00000000000000000000000000000000000000aa");
+        let me = address!("4b20993bc481177ec7e8f571cecae8a9e22c02db");
+        sqlx::query("DELETE FROM initscan_findings WHERE chain_id = 31337 AND contract = $1")
+            .bind(fmt_addr(contract))
+            .execute(&store.db.pool)
+            .await
+            .map_err(DbError)?;
+        let init = [0x81, 0x29, 0xfc, 0x1c];
+        let setup = [0x0a, 0xe9, 0xd9, 0x3c];
+        assert_eq!(store.record_open(contract, &init, Some(1), Some(12.5)).await?, RecordOutcome::New);
+        assert_eq!(store.record_open(contract, &init, Some(1), Some(12.5)).await?, RecordOutcome::Refreshed);
+        // a second initializer of the same contract is its own finding
+        store.record_open(contract, &setup, Some(1), Some(12.5)).await?;
+        assert_eq!(store.open_findings().await?.iter().filter(|f| f.contract == contract).count(), 2);
+        store.close(contract, &init, FindingStatus::Initialized, Some(me)).await?;
+        // rediscovering a closed finding neither reopens it nor clears initialized_by
+        assert_eq!(store.record_open(contract, &init, Some(2), Some(99.0)).await?, RecordOutcome::Closed);
+        let q = FindingQuery { chain_id: Some(31337), contract: Some(contract), status: Some(FindingStatus::Initialized), ..Default::default() };
+        let found = query(&store.db, &q).await?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].calldata[..], init);
+        assert_eq!(found[0].initialized_by, Some(me));
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+//! 部署字节码指纹索引（本地 JSONL 文件）
+//!
+//! 每个新部署记录运行时代码哈希与归一化指纹（去掉 metadata、抹掉 immutable，见 `bytecode::normalize`），
+//! 指纹相同的部署视为同一模板的一个簇。参考文件给已知模板（骗局代币、drainer、攻击合约……）打标签，
+//! 新部署落入带标签的簇时告警。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use alloy_primitives::{Address, Bytes, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::bytecode;
+use crate::error::{AppError, Result};
+
+/// 索引中的一条部署
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Member {
+    pub contract: Address,
+    #[serde(default)]
+    pub deployer: Option<Address>,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    pub code_hash: B256,
+    pub fingerprint: B256,
+    pub code_size: usize,
+}
+
+/// 参考文件中的一项：`label` 加上 `fingerprint`、`codeHash`、`bytecode`（运行时代码）之一
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceEntry {
+    label: String,
+    #[serde(default)]
+    fingerprint: Option<B256>,
+    #[serde(default)]
+    code_hash: Option<B256>,
+    #[serde(default)]
+    bytecode: Option<Bytes>,
+}
+
+/// 带标签的参考指纹
+#[derive(Debug, Default)]
+pub struct References {
+    by_fingerprint: HashMap<B256, String>,
+    by_code_hash: HashMap<B256, String>,
+}
+
+impl References {
+    /// 读取参考文件（JSON 数组）；文件不存在时视为没有参考并打印警告
+    pub fn load(path: &str) -> Result<Self> {
+        let data = match std::fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("[fingerprint] references {path} not found, no labels");
+                return Ok(References::default());
+            }
+            Err(e) => return Err(AppError::Config(format!("fingerprint references {path}: {e}"))),
+        };
+        let entries: Vec<ReferenceEntry> = serde_json::from_str(&data)
+            .map_err(|e| AppError::Config(format!("fingerprint references {path}: {e}")))?;
+        let mut refs = References::default();
+        for e in entries {
+            if let Some(code) = &e.bytecode {
+                refs.by_fingerprint.insert(bytecode::fingerprint(code), e.label.clone());
+                refs.by_code_hash.insert(alloy_primitives::keccak256(code), e.label.clone());
+            }
+            if let Some(fp) = e.fingerprint {
+                refs.by_fingerprint.insert(fp, e.label.clone());
+            }
+            if let Some(h) = e.code_hash {
+                refs.by_code_hash.insert(h, e.label.clone());
+            }
+            if e.bytecode.is_none() && e.fingerprint.is_none() && e.code_hash.is_none() {
+                return Err(AppError::Config(format!(
+                    "fingerprint references {path}: '{}' needs fingerprint, codeHash or bytecode",
+                    e.label
+                )));
+            }
+        }
+        Ok(refs)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_fingerprint.len() + self.by_code_hash.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 成员自身命中的标签：完全相同的代码优先于同模板
+    fn direct(&self, m: &Member) -> Option<(&str, MatchKind)> {
+        if let Some(l) = self.by_code_hash.get(&m.code_hash) {
+            return Some((l, MatchKind::CodeHash));
+        }
+        self.by_fingerprint.get(&m.fingerprint).map(|l| (l.as_str(), MatchKind::Fingerprint))
+    }
+}
+
+/// 新部署以何种方式命中带标签的簇
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// 代码哈希与参考完全相同
+    CodeHash,
+    /// 归一化指纹与参考相同
+    Fingerprint,
+    /// 簇内另一个成员的代码哈希命中过参考
+    Cluster,
+}
+
+impl MatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::CodeHash => "code-hash",
+            MatchKind::Fingerprint => "fingerprint",
+            MatchKind::Cluster => "cluster",
+        }
+    }
+}
+
+/// 把一个部署加入索引后的结果
+#[derive(Debug, Clone)]
+pub struct Observation {
+    /// 包括本次部署在内的簇大小
+    pub cluster_size: usize,
+    pub label: Option<(String, MatchKind)>,
+}
+
+#[derive(Default)]
+struct IndexState {
+    seen: HashSet<Address>,
+    sizes: HashMap<B256, usize>,
+    // 由代码哈希命中推出的簇标签，同模板的变体（immutable 不同）随之命中
+    cluster_labels: HashMap<B256, String>,
+}
+
+impl IndexState {
+    fn add(&mut self, refs: &References, m: &Member) -> Option<Observation> {
+        if !self.seen.insert(m.contract) {
+            return None;
+        }
+        let size = self.sizes.entry(m.fingerprint).or_default();
+        *size += 1;
+        let cluster_size = *size;
+        let label = match refs.direct(m) {
+            Some((l, kind)) => {
+                self.cluster_labels.entry(m.fingerprint).or_insert_with(|| l.to_string());
+                Some((l.to_string(), kind))
+            }
+            None => self.cluster_labels.get(&m.fingerprint).map(|l| (l.clone(), MatchKind::Cluster)),
+        };
+        Some(Observation { cluster_size, label })
+    }
+}
+
+/// 运行中的指纹索引：启动时回放已有文件，之后每个新部署追加一行
+pub struct FingerprintIndex {
+    path: Option<PathBuf>,
+    refs: References,
+    state: Mutex<IndexState>,
+}
+
+impl FingerprintIndex {
+    pub fn open(index_path: Option<&str>, references_path: Option<&str>) -> Result<Self> {
+        let refs = references_path.map(References::load).transpose()?.unwrap_or_default();
+        let mut state = IndexState::default();
+        if let Some(p) = index_path
+            && Path::new(p).exists()
+        {
+            for m in load_members(p)? {
+                state.add(&refs, &m);
+            }
+        }
+        Ok(Self { path: index_path.map(PathBuf::from), refs, state: Mutex::new(state) })
+    }
+
+    /// 记录一个部署；同一合约重复上报（交易回执与 trace 各报一次）时返回 None。
+    /// 写文件失败只打印错误，内存中的索引照常更新。
+    pub fn observe(&self, m: &Member) -> Option<Observation> {
+        let obs = self.state.lock().unwrap_or_else(|e| e.into_inner()).add(&self.refs, m);
+        if obs.is_some()
+            && let Some(path) = &self.path
+            && let Err(e) = append_member(path, m)
+        {
+            eprintln!("[fingerprint] append {}: {e}", path.display());
+        }
+        obs
+    }
+}
+
+fn append_member(path: &Path, m: &Member) -> Result<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut f = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(f, "{}", serde_json::to_string(m)?)?;
+    Ok(())
+}
+
+/// 读取索引文件；无法解析的行（例如进程中断留下的半行）跳过
+pub fn load_members(path: &str) -> Result<Vec<Member>> {
+    let data = std::fs::read_to_string(path)?;
+    let mut out = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(m) => out.push(m),
+            Err(e) => eprintln!("[fingerprint] skip {path}:{}: {e}", i + 1),
+        }
+    }
+    Ok(out)
+}
+
+/// 一个指纹簇的汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cluster {
+    pub fingerprint: B256,
+    pub label: Option<String>,
+    pub size: usize,
+    pub code_hashes: BTreeSet<B256>,
+    /// (部署者, 部署数)，按数量倒序；未知部署者不计
+    pub deployers: Vec<(Address, usize)>,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+    pub contracts: Vec<Address>,
+}
+
+/// 按指纹聚类，簇按大小倒序
+pub fn clusters(members: &[Member], refs: &References) -> Vec<Cluster> {
+    let mut state = IndexState::default();
+    let mut by_fp: BTreeMap<B256, Vec<&Member>> = BTreeMap::new();
+    for m in members {
+        if state.add(refs, m).is_some() {
+            by_fp.entry(m.fingerprint).or_default().push(m);
+        }
+    }
+    let mut out: Vec<Cluster> = by_fp
+        .into_iter()
+        .map(|(fp, ms)| {
+            let mut deployers: HashMap<Address, usize> = HashMap::new();
+            for d in ms.iter().filter_map(|m| m.deployer) {
+                *deployers.entry(d).or_default() += 1;
+            }
+            let mut deployers: Vec<_> = deployers.into_iter().collect();
+            deployers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            Cluster {
+                fingerprint: fp,
+                label: state.cluster_labels.get(&fp).cloned(),
+                size: ms.len(),
+                code_hashes: ms.iter().map(|m| m.code_hash).collect(),
+                deployers,
+                first_block: ms.iter().filter_map(|m| m.block_number).min(),
+                last_block: ms.iter().filter_map(|m| m.block_number).max(),
+                contracts: ms.iter().map(|m| m.contract).collect(),
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| b.size.cmp(&a.size).then(a.fingerprint.cmp(&b.fingerprint)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(contract: u8, deployer: u8, code_hash: u8, fingerprint: u8) -> Member {
+        Member {
+            contract: Address::repeat_byte(contract),
+            deployer: Some(Address::repeat_byte(deployer)),
+            block_number: Some(contract as u64),
+            code_hash: B256::repeat_byte(code_hash),
+            fingerprint: B256::repeat_byte(fingerprint),
+            code_size: 100,
+        }
+    }
+
+    #[test]
+    fn missing_references_file_is_empty() {
+        let refs = References::load("./data/does_not_exist_fingerprint_refs.json").unwrap();
+        assert!(refs.by_fingerprint.is_empty() && refs.by_code_hash.is_empty());
+        let refs = References::load("./data/fingerprint_refs.json").unwrap();
+        assert!(refs.by_fingerprint.is_empty() && refs.by_code_hash.is_empty());
+    }
+
+    #[test]
+    fn labels_spread_from_code_hash_to_cluster() {
+        let mut refs = References::default();
+        refs.by_code_hash.insert(B256::repeat_byte(0xc1), "drainer".to_string());
+        let mut state = IndexState::default();
+
+        // unlabeled variant first, then the known one, then another variant
+        let a = state.add(&refs, &member(1, 0xd1, 0xc0, 0xf1)).unwrap();
+        assert_eq!((a.cluster_size, a.label), (1, None));
+        let b = state.add(&refs, &member(2, 0xd1, 0xc1, 0xf1)).unwrap();
+        assert_eq!(b.label, Some(("drainer".to_string(), MatchKind::CodeHash)));
+        let c = state.add(&refs, &member(3, 0xd2, 0xc2, 0xf1)).unwrap();
+        assert_eq!((c.cluster_size, c.label), (3, Some(("drainer".to_string(), MatchKind::Cluster))));
+        // duplicate report of the same contract
+        assert!(state.add(&refs, &member(3, 0xd2, 0xc2, 0xf1)).is_none());
+
+        let ms = [member(1, 0xd1, 0xc0, 0xf1), member(2, 0xd1, 0xc1, 0xf1), member(3, 0xd2, 0xc2, 0xf1), member(4, 0xd3, 0xc3, 0xf2)];
+        let cs = clusters(&ms, &refs);
+        assert_eq!(cs.len(), 2);
+        assert_eq!(cs[0].size, 3);
+        assert_eq!(cs[0].label.as_deref(), Some("drainer"));
+        assert_eq!(cs[0].deployers[0], (Address::repeat_byte(0xd1), 2));
+        assert_eq!((cs[0].first_block, cs[0].last_block), (Some(1), Some(3)));
+        assert_eq!(cs[1].label, None);
+    }
+}
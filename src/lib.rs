@@ -1,5 +1,6 @@
 pub mod error;
 pub mod abi;
+pub mod abi_registry;
 pub mod actions;
 pub mod app;
 pub mod cli;
@@ -8,11 +9,17 @@ pub mod config;
 pub mod context;
 pub mod data_cmd;
 pub mod db;
+pub mod findings;
+pub mod fingerprints;
+pub mod pricing;
 pub mod provider;
+pub mod reload;
 pub mod runtime;
 pub mod throttle;
+pub mod token_meta;
 pub mod source;
 pub mod registry;
 pub mod factories;
 pub mod output;
+pub mod persist;
 pub use crate::actions::history_tx_scan;
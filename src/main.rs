@@ -4,7 +4,7 @@ use tracing_subscriber::EnvFilter;
 
 use evm_track::actions::history_tx_scan;
 use evm_track::cli::{Cli, Commands, DataWhichCmd};
-use evm_track::commands::{init_scan_cmd, track};
+use evm_track::commands::{findings_cmd, fingerprints_cmd, init_scan_cmd, track};
 use evm_track::config;
 use evm_track::data_cmd;
 use evm_track::error::{AppError, Result};
@@ -40,8 +40,31 @@ async fn main() -> Result<()> {
                 println!("wrote ABI to {}", args.output.display());
                 Ok(())
             }
+            DataWhichCmd::ImportSigs(args) => {
+                let kind = args.kind.as_deref().map(str::parse).transpose()?;
+                let paths = data_cmd::SigImportPaths {
+                    func: args.func_output.clone(),
+                    event: args.event_output.clone(),
+                    error: args.error_output.clone(),
+                };
+                let report = data_cmd::import_sigs(&args.inputs, kind, &paths)?;
+                for (label, s, path) in [
+                    ("functions", &report.functions, &paths.func),
+                    ("events", &report.events, &paths.event),
+                    ("errors", &report.errors, &paths.error),
+                ] {
+                    println!(
+                        "[import-sigs] {}: added={} collisions={} duplicates={} invalid={} mismatched={} -> {}",
+                        label, s.added, s.collisions, s.duplicates, s.invalid, s.mismatched, path.display()
+                    );
+                }
+                println!("[import-sigs] skipped={} unrecognized lines/entries", report.skipped);
+                Ok(())
+            }
         },
         Commands::InitScan(cmd) => init_scan_cmd::run(&cli, cmd).await,
+        Commands::Findings(cmd) => findings_cmd::run(&cli, cmd).await,
+        Commands::Fingerprints(cmd) => fingerprints_cmd::run(&cli, cmd),
         Commands::HistoryTxScan(cmd) => {
             let cfg_path = cmd.config.as_ref().ok_or_else(|| {
                 AppError::Config("--config is required for history-tx-scan".to_string())
@@ -0,0 +1,62 @@
+//! 本地 JSON 状态文件（代币元数据缓存、权限登记、代理升级历史）的读写
+
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::Result;
+
+/// 读取 JSON 文件；文件不存在时返回 `None`，读取或解析失败返回错误（由调用方决定是否忽略）
+pub fn read_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<Option<T>> {
+    match std::fs::read(path.as_ref()) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 以缩进格式写入 JSON，必要时创建目录。
+/// 先写同目录下的 `<文件名>.tmp` 再替换，避免进程中断留下半个文件
+pub fn write_json_atomic<T: Serialize + ?Sized>(path: impl AsRef<Path>, value: &T) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = tmp_path(path);
+    std::fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn round_trips_and_treats_missing_file_as_none() {
+        let dir = std::env::temp_dir().join(format!("evm-track-persist-{}", std::process::id()));
+        let path = dir.join("nested").join("state.json");
+        assert!(read_json::<BTreeMap<String, u64>>(&path).unwrap().is_none());
+
+        let value = BTreeMap::from([("a".to_string(), 1u64), ("b".to_string(), 2)]);
+        write_json_atomic(&path, &value).unwrap();
+        assert_eq!(read_json::<BTreeMap<String, u64>>(&path).unwrap(), Some(value));
+        assert!(!tmp_path(&path).exists());
+
+        std::fs::write(&path, b"{\"a\":").unwrap();
+        assert!(read_json::<BTreeMap<String, u64>>(&path).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
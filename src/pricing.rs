@@ -0,0 +1,506 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::throttle;
+use crate::token_meta::{self, TokenMetaService};
+use alloy_primitives::{Address, Bytes, U256, address};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types_eth::{BlockId, TransactionRequest, transaction::TransactionInput};
+use alloy_transport::BoxTransport;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+// 链上 USD 价格
+// - 稳定币按 1 USD 计价
+// - 配置了 Chainlink 喂价（USD 计价的 aggregator）的代币直接读取 latestRoundData
+// - 其余代币在 Uniswap V2 / PancakeSwap 工厂下查找与稳定币或 wrapped native 的交易对，
+//   按储备计算价格，经 wrapped native 路由时再乘以 native 的 USD 价格
+// - 多个交易对时取报价侧流动性最大的一个，低于 min-liquidity-usd 的池子忽略
+// - 价格按区块缓存，只保留最近 cache-blocks 个区块
+
+static ORACLE: OnceCell<Arc<PriceOracle>> = OnceCell::new();
+
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+const LATEST_ROUND_DATA_SELECTOR: [u8; 4] = [0xfe, 0xaf, 0x96, 0x8c];
+const GET_PAIR_SELECTOR: [u8; 4] = [0xe6, 0xa4, 0x39, 0x05];
+const GET_RESERVES_SELECTOR: [u8; 4] = [0x09, 0x02, 0xf1, 0xac];
+
+/// 配置文件中的 "pricing" 段；未填写的字段使用当前链的内置预设
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct PricingConfig {
+    /// WBNB / WETH 等 wrapped native 代币
+    #[serde(default)]
+    pub wrapped_native: Option<Address>,
+    /// native 代币的 Chainlink USD 喂价
+    #[serde(default)]
+    pub native_feed: Option<Address>,
+    /// 按 1 USD 计价的稳定币
+    #[serde(default)]
+    pub stablecoins: Vec<Address>,
+    /// Uniswap V2 兼容的工厂合约
+    #[serde(default)]
+    pub factories: Vec<Address>,
+    /// 代币 -> Chainlink USD 喂价
+    #[serde(default)]
+    pub feeds: HashMap<Address, Address>,
+    #[serde(default = "default_min_liquidity_usd")]
+    pub min_liquidity_usd: f64,
+    #[serde(default = "default_cache_blocks")]
+    pub cache_blocks: u64,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            wrapped_native: None,
+            native_feed: None,
+            stablecoins: Vec::new(),
+            factories: Vec::new(),
+            feeds: HashMap::new(),
+            min_liquidity_usd: default_min_liquidity_usd(),
+            cache_blocks: default_cache_blocks(),
+        }
+    }
+}
+
+fn default_min_liquidity_usd() -> f64 {
+    10_000.0
+}
+
+fn default_cache_blocks() -> u64 {
+    64
+}
+
+impl PricingConfig {
+    /// 内置预设：BSC 与以太坊主网
+    pub fn preset(chain_id: u64) -> Self {
+        let mut c = Self::default();
+        match chain_id {
+            1 => {
+                c.wrapped_native = Some(address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"));
+                c.native_feed = Some(address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"));
+                c.stablecoins = vec![
+                    address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"), // USDC
+                    address!("dAC17F958D2ee523a2206206994597C13D831ec7"), // USDT
+                    address!("6B175474E89094C44Da98b954EedeAC495271d0F"), // DAI
+                ];
+                c.factories = vec![address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f")];
+            }
+            56 => {
+                c.wrapped_native = Some(address!("bb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c"));
+                c.native_feed = Some(address!("0567F2323251f0Aab15c8dFb1967E4e8A7D42aeE"));
+                c.stablecoins = vec![
+                    address!("55d398326f99059fF775485246999027B3197955"), // USDT
+                    address!("8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d"), // USDC
+                    address!("e9e7CEA3DedcA5984780Bafc599bD69ADd087D56"), // BUSD
+                ];
+                c.factories = vec![address!("cA143Ce32Fe78f1f7019d7d551a6402fC5350c73")];
+            }
+            _ => {}
+        }
+        c
+    }
+
+    /// 用配置文件中填写的字段覆盖预设
+    fn merged_over(self, mut base: Self) -> Self {
+        if self.wrapped_native.is_some() {
+            base.wrapped_native = self.wrapped_native;
+        }
+        if self.native_feed.is_some() {
+            base.native_feed = self.native_feed;
+        }
+        if !self.stablecoins.is_empty() {
+            base.stablecoins = self.stablecoins;
+        }
+        if !self.factories.is_empty() {
+            base.factories = self.factories;
+        }
+        base.feeds.extend(self.feeds);
+        base.min_liquidity_usd = self.min_liquidity_usd;
+        base.cache_blocks = self.cache_blocks;
+        base
+    }
+}
+
+pub struct PriceOracle {
+    provider: RootProvider<BoxTransport>,
+    tokens: Arc<TokenMetaService>,
+    cfg: PricingConfig,
+    /// block -> token -> USD 价格（`None` 表示该区块无法定价）
+    prices: Mutex<BTreeMap<u64, HashMap<Address, Option<f64>>>>,
+    /// (factory, token, quote) -> pair；只缓存已存在的交易对
+    pairs: Mutex<HashMap<(Address, Address, Address), Address>>,
+    feed_decimals: Mutex<HashMap<Address, u8>>,
+}
+
+/// 交易对报价：以报价代币计的价格与报价侧储备
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PairQuote {
+    price: f64,
+    quote_reserve: f64,
+}
+
+impl PriceOracle {
+    pub fn new(
+        provider: RootProvider<BoxTransport>,
+        tokens: Arc<TokenMetaService>,
+        cfg: PricingConfig,
+    ) -> Self {
+        Self {
+            provider,
+            tokens,
+            cfg,
+            prices: Mutex::new(BTreeMap::new()),
+            pairs: Mutex::new(HashMap::new()),
+            feed_decimals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 代币在 `block`（`None` 为 latest）的 USD 价格
+    pub async fn price_usd(&self, token: Address, block: Option<u64>) -> Option<f64> {
+        if let Some(b) = block
+            && let Some(p) = self
+                .prices
+                .lock()
+                .unwrap()
+                .get(&b)
+                .and_then(|m| m.get(&token))
+        {
+            return *p;
+        }
+        let price = self.resolve_price(token, block).await;
+        debug!("[price] token={} block={:?} usd={:?}", token, block, price);
+        if let Some(b) = block {
+            let mut prices = self.prices.lock().unwrap();
+            prices.entry(b).or_default().insert(token, price);
+            let keep_from = b.saturating_sub(self.cfg.cache_blocks);
+            *prices = prices.split_off(&keep_from);
+        }
+        price
+    }
+
+    /// 代币原始数量的 USD 价值；decimals 未知或无法定价时为 `None`
+    pub async fn value_usd(&self, token: Address, raw: U256, block: Option<u64>) -> Option<f64> {
+        let decimals = self.tokens.get(token).await.decimals?;
+        let price = self.price_usd(token, block).await?;
+        Some(to_units(raw, decimals) * price)
+    }
+
+    /// native 代币（wei）的 USD 价值
+    pub async fn native_value_usd(&self, wei: U256, block: Option<u64>) -> Option<f64> {
+        let wrapped = self.cfg.wrapped_native?;
+        let price = self.price_usd(wrapped, block).await?;
+        Some(to_units(wei, 18) * price)
+    }
+
+    /// 有直接定价来源的代币：wrapped native 与稳定币
+    pub fn quote_tokens(&self) -> Vec<Address> {
+        self.cfg
+            .wrapped_native
+            .iter()
+            .chain(&self.cfg.stablecoins)
+            .copied()
+            .collect()
+    }
+
+    async fn resolve_price(&self, token: Address, block: Option<u64>) -> Option<f64> {
+        if self.cfg.stablecoins.contains(&token) {
+            return Some(1.0);
+        }
+        if let Some(&feed) = self.cfg.feeds.get(&token) {
+            return self.feed_price(feed, block).await;
+        }
+        let native = match self.cfg.wrapped_native {
+            Some(wrapped) if wrapped == token => return self.native_price(block).await,
+            // 经 price_usd 取 native 价格，以便同一区块内复用缓存
+            Some(wrapped) => Box::pin(self.price_usd(wrapped, block)).await,
+            None => None,
+        };
+        self.best_pair_price(token, native, block).await
+    }
+
+    /// wrapped native 的 USD 价格：优先 Chainlink，其次与稳定币的交易对
+    async fn native_price(&self, block: Option<u64>) -> Option<f64> {
+        let wrapped = self.cfg.wrapped_native?;
+        if let Some(feed) = self.cfg.native_feed
+            && let Some(p) = self.feed_price(feed, block).await
+        {
+            return Some(p);
+        }
+        self.best_pair_price(wrapped, None, block).await
+    }
+
+    /// 在所有工厂下比较与稳定币 / wrapped native 的交易对，取报价侧流动性最大的价格
+    async fn best_pair_price(
+        &self,
+        token: Address,
+        native_usd: Option<f64>,
+        block: Option<u64>,
+    ) -> Option<f64> {
+        let mut quotes: Vec<(Address, f64)> =
+            self.cfg.stablecoins.iter().map(|&s| (s, 1.0)).collect();
+        if let (Some(wrapped), Some(p)) = (self.cfg.wrapped_native, native_usd)
+            && wrapped != token
+        {
+            quotes.push((wrapped, p));
+        }
+        let mut found = vec![];
+        for &factory in &self.cfg.factories {
+            for &(quote, quote_usd) in &quotes {
+                if let Some(q) = self.pair_quote(factory, token, quote, block).await {
+                    found.push((q, quote_usd));
+                }
+            }
+        }
+        deepest_price(&found, self.cfg.min_liquidity_usd)
+    }
+
+    async fn pair_quote(
+        &self,
+        factory: Address,
+        token: Address,
+        quote: Address,
+        block: Option<u64>,
+    ) -> Option<PairQuote> {
+        let pair = self.get_pair(factory, token, quote).await?;
+        let data = self
+            .call(pair, GET_RESERVES_SELECTOR.to_vec(), block)
+            .await?;
+        let reserves = (word(&data, 0)?, word(&data, 1)?);
+        let dec_token = self.tokens.get(token).await.decimals?;
+        let dec_quote = self.tokens.get(quote).await.decimals?;
+        quote_from_reserves(token, quote, reserves, (dec_token, dec_quote))
+    }
+
+    async fn get_pair(&self, factory: Address, token: Address, quote: Address) -> Option<Address> {
+        let key = (factory, token, quote);
+        if let Some(p) = self.pairs.lock().unwrap().get(&key) {
+            return Some(*p);
+        }
+        let mut data = GET_PAIR_SELECTOR.to_vec();
+        data.extend_from_slice(token.into_word().as_slice());
+        data.extend_from_slice(quote.into_word().as_slice());
+        let out = self.call(factory, data, None).await?;
+        let pair = Address::from_word(out.get(..32)?.try_into().ok()?);
+        if pair.is_zero() {
+            return None;
+        }
+        self.pairs.lock().unwrap().insert(key, pair);
+        Some(pair)
+    }
+
+    /// Chainlink aggregator 的 latestRoundData().answer，按 aggregator 的 decimals 换算
+    async fn feed_price(&self, feed: Address, block: Option<u64>) -> Option<f64> {
+        let decimals = self.feed_decimals(feed).await?;
+        let data = self
+            .call(feed, LATEST_ROUND_DATA_SELECTOR.to_vec(), block)
+            .await?;
+        let answer = word(&data, 1)?;
+        // int256：负数或 0 视为无效
+        if answer.bit(255) || answer.is_zero() {
+            return None;
+        }
+        Some(to_units(answer, decimals))
+    }
+
+    async fn feed_decimals(&self, feed: Address) -> Option<u8> {
+        if let Some(d) = self.feed_decimals.lock().unwrap().get(&feed) {
+            return Some(*d);
+        }
+        let data = self.call(feed, DECIMALS_SELECTOR.to_vec(), None).await?;
+        let d = u8::try_from(word(&data, 0)?).ok()?;
+        self.feed_decimals.lock().unwrap().insert(feed, d);
+        Some(d)
+    }
+
+    async fn call(&self, to: Address, data: Vec<u8>, block: Option<u64>) -> Option<Bytes> {
+        let tx = TransactionRequest::default()
+            .to(to)
+            .input(TransactionInput::new(Bytes::from(data)));
+        throttle::acquire().await;
+        let res = match block {
+            Some(b) => self.provider.call(&tx).block(BlockId::number(b)).await,
+            None => self.provider.call(&tx).await,
+        };
+        match res {
+            Ok(out) if !out.is_empty() => Some(out),
+            Ok(_) => None,
+            Err(e) => {
+                debug!("[price] eth_call {} failed: {}", to, e);
+                None
+            }
+        }
+    }
+}
+
+/// 返回数据中第 `i` 个 32 字节字
+/// 由 getReserves 的 (reserve0, reserve1) 计算 `token` 以 `quote` 计的价格；
+/// 交易对按地址排序，较小的地址为 token0。`decimals` 为 (token, quote) 的精度
+fn quote_from_reserves(
+    token: Address,
+    quote: Address,
+    (r0, r1): (U256, U256),
+    (dec_token, dec_quote): (u8, u8),
+) -> Option<PairQuote> {
+    let (reserve_token, reserve_quote) = if token < quote { (r0, r1) } else { (r1, r0) };
+    let reserve_token = to_units(reserve_token, dec_token);
+    let quote_reserve = to_units(reserve_quote, dec_quote);
+    if reserve_token <= 0.0 {
+        return None;
+    }
+    Some(PairQuote {
+        price: quote_reserve / reserve_token,
+        quote_reserve,
+    })
+}
+
+/// 在 (报价, 报价代币 USD 价格) 中取报价侧流动性最大且不低于下限的 USD 价格
+fn deepest_price(quotes: &[(PairQuote, f64)], min_liquidity_usd: f64) -> Option<f64> {
+    let mut best: Option<(f64, f64)> = None;
+    for &(q, quote_usd) in quotes {
+        // 池子总价值约为报价侧的两倍
+        let liquidity = q.quote_reserve * quote_usd;
+        if liquidity * 2.0 < min_liquidity_usd {
+            continue;
+        }
+        if best.is_none_or(|(_, l)| liquidity > l) {
+            best = Some((q.price * quote_usd, liquidity));
+        }
+    }
+    best.map(|(p, _)| p)
+}
+
+fn word(data: &[u8], i: usize) -> Option<U256> {
+    let w: [u8; 32] = data.get(i * 32..(i + 1) * 32)?.try_into().ok()?;
+    Some(U256::from_be_bytes(w))
+}
+
+/// 原始数量按 decimals 换算为浮点数（用于 USD 估值，精度足够）
+pub fn to_units(raw: U256, decimals: u8) -> f64 {
+    let v: f64 = raw.to_string().parse().unwrap_or(0.0);
+    v / 10f64.powi(decimals as i32)
+}
+
+/// 按配置与当前链的预设初始化全局价格服务；需在 `token_meta::init_from_config` 之后、构建 Action 之前调用，重复调用无效
+pub async fn init_from_config(cfg: &Config, provider: &RootProvider<BoxTransport>) -> Result<()> {
+    if ORACLE.get().is_some() {
+        return Ok(());
+    }
+    throttle::acquire().await;
+    let chain_id = provider.get_chain_id().await?;
+    let pricing = cfg
+        .pricing
+        .clone()
+        .unwrap_or_default()
+        .merged_over(PricingConfig::preset(chain_id));
+    debug!(
+        "[price] chain={} wrapped={:?} stables={} factories={} feeds={}",
+        chain_id,
+        pricing.wrapped_native,
+        pricing.stablecoins.len(),
+        pricing.factories.len(),
+        pricing.feeds.len()
+    );
+    let oracle = PriceOracle::new(provider.clone(), token_meta::shared()?, pricing);
+    let _ = ORACLE.set(Arc::new(oracle));
+    Ok(())
+}
+
+pub fn global() -> Option<&'static Arc<PriceOracle>> {
+    ORACLE.get()
+}
+
+/// 全局价格服务；必须先调用 [`init_from_config`]，否则返回配置错误（不会静默退回没有价格来源的服务）
+pub fn shared() -> Result<Arc<PriceOracle>> {
+    ORACLE.get().cloned().ok_or_else(|| {
+        AppError::Config("price oracle is not initialized; call pricing::init_from_config at startup".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_overrides_chain_preset() {
+        let user: PricingConfig = serde_json::from_value(serde_json::json!({
+            "stablecoins": ["0x0000000000000000000000000000000000000001"],
+            "feeds": {
+                "0x0000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000003"
+            },
+            "min-liquidity-usd": 500.0
+        }))
+        .unwrap();
+        let merged = user.merged_over(PricingConfig::preset(56));
+        assert_eq!(
+            merged.wrapped_native,
+            Some(address!("bb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c"))
+        );
+        assert_eq!(merged.stablecoins, vec![Address::with_last_byte(1)]);
+        assert_eq!(merged.factories.len(), 1);
+        assert_eq!(
+            merged.feeds.get(&Address::with_last_byte(2)),
+            Some(&Address::with_last_byte(3))
+        );
+        assert_eq!(merged.min_liquidity_usd, 500.0);
+        assert_eq!(merged.cache_blocks, 64);
+
+        assert_eq!(to_units(U256::from(1_500_000u64), 6), 1.5);
+        let mut data = vec![0u8; 64];
+        data[63] = 7;
+        assert_eq!(word(&data, 1), Some(U256::from(7)));
+        assert_eq!(word(&data, 2), None);
+    }
+
+    fn units(v: u64, decimals: u8) -> U256 {
+        U256::from(v) * U256::from(10u64).pow(U256::from(decimals))
+    }
+
+    #[test]
+    fn quotes_from_reserves_in_both_token_orders() {
+        let (low, high) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        // 18 位精度的代币 1000 枚 / 6 位精度的稳定币 2000 枚：价格 2
+        let q = quote_from_reserves(low, high, (units(1000, 18), units(2000, 6)), (18, 6)).unwrap();
+        assert_eq!(q, PairQuote { price: 2.0, quote_reserve: 2000.0 });
+        // token 地址较大时是 token1，储备顺序对调
+        let q = quote_from_reserves(high, low, (units(2000, 6), units(1000, 18)), (18, 6)).unwrap();
+        assert_eq!(q, PairQuote { price: 2.0, quote_reserve: 2000.0 });
+        // 反过来给稳定币定价
+        let q = quote_from_reserves(high, low, (units(1000, 18), units(2000, 6)), (6, 18)).unwrap();
+        assert_eq!(q, PairQuote { price: 0.5, quote_reserve: 1000.0 });
+        assert_eq!(quote_from_reserves(low, high, (U256::ZERO, units(1, 6)), (18, 6)), None);
+    }
+
+    #[test]
+    fn picks_deepest_pool_above_liquidity_floor() {
+        let shallow = PairQuote { price: 3.0, quote_reserve: 100.0 };
+        let deep = PairQuote { price: 2.0, quote_reserve: 10_000.0 };
+        let native = PairQuote { price: 0.002, quote_reserve: 10.0 };
+        // native 报价侧 10 * 2000 = 20000 USD，比稳定币池更深
+        assert_eq!(deepest_price(&[(shallow, 1.0), (deep, 1.0), (native, 2000.0)], 0.0), Some(4.0));
+        assert_eq!(deepest_price(&[(shallow, 1.0), (deep, 1.0)], 0.0), Some(2.0));
+        // 池子总价值按报价侧两倍估算：200 < 1000 被过滤
+        assert_eq!(deepest_price(&[(shallow, 1.0)], 1000.0), None);
+        assert_eq!(deepest_price(&[(shallow, 1.0)], 200.0), Some(3.0));
+        assert_eq!(deepest_price(&[], 0.0), None);
+    }
+
+    #[tokio::test]
+    async fn prunes_price_cache_to_recent_blocks() {
+        let provider = crate::provider::connect_auto("http://127.0.0.1:1").await.unwrap();
+        let tokens = Arc::new(TokenMetaService::new(provider.clone()));
+        let stable = Address::with_last_byte(1);
+        let cfg = PricingConfig { stablecoins: vec![stable], cache_blocks: 10, ..Default::default() };
+        let oracle = PriceOracle::new(provider, tokens, cfg);
+        for b in [100, 105, 111] {
+            assert_eq!(oracle.price_usd(stable, Some(b)).await, Some(1.0));
+        }
+        assert_eq!(oracle.prices.lock().unwrap().keys().copied().collect::<Vec<_>>(), [105, 111]);
+        // latest 不进缓存
+        assert_eq!(oracle.price_usd(stable, None).await, Some(1.0));
+        assert_eq!(oracle.prices.lock().unwrap().len(), 2);
+    }
+}
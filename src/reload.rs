@@ -0,0 +1,249 @@
+//! 配置与签名文件热重载。
+//!
+//! 按修改时间轮询配置文件和 event/func/error 签名文件：
+//! - 签名文件变化：重新读取并原子替换 [`abi::sigs`] 快照；
+//! - 配置文件变化：重新校验配置、重载签名，并通过注册表重建 Action 后替换到同一个 `ActionSet` 中。
+//!
+//! Provider 连接与订阅循环不受影响，因此不会丢失当前处理到的区块位置。
+//! 全局服务的配置段（`contract-abi`、`token-metadata`、`pricing`、`findings`）只在启动时读取，
+//! 重载时若与启动时不同会提示需要重启。
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use alloy_primitives::Address;
+use alloy_provider::RootProvider;
+use alloy_transport::BoxTransport;
+use tracing::warn;
+
+use crate::{abi, actions::ActionSet, app, cli::Cli, config};
+
+/// 轮询间隔
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 由全局服务在启动时读取一次的配置段，修改后需要重启
+const RESTART_SECTIONS: &[&str] = &["contract-abi", "token-metadata", "pricing", "findings"];
+
+/// 配置文件的原始内容（不做校验），用于比较只在启动时生效的配置段
+fn raw_config(path: &Path) -> Option<serde_json::Value> {
+    config::ConfigLoader::load_config(path).ok()
+}
+
+/// 与启动时相比发生变化的 [`RESTART_SECTIONS`]
+fn changed_restart_sections(startup: &serde_json::Value, current: &serde_json::Value) -> Vec<&'static str> {
+    RESTART_SECTIONS
+        .iter()
+        .copied()
+        .filter(|k| startup.get(k) != current.get(k))
+        .collect()
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn sig_paths() -> Vec<PathBuf> {
+    vec![
+        PathBuf::from(abi::event_sigs_path()),
+        PathBuf::from(abi::func_sigs_path()),
+        PathBuf::from(abi::error_sigs_path()),
+    ]
+}
+
+/// 记录一组文件的修改时间，`changed` 返回自上次检查以来是否有变化（含新建/删除）
+struct Watched {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watched {
+    fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            files: paths
+                .into_iter()
+                .map(|p| {
+                    let t = mtime(&p);
+                    (p, t)
+                })
+                .collect(),
+        }
+    }
+
+    fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (p, last) in &mut self.files {
+            let t = mtime(p);
+            if t != *last {
+                *last = t;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// 监视配置与签名文件，变化时重新加载并替换 `set` 中的 Action。
+/// `addrs` 为启动时订阅使用的地址过滤；配置中的地址变化需要重启才会影响订阅过滤。
+/// 该 future 不会结束，通常与运行时循环一起 `select!`。
+pub async fn watch(
+    cfg_path: &Path,
+    provider: &RootProvider<BoxTransport>,
+    cli: &Cli,
+    set: &ActionSet,
+    addrs: &[Address],
+) {
+    let mut cfg_file = Watched::new(vec![cfg_path.to_path_buf()]);
+    let mut sig_files = Watched::new(sig_paths());
+    // 监视开始时的文件内容即启动时使用的配置
+    let startup = raw_config(cfg_path);
+    let mut tick = tokio::time::interval(POLL_INTERVAL);
+    tick.tick().await;
+    loop {
+        tick.tick().await;
+        // 两者都要检查，以刷新记录的修改时间
+        let cfg_changed = cfg_file.changed();
+        let sigs_changed = sig_files.changed();
+        if cfg_changed {
+            reload_config(cfg_path, startup.as_ref(), provider, cli, set, addrs).await;
+        } else if sigs_changed {
+            reload_sigs();
+        }
+    }
+}
+
+fn reload_sigs() {
+    let (sigs, errs) = abi::reload_sigs();
+    for e in &errs {
+        warn!("reload signatures: {}; keeping previous entries", e);
+    }
+    println!(
+        "[reload] signatures events={} funcs={} errors={}",
+        sigs.events.len(),
+        sigs.funcs.len(),
+        sigs.errors.len()
+    );
+}
+
+async fn reload_config(
+    cfg_path: &Path,
+    startup: Option<&serde_json::Value>,
+    provider: &RootProvider<BoxTransport>,
+    cli: &Cli,
+    set: &ActionSet,
+    addrs: &[Address],
+) {
+    // 校验失败（例如文件写到一半）时保留当前配置，等待下一次修改
+    let cfg = match config::load_and_validate_config(cfg_path) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!(
+                "reload config {}: {}; keeping current config",
+                cfg_path.display(),
+                e
+            );
+            return;
+        }
+    };
+    if cli.event_sigs.is_none()
+        && let Some(p) = &cfg.event_sigs_path
+        && *p != abi::event_sigs_path()
+    {
+        warn!("event-sigs-path changed to {}; restart to apply", p);
+    }
+    if cli.func_sigs.is_none()
+        && let Some(p) = &cfg.func_sigs_path
+        && *p != abi::func_sigs_path()
+    {
+        warn!("func-sigs-path changed to {}; restart to apply", p);
+    }
+    match config::collect_enabled_addresses(&cfg) {
+        Ok(new_addrs) if !same_addresses(addrs, &new_addrs) => warn!(
+            "monitored addresses changed ({} -> {}); subscription filter is kept until restart",
+            addrs.len(),
+            new_addrs.len()
+        ),
+        _ => {}
+    }
+    if let (Some(startup), Some(current)) = (startup, raw_config(cfg_path)) {
+        for section in changed_restart_sections(startup, &current) {
+            warn!("config section '{}' changed; restart to apply", section);
+        }
+    }
+    reload_sigs();
+
+    match app::build_actionset_v2(provider, &cfg, cli).await {
+        Ok(new_set) => {
+            let n = set.replace(new_set);
+            println!("[reload] config={} actions={}", cfg_path.display(), n);
+        }
+        Err(e) => warn!("rebuild actions failed: {}; keeping current actions", e),
+    }
+}
+
+fn same_addresses(a: &[Address], b: &[Address]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    a.dedup();
+    b.sort();
+    b.dedup();
+    a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::jsonlog::JsonLogAction;
+    use crate::cli::Parser;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("evm-track-reload-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn detects_mtime_changes_creation_and_removal() {
+        let path = temp_path("watched.json");
+        let _ = std::fs::remove_file(&path);
+        let mut w = Watched::new(vec![path.clone()]);
+        assert!(!w.changed());
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(w.changed());
+        assert!(!w.changed());
+
+        let f = std::fs::File::options().write(true).open(&path).unwrap();
+        f.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        drop(f);
+        assert!(w.changed());
+        assert!(!w.changed());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(w.changed());
+    }
+
+    #[test]
+    fn reports_changed_restart_sections() {
+        let startup = json!({ "rpcurl": "http://a", "pricing": { "feeds": {} }, "findings": { "database-url": "x" } });
+        let current = json!({ "rpcurl": "http://b", "pricing": { "feeds": { "a": 1 } }, "token-metadata": { "persist": true }, "findings": { "database-url": "x" } });
+        assert_eq!(changed_restart_sections(&startup, &current), ["token-metadata", "pricing"]);
+        assert!(changed_restart_sections(&startup, &startup).is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_config_keeps_current_actions() {
+        let path = temp_path("config.json");
+        // half-written file
+        std::fs::write(&path, r#"{ "rpcurl": "http://127.0.0.1:1", "actions": {"#).unwrap();
+        let provider = crate::provider::connect_auto("http://127.0.0.1:1").await.unwrap();
+        let cli = Cli::parse_from(["evm-track", "fingerprints"]);
+        let mut set = ActionSet::new();
+        set.add(JsonLogAction);
+        set.add(JsonLogAction);
+
+        reload_config(&path, None, &provider, &cli, &set, &[]).await;
+        assert_eq!(set.len(), 2);
+        let _ = std::fs::remove_file(&path);
+    }
+}
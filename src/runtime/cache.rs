@@ -1,10 +1,12 @@
 use crate::{
     abi,
-    actions::{ActionSet, BlockRecord, EventRecord, TxRecord, ContractCreationRecord},
+    abi_registry,
+    actions::{ActionSet, BlockRecord, EventRecord, TxFailure, TxRecord, ContractCreationRecord, CreateKind},
+    runtime::{creations, revert},
     throttle,
     error::Result,
 };
-use alloy_primitives::{hex, Address, B256};
+use alloy_primitives::{hex, keccak256, Address, B256};
 use alloy_provider::{Provider, RootProvider};
 use alloy_rpc_types_eth::{Filter, BlockTransactionsKind, TransactionTrait};
 use alloy_transport::BoxTransport;
@@ -16,6 +18,8 @@ use std::{sync::Arc, collections::{HashMap, HashSet}};
 pub struct CachedTxData {
     pub transaction: alloy_rpc_types_eth::Transaction,
     pub receipt: Option<alloy_rpc_types_eth::TransactionReceipt>,
+    /// 失败交易重放得到的 revert 信息；每笔交易只重放一次
+    pub failure: Option<TxFailure>,
 }
 
 /// 交易缓存管理器
@@ -42,10 +46,12 @@ impl TxCache {
                 if let Some(tx) = provider.get_transaction_by_hash(tx_hash).await? {
                     throttle::acquire().await;
                     let receipt = provider.get_transaction_receipt(tx_hash).await.ok().flatten();
-                    
+                    let failure = revert::resolve_failure(provider, &tx, receipt.as_ref()).await;
+
                     self.cache.insert(tx_hash, CachedTxData {
                         transaction: tx,
                         receipt,
+                        failure,
                     });
                 }
             }
@@ -72,9 +78,9 @@ pub async fn process_block_unified(
     actions: &Option<Arc<ActionSet>>,
     process_events: bool,
     process_deployments: bool,
+    trace_creations: bool,
 ) -> Result<()> {
-    let events = abi::load_event_sigs_default().unwrap_or_default();
-    let funcs = abi::load_func_sigs_default().unwrap_or_default();
+    let sigs = abi::sigs();
 
     // 1. 收集所有需要的交易哈希
     let mut required_tx_hashes = HashSet::new();
@@ -124,13 +130,21 @@ pub async fn process_block_unified(
     
     // 4. 处理事件（使用缓存的数据）
     if process_events {
-        process_events_with_cache(&logs, &tx_cache, actions, &events, &funcs);
+        process_events_with_cache(&logs, &tx_cache, actions, &sigs.events, &sigs.funcs);
     }
     
     // 5. 处理合约创建（使用缓存的数据）
     if process_deployments {
         process_deployments_with_cache(&block, block_number, &tx_cache, actions);
     }
+
+    // 6. 工厂合约 / CREATE2 部署器在交易内部创建的合约（需要 trace 支持）
+    if process_deployments && trace_creations {
+        match creations::trace_block_creations(provider, block_number).await {
+            Ok(list) => process_internal_creations(&list, &block, block_number, actions),
+            Err(e) => tracing::warn!("trace creations for block {} failed: {}", block_number, e),
+        }
+    }
     
     Ok(())
 }
@@ -147,7 +161,7 @@ fn process_events_with_cache(
         let topic0 = v.topic0().cloned().unwrap_or(B256::ZERO);
         let topic0_hex = format!("0x{}", hex::encode(topic0));
         let (name, fields) = if let Some((nm, fs)) =
-            abi::try_decode_event(&topic0_hex, v.topics(), v.data().data.as_ref(), events)
+            abi_registry::try_decode_event(v.address(), &topic0_hex, v.topics(), v.data().data.as_ref(), events)
         {
             (Some(nm), fs)
         } else {
@@ -174,7 +188,7 @@ fn process_events_with_cache(
         // 处理关联的交易（使用缓存）
         if let Some(txh) = v.transaction_hash {
             if let Some(tx_data) = tx_cache.get(&txh) {
-                process_transaction(&tx_data.transaction, &tx_data.receipt, actions, funcs);
+                process_transaction(tx_data, actions, funcs);
             }
         }
     }
@@ -206,6 +220,10 @@ fn process_deployments_with_cache(
                                     } else { 
                                         Some(tx.input().to_vec()) 
                                     },
+                                    creator: None,
+                                    kind: CreateKind::Create,
+                                    salt: None,
+                                    init_code_hash: Some(keccak256(tx.input())),
                                 };
                                 
                                 println!(
@@ -230,19 +248,68 @@ fn process_deployments_with_cache(
     }
 }
 
+/// 处理交易内部创建的合约
+fn process_internal_creations(
+    list: &[creations::InternalCreation],
+    block: &Option<alloy_rpc_types_eth::Block>,
+    block_number: u64,
+    actions: &Option<Arc<ActionSet>>,
+) {
+    // tx hash -> 交易发起者（EOA）
+    let senders: HashMap<B256, Address> = block
+        .as_ref()
+        .and_then(|b| b.transactions.as_transactions())
+        .map(|txs| txs.iter().map(|tx| (tx.tx_hash(), tx.from)).collect())
+        .unwrap_or_default();
+
+    for c in list {
+        let Some(tx_hash) = c.tx_hash else { continue };
+        let deployer = senders.get(&tx_hash).copied().unwrap_or(c.creator);
+        let record = ContractCreationRecord {
+            tx_hash,
+            contract_address: c.contract_address,
+            deployer,
+            block_number,
+            tx_index: c.tx_index.unwrap_or(0),
+            gas_used: c.gas_used,
+            constructor_args: if c.init_code.is_empty() { None } else { Some(c.init_code.clone()) },
+            creator: Some(c.creator),
+            kind: c.kind,
+            salt: c.salt,
+            init_code_hash: Some(c.init_code_hash),
+        };
+
+        println!(
+            "[deployment] contract={} deployer={} creator={} kind={} salt={:?} tx={} block={}",
+            c.contract_address,
+            deployer,
+            c.creator,
+            c.kind.as_str(),
+            c.salt,
+            tx_hash,
+            block_number
+        );
+
+        if let Some(a) = actions {
+            a.on_contract_creation(&record);
+        }
+    }
+}
+
 /// 处理单个交易
 fn process_transaction(
-    tx: &alloy_rpc_types_eth::Transaction,
-    receipt: &Option<alloy_rpc_types_eth::TransactionReceipt>,
+    tx_data: &CachedTxData,
     actions: &Option<Arc<ActionSet>>,
     funcs: &abi::FuncSigMap,
 ) {
+    let tx = &tx_data.transaction;
+    let receipt = &tx_data.receipt;
     let input = tx.input().as_ref();
     if input.len() >= 4 {
         let sel = &input[0..4];
         let sel_hex = format!("0x{}", hex::encode(sel));
         let (fname, args) = if let Some((f, a)) =
-            abi::try_decode_function(&sel_hex, input, funcs)
+            abi_registry::try_decode_function(tx.to(), &sel_hex, input, funcs)
         {
             (Some(f), a)
         } else {
@@ -307,6 +374,7 @@ fn process_transaction(
             tx_index,
             contract_address,
             receipt_logs,
+            failure: tx_data.failure.clone(),
         };
         
         if let Some(a) = actions {
@@ -314,3 +382,144 @@ fn process_transaction(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::Action;
+    use crate::provider;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const TX_HASH: &str = "0x00000000000000000000000000000000000000000000000000000000000000a1";
+
+    /// 本地 JSON-RPC 替身：一笔 status=0 的交易，eth_call 重放时回滚 `Error("not owner")`
+    async fn stand_in_node() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let common = json!({
+            "blockHash": "0x00000000000000000000000000000000000000000000000000000000000000b1",
+            "blockNumber": "0x10",
+            "transactionIndex": "0x0",
+            "from": "0x00000000000000000000000000000000000000f1",
+            "to": "0x00000000000000000000000000000000000000c1",
+        });
+        let mut tx = json!({
+            "hash": TX_HASH,
+            "nonce": "0x0",
+            "value": "0x0",
+            "gasPrice": "0x1",
+            "gas": "0x5208",
+            "input": "0xa9059cbb",
+            "v": "0x1b",
+            "r": "0x1",
+            "s": "0x1",
+            "type": "0x0",
+        });
+        let mut receipt = json!({
+            "transactionHash": TX_HASH,
+            "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0x1",
+            "contractAddress": null,
+            "logs": [],
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "status": "0x0",
+            "type": "0x0",
+        });
+        for v in [&mut tx, &mut receipt] {
+            v.as_object_mut().unwrap().extend(common.as_object().unwrap().clone());
+        }
+        // Error(string) "not owner"
+        let revert = format!(
+            "0x08c379a0{:064x}{:064x}{:0<64}",
+            0x20,
+            9,
+            hex::encode("not owner")
+        );
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else { return };
+                let (tx, receipt, revert) = (tx.clone(), receipt.clone(), revert.clone());
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        // 同一连接上可能有多个请求（keep-alive）：按 Content-Length 切分
+                        let request = loop {
+                            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                                let head = String::from_utf8_lossy(&buf[..end]).to_ascii_lowercase();
+                                let len: usize = head
+                                    .lines()
+                                    .find_map(|l| l.strip_prefix("content-length:"))
+                                    .and_then(|v| v.trim().parse().ok())
+                                    .unwrap_or(0);
+                                if buf.len() >= end + 4 + len {
+                                    let body = buf[end + 4..end + 4 + len].to_vec();
+                                    buf.drain(..end + 4 + len);
+                                    break body;
+                                }
+                            }
+                            match sock.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                        };
+                        let req: Value = serde_json::from_slice(&request).unwrap();
+                        let mut resp = json!({ "jsonrpc": "2.0", "id": req["id"] });
+                        match req["method"].as_str().unwrap_or_default() {
+                            "eth_getTransactionByHash" => resp["result"] = tx.clone(),
+                            "eth_getTransactionReceipt" => resp["result"] = receipt.clone(),
+                            "eth_call" => {
+                                resp["error"] = json!({ "code": 3, "message": "execution reverted: not owner", "data": revert })
+                            }
+                            _ => resp["error"] = json!({ "code": -32601, "message": "method not found" }),
+                        }
+                        let body = resp.to_string();
+                        let out = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        if sock.write_all(out.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        format!("http://127.0.0.1:{port}")
+    }
+
+    struct Recorder(Arc<Mutex<Vec<TxRecord>>>);
+
+    impl Action for Recorder {
+        fn on_tx(&self, t: &TxRecord) -> Result<()> {
+            self.0.lock().unwrap().push(t.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_transactions_carry_revert_reason() {
+        let provider = provider::connect_auto(&stand_in_node().await).await.unwrap();
+        let hash: B256 = TX_HASH.parse().unwrap();
+        let mut cache = TxCache::new();
+        cache.fetch_transactions(&provider, &HashSet::from([hash])).await.unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut set = ActionSet::new();
+        set.add(Recorder(seen.clone()));
+        let actions = Some(Arc::new(set));
+        process_transaction(cache.get(&hash).unwrap(), &actions, &abi::FuncSigMap::new());
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].status, Some(0));
+        let failure = seen[0].failure.as_ref().expect("failure resolved");
+        assert_eq!(failure.source, "eth_call");
+        assert_eq!(failure.reason, abi::RevertReason::Error("not owner".to_string()));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{actions::CreateKind, error::Result, throttle};
+use alloy_primitives::{Address, B256, keccak256};
+use alloy_provider::{
+    RootProvider,
+    ext::{DebugApi, TraceApi},
+};
+use alloy_rpc_types::trace::{
+    common::TraceResult,
+    geth::{
+        CallConfig, CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
+        GethDebugTracingOptions, GethTrace,
+    },
+    parity::{Action as ParityAction, CreationMethod, LocalizedTransactionTrace, TraceOutput},
+};
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_transport::BoxTransport;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// 交易内部（工厂合约 / CREATE2 部署器）创建的合约
+#[derive(Debug, Clone)]
+pub struct InternalCreation {
+    pub tx_hash: Option<B256>,
+    pub tx_index: Option<u64>,
+    /// 执行 CREATE/CREATE2 的合约
+    pub creator: Address,
+    pub contract_address: Address,
+    pub kind: CreateKind,
+    pub init_code: Vec<u8>,
+    pub init_code_hash: B256,
+    pub salt: Option<B256>,
+    pub gas_used: Option<u64>,
+}
+
+/// 获取区块内所有内部合约创建。
+/// 优先使用 Parity/Erigon 的 `trace_block`，失败时退回 geth `debug_traceBlockByNumber` + callTracer。
+/// 交易顶层的 CREATE 不在此返回（由回执的 `contractAddress` 覆盖）。
+pub async fn trace_block_creations(
+    provider: &RootProvider<BoxTransport>,
+    block_number: u64,
+) -> Result<Vec<InternalCreation>> {
+    throttle::acquire().await;
+    match provider.trace_block(block_number.into()).await {
+        Ok(traces) => return Ok(collect_parity_creations(&traces)),
+        Err(e) => debug!(
+            "trace_block {} failed: {}; trying debug_traceBlockByNumber",
+            block_number, e
+        ),
+    }
+
+    let options = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugTracerType::BuiltInTracer(
+            GethDebugBuiltInTracerType::CallTracer,
+        ))
+        .with_call_config(CallConfig::default());
+    throttle::acquire().await;
+    let results = provider
+        .debug_trace_block_by_number(BlockNumberOrTag::Number(block_number), options)
+        .await?;
+
+    let mut out = Vec::new();
+    for (i, r) in results.into_iter().enumerate() {
+        if let TraceResult::Success {
+            result: GethTrace::CallTracer(frame),
+            tx_hash,
+        } = r
+        {
+            collect_geth_creations(&frame, tx_hash, Some(i as u64), &mut out);
+        }
+    }
+    Ok(out)
+}
+
+/// 从 `trace_block` 结果中提取内部创建（跳过失败帧及其子帧）
+pub fn collect_parity_creations(traces: &[LocalizedTransactionTrace]) -> Vec<InternalCreation> {
+    let mut by_tx: HashMap<Option<B256>, Vec<&LocalizedTransactionTrace>> = HashMap::new();
+    for t in traces {
+        by_tx.entry(t.transaction_hash).or_default().push(t);
+    }
+
+    let mut out = Vec::new();
+    for (tx_hash, list) in by_tx {
+        let failed: Vec<&[usize]> = list
+            .iter()
+            .filter(|t| t.trace.error.is_some())
+            .map(|t| t.trace.trace_address.as_slice())
+            .collect();
+        for t in &list {
+            let addr_path = &t.trace.trace_address;
+            if addr_path.is_empty() {
+                continue;
+            }
+            if failed.iter().any(|f| addr_path.starts_with(f)) {
+                continue;
+            }
+            let (ParityAction::Create(create), Some(TraceOutput::Create(output))) =
+                (&t.trace.action, &t.trace.result)
+            else {
+                continue;
+            };
+            let parent_path = &addr_path[..addr_path.len() - 1];
+            let parent_input = list
+                .iter()
+                .find(|p| p.trace.trace_address.as_slice() == parent_path)
+                .map(|p| match &p.trace.action {
+                    ParityAction::Call(c) => c.input.as_ref(),
+                    ParityAction::Create(c) => c.init.as_ref(),
+                    _ => &[][..],
+                })
+                .unwrap_or(&[]);
+            let init_code_hash = keccak256(&create.init);
+            let salt =
+                derive_create2_salt(create.from, init_code_hash, output.address, parent_input);
+            let kind = if create.creation_method == CreationMethod::Create2 || salt.is_some() {
+                CreateKind::Create2
+            } else {
+                CreateKind::Create
+            };
+            out.push(InternalCreation {
+                tx_hash,
+                tx_index: t.transaction_position,
+                creator: create.from,
+                contract_address: output.address,
+                kind,
+                init_code: create.init.to_vec(),
+                init_code_hash,
+                salt,
+                gas_used: Some(output.gas_used),
+            });
+        }
+    }
+    out.sort_by_key(|c| c.tx_index);
+    out
+}
+
+/// 递归遍历 callTracer 帧，收集非顶层的 CREATE/CREATE2
+pub fn collect_geth_creations(
+    root: &CallFrame,
+    tx_hash: Option<B256>,
+    tx_index: Option<u64>,
+    out: &mut Vec<InternalCreation>,
+) {
+    if root.error.is_some() {
+        return;
+    }
+    for child in &root.calls {
+        walk_geth_frame(child, root, tx_hash, tx_index, out);
+    }
+}
+
+fn walk_geth_frame(
+    frame: &CallFrame,
+    parent: &CallFrame,
+    tx_hash: Option<B256>,
+    tx_index: Option<u64>,
+    out: &mut Vec<InternalCreation>,
+) {
+    // 失败帧中的状态变更会被回滚，子帧一并忽略
+    if frame.error.is_some() {
+        return;
+    }
+    let typ = frame.typ.to_ascii_uppercase();
+    if (typ == "CREATE" || typ == "CREATE2")
+        && let Some(created) = frame.to
+    {
+        let init_code_hash = keccak256(&frame.input);
+        let salt = derive_create2_salt(frame.from, init_code_hash, created, &parent.input);
+        let kind = if typ == "CREATE2" || salt.is_some() {
+            CreateKind::Create2
+        } else {
+            CreateKind::Create
+        };
+        out.push(InternalCreation {
+            tx_hash,
+            tx_index,
+            creator: frame.from,
+            contract_address: created,
+            kind,
+            init_code: frame.input.to_vec(),
+            init_code_hash,
+            salt,
+            gas_used: Some(frame.gas_used.saturating_to::<u64>()),
+        });
+    }
+    for child in &frame.calls {
+        walk_geth_frame(child, frame, tx_hash, tx_index, out);
+    }
+}
+
+/// 尝试从父调用的 calldata 中还原 CREATE2 salt。
+/// 工厂合约通常把 salt 作为参数传入；确定性部署代理（0x4e59…956c）则直接以 salt 开头，
+/// 因此分别按 0 与 4（跳过 selector）对齐逐个 32 字节字校验地址推导结果。
+pub fn derive_create2_salt(
+    creator: Address,
+    init_code_hash: B256,
+    created: Address,
+    parent_input: &[u8],
+) -> Option<B256> {
+    for start in [0usize, 4] {
+        let mut off = start;
+        while off + 32 <= parent_input.len() {
+            let salt = B256::from_slice(&parent_input[off..off + 32]);
+            if creator.create2(salt, init_code_hash) == created {
+                return Some(salt);
+            }
+            off += 32;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn derives_salt_from_factory_calldata() {
+        let factory = address!("4e59b44847b379578588920ca78fbf26c0b4956c");
+        let init_code = hex::decode("6080604052348015600f57600080fd5b50").unwrap();
+        let init_code_hash = keccak256(&init_code);
+        let salt = b256!("00000000000000000000000000000000000000000000000000000000000000aa");
+        let created = factory.create2(salt, init_code_hash);
+
+        // deterministic deployer: salt ++ init code
+        let mut raw = salt.to_vec();
+        raw.extend_from_slice(&init_code);
+        assert_eq!(
+            derive_create2_salt(factory, init_code_hash, created, &raw),
+            Some(salt)
+        );
+
+        // selector + (bytes code, bytes32 salt)
+        let mut abi_call = vec![0x12, 0x34, 0x56, 0x78];
+        abi_call.extend_from_slice(&[0u8; 32]);
+        abi_call.extend_from_slice(salt.as_slice());
+        assert_eq!(
+            derive_create2_salt(factory, init_code_hash, created, &abi_call),
+            Some(salt)
+        );
+
+        assert_eq!(
+            derive_create2_salt(factory, init_code_hash, created, &[0u8; 36]),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_reverted_geth_frames() {
+        let factory = address!("00000000000000000000000000000000000000f1");
+        let child = |to: u8, error: Option<&str>| CallFrame {
+            from: factory,
+            to: Some(Address::with_last_byte(to)),
+            input: vec![0x60, 0x00].into(),
+            error: error.map(|s| s.to_string()),
+            typ: "CREATE".to_string(),
+            ..Default::default()
+        };
+        let root = CallFrame {
+            to: Some(factory),
+            typ: "CALL".to_string(),
+            calls: vec![child(1, None), child(2, Some("execution reverted"))],
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        collect_geth_creations(&root, None, Some(0), &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].contract_address, Address::with_last_byte(1));
+        assert_eq!(out[0].kind, CreateKind::Create);
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
     abi,
     actions::{ActionSet, BlockRecord},
     cli::RangeFlags,
-    runtime::public,
+    runtime::{public, revert},
 };
 use alloy_rpc_types_eth::TransactionTrait;
 use std::sync::Arc;
@@ -22,7 +22,6 @@ pub async fn run_events(
     range: &RangeFlags,
     actions: Option<Arc<ActionSet>>,
 ) -> Result<()> {
-    let events = abi::load_event_sigs_default().unwrap_or_default();
     let from = range.from_block;
     let to = range.to_block.unwrap_or_else(|| from);
     let step = range.step_blocks.max(1);
@@ -35,8 +34,10 @@ pub async fn run_events(
             .to_block(end);
         throttle::acquire().await;
         let logs = provider.get_logs(&filter).await?;
+        // 每批取一次签名快照，热重载后下一批即生效
+        let sigs = abi::sigs();
         for v in logs {
-            let _er = public::process_log(&v, &events, &actions);
+            let _er = public::process_log(&v, &sigs.events, &actions);
         }
         cur = end.saturating_add(1);
     }
@@ -49,8 +50,6 @@ pub async fn run_blocks(
     range: &RangeFlags,
     actions: Option<Arc<ActionSet>>,
 ) -> Result<()> {
-    let events = abi::load_event_sigs_default().unwrap_or_default();
-    let funcs = abi::load_func_sigs_default().unwrap_or_default();
     let from = range.from_block;
     let to = range.to_block.unwrap_or_else(|| from);
     if addrs.is_empty() {
@@ -76,12 +75,12 @@ pub async fn run_blocks(
                 let tx_opt = match provider.get_transaction_by_hash(txh).await { Ok(v) => v, Err(e) => { warn!("get_transaction_by_hash {:?} error: {}; skipping tx", txh, e); None } };
                 if let Some(tx) = tx_opt {
                     let input = tx.input().as_ref();
-                    let (fname, args, input_selector) = public::decode_transaction_function(input, &funcs);
+                    let (fname, args, input_selector) = public::decode_transaction_function(tx.to(), input, &abi::sigs().funcs);
                     throttle::acquire().await;
                     let receipt = provider.get_transaction_receipt(txh).await.ok().flatten();
                     
                     // 使用公共函数创建 TxRecord
-                    let tr = public::create_tx_record_from_standard_tx(
+                    let mut tr = public::create_tx_record_from_standard_tx(
                         &tx, 
                         txh, 
                         &receipt, 
@@ -89,6 +88,7 @@ pub async fn run_blocks(
                         args, 
                         input_selector
                     );
+                    tr.failure = revert::resolve_failure(&provider, &tx, receipt.as_ref()).await;
                     
                     if let Some(a) = &actions { 
                         a.on_tx(&tr); 
@@ -133,15 +133,16 @@ pub async fn run_blocks(
         
         // 选择批量处理方式
         let use_smart_grouping = logs.len() > 50; // 如果日志太多，使用智能分组
+        let sigs = abi::sigs();
         
         if use_smart_grouping {
             println!("Using smart block-grouped processing for {} logs", logs.len());
-            if let Err(e) = public::process_logs_by_blocks(logs, &provider, &events, &funcs, &actions).await {
+            if let Err(e) = public::process_logs_by_blocks(logs, &provider, &sigs.events, &sigs.funcs, &actions).await {
                 warn!("Smart batch processing error for blocks {}-{}: {}", num, batch_end, e);
             }
         } else {
             println!("Using simple batch processing for {} logs", logs.len());
-            if let Err(e) = public::process_logs_batch(logs, &provider, &events, &funcs, &actions).await {
+            if let Err(e) = public::process_logs_batch(logs, &provider, &sigs.events, &sigs.funcs, &actions).await {
                 warn!("Batch processing error for blocks {}-{}: {}", num, batch_end, e);
             }
         }
@@ -1,4 +1,6 @@
 pub mod cache;
+pub mod creations;
 pub mod historical;
 pub mod public;
 pub mod realtime;
+pub mod revert;
@@ -1,6 +1,8 @@
 use crate::{
     abi,
+    abi_registry,
     actions::{ActionSet, EventRecord, TxRecord},
+    runtime::revert,
 };
 use alloy_primitives::{hex, B256, U256, Address};
 use alloy_rpc_types_eth::{Transaction, TransactionReceipt, TransactionTrait};
@@ -18,7 +20,7 @@ pub fn decode_log_event(
 ) -> (Option<String>, Vec<crate::abi::DecodedField>) {
     let topic0 = log.topic0().cloned().unwrap_or(B256::ZERO);
     let topic0_hex = format!("0x{}", hex::encode(topic0));
-    if let Some((name, fields)) = abi::try_decode_event(&topic0_hex, log.topics(), log.data().data.as_ref(), events) {
+    if let Some((name, fields)) = abi_registry::try_decode_event(log.address(), &topic0_hex, log.topics(), log.data().data.as_ref(), events) {
         (Some(name), fields)
     } else {
         (None, vec![])
@@ -63,13 +65,14 @@ pub fn process_log(
 
 /// 公共交易解码函数
 pub fn decode_transaction_function(
+    to: Option<Address>,
     input: &[u8],
     funcs: &abi::FuncSigMap,
-) -> (Option<String>, Vec<crate::abi::DecodedValue>, Option<[u8; 4]>) {
+) -> (Option<String>, Vec<crate::abi::DecodedField>, Option<[u8; 4]>) {
     if input.len() >= 4 {
         let sel = &input[0..4];
         let sel_hex = format!("0x{}", hex::encode(sel));
-        let (fname, args) = if let Some((f, a)) = abi::try_decode_function(&sel_hex, input, funcs) {
+        let (fname, args) = if let Some((f, a)) = abi_registry::try_decode_function(to, &sel_hex, input, funcs) {
             (Some(f), a)
         } else {
             (None, vec![])
@@ -130,7 +133,7 @@ pub fn create_tx_record_from_standard_tx(
     tx_hash: B256,
     receipt: &Option<TransactionReceipt>,
     func_name: Option<String>,
-    func_args: Vec<crate::abi::DecodedValue>,
+    func_args: Vec<crate::abi::DecodedField>,
     input_selector: Option<[u8; 4]>,
 ) -> TxRecord {
     let (
@@ -164,6 +167,7 @@ pub fn create_tx_record_from_standard_tx(
         tx_index,
         contract_address,
         receipt_logs,
+        failure: None,
     }
 }
 
@@ -265,10 +269,10 @@ pub async fn process_logs_batch(
         if let Some(tx_hash) = log.transaction_hash {
             if let Some(tx) = tx_cache.get(&tx_hash) {
                 let input = tx.input().as_ref();
-                let (fname, args, input_selector) = decode_transaction_function(input, funcs);
+                let (fname, args, input_selector) = decode_transaction_function(tx.to(), input, funcs);
                 let receipt = receipt_cache.get(&tx_hash);
                 
-                let tr = create_tx_record_from_standard_tx(
+                let mut tr = create_tx_record_from_standard_tx(
                     tx, 
                     tx_hash, 
                     &receipt.cloned(), 
@@ -276,6 +280,7 @@ pub async fn process_logs_batch(
                     args, 
                     input_selector
                 );
+                tr.failure = revert::resolve_failure(provider, tx, receipt).await;
                 
                 if let Some(a) = actions {
                     a.on_tx(&tr);
@@ -38,7 +38,6 @@ async fn run_events_subscribe(
     actions: Option<Arc<ActionSet>>,
 ) -> Result<()> {
     info!("Subscribing to logs via eth_subscribe");
-    let events = abi::load_event_sigs_default().unwrap_or_default();
     let filter = Filter::new().address(addrs.clone());
     throttle::acquire().await;
     let mut last_seen: u64 = provider.get_block_number().await?;
@@ -50,7 +49,7 @@ async fn run_events_subscribe(
         let sub = provider.subscribe_logs(&filter).await?;
         let mut stream = sub.into_stream();
         while let Some(v) = stream.next().await {
-            let rec = public::process_log(&v, &events, &actions);
+            let rec = public::process_log(&v, &abi::sigs().events, &actions);
             last_seen = rec.block_number.unwrap_or(last_seen);
         }
         warn!("log subscription ended; attempting backfill and resubscribe");
@@ -82,7 +81,6 @@ async fn run_events_poll(
     actions: Option<Arc<ActionSet>>,
 ) -> Result<()> {
     info!("Polling for new logs via latest block");
-    let events = abi::load_event_sigs_default().unwrap_or_default();
     throttle::acquire().await;
     let mut last = provider.get_block_number().await?;
     loop {
@@ -92,8 +90,9 @@ async fn run_events_poll(
             let filter = Filter::new().address(addrs.clone()).from_block(last + 1).to_block(cur);
             throttle::acquire().await;
             let logs = provider.get_logs(&filter).await?;
+            let sigs = abi::sigs();
             for v in logs {
-                public::process_log(&v, &events, &actions);
+                public::process_log(&v, &sigs.events, &actions);
             }
             last = cur;
         }
@@ -118,6 +117,7 @@ pub async fn run_blocks(
 pub async fn run_contract_deployments(
     provider: RootProvider<BoxTransport>,
     actions: Option<Arc<ActionSet>>,
+    trace_creations: bool,
 ) -> Result<()> {
     info!("Starting contract deployment monitoring...");
     let mut last_seen = provider.get_block_number().await?;
@@ -125,14 +125,14 @@ pub async fn run_contract_deployments(
     const MAX_BACKOFF: u64 = 30;
     
     loop {
-        match run_deployments_subscribe(provider.clone(), actions.clone(), last_seen).await {
+        match run_deployments_subscribe(provider.clone(), actions.clone(), last_seen, trace_creations).await {
             Ok(new_last_seen) => {
                 last_seen = new_last_seen;
                 backoff = 1; // 重置退避
             }
             Err(e) => {
                 warn!("deployment subscription failed: {e}; fallback to polling");
-                last_seen = run_deployments_poll(provider.clone(), actions.clone(), last_seen).await?;
+                last_seen = run_deployments_poll(provider.clone(), actions.clone(), last_seen, trace_creations).await?;
                 backoff = 1;
             }
         }
@@ -146,6 +146,7 @@ async fn run_deployments_subscribe(
     provider: RootProvider<BoxTransport>,
     actions: Option<Arc<ActionSet>>,
     mut last_seen: u64,
+    trace_creations: bool,
 ) -> Result<u64> {
     let sub = provider.subscribe_blocks().await?;
     let mut stream = sub.into_stream();
@@ -161,6 +162,7 @@ async fn run_deployments_subscribe(
             &actions,
             false, // process_events
             true,  // process_deployments
+            trace_creations,
         ).await {
             warn!("Error processing deployments for block {}: {}", n, e);
         }
@@ -175,6 +177,7 @@ async fn run_deployments_poll(
     provider: RootProvider<BoxTransport>,
     actions: Option<Arc<ActionSet>>,
     mut last_seen: u64,
+    trace_creations: bool,
 ) -> Result<u64> {
     loop {
         throttle::acquire().await;
@@ -190,6 +193,7 @@ async fn run_deployments_poll(
                     &actions,
                     false, // process_events
                     true,  // process_deployments
+                    trace_creations,
                 ).await {
                     warn!("Error processing deployments for block {}: {}", n, e);
                 }
@@ -207,7 +211,6 @@ pub async fn run_pending_transactions(
     actions: Option<Arc<ActionSet>>,
     hashes_only: bool,
 ) -> Result<()> {
-    let funcs = abi::load_func_sigs("./data/func_sigs.json").unwrap_or_default();
     // Try full pending tx subscription first unless hashes_only
     if !hashes_only {
         throttle::acquire().await;
@@ -226,7 +229,7 @@ pub async fn run_pending_transactions(
                     }
                 }
                 let input = tx.input().as_ref();
-                let (fname, args, input_selector) = public::decode_transaction_function(input, &funcs);
+                let (fname, args, input_selector) = public::decode_transaction_function(tx.to(), input, &abi::sigs().funcs);
                 let tr = TxRecord {
                     hash: tx.tx_hash(),
                     from: Some(tx.from()),
@@ -245,6 +248,7 @@ pub async fn run_pending_transactions(
                     tx_index: None,
                     contract_address: None,
                     receipt_logs: None,
+                    failure: None,
                 };
                 if let Some(a) = &actions {
                     a.on_tx(&tr);
@@ -272,7 +276,7 @@ pub async fn run_pending_transactions(
                 }
             }
             let input = tx.input().as_ref();
-            let (fname, args, input_selector) = public::decode_transaction_function(input, &funcs);
+            let (fname, args, input_selector) = public::decode_transaction_function(tx.to(), input, &abi::sigs().funcs);
             let tr = TxRecord {
                 hash: h,
                 from: Some(tx.from),
@@ -291,6 +295,7 @@ pub async fn run_pending_transactions(
                 tx_index: None,
                 contract_address: None,
                 receipt_logs: None,
+                failure: None,
             };
             if let Some(a) = &actions {
                 a.on_tx(&tr);
@@ -325,6 +330,7 @@ async fn run_blocks_subscribe(
                 &actions,
                 true,  // process_events
                 false, // process_deployments (在这个函数中不处理合约创建)
+                false, // trace_creations
             ).await {
                 warn!("Error processing block {}: {}", n, e);
             }
@@ -358,7 +364,6 @@ async fn run_blocks_poll(
     actions: Option<Arc<ActionSet>>,
 ) -> Result<()> {
     info!("Polling new heads");
-    let events = abi::load_event_sigs("./data/event_sigs.json").unwrap_or_default();
     throttle::acquire().await;
     let mut last = provider.get_block_number().await?;
     loop {
@@ -373,8 +378,9 @@ async fn run_blocks_poll(
                 let filter = Filter::new().address(addrs.clone()).from_block(n).to_block(n);
                 throttle::acquire().await;
                 let logs = provider.get_logs(&filter).await?;
+                let sigs = abi::sigs();
                 for v in logs {
-                    public::process_log(&v, &events, &actions);
+                    public::process_log(&v, &sigs.events, &actions);
                 }
             }
             last = cur;
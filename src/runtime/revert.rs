@@ -0,0 +1,96 @@
+use crate::{
+    abi,
+    actions::TxFailure,
+    throttle,
+};
+use alloy_primitives::B256;
+use alloy_provider::{Provider, RootProvider, ext::DebugApi};
+use alloy_rpc_types::trace::geth::{
+    CallConfig, GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingOptions, GethTrace,
+};
+use alloy_rpc_types_eth::{BlockId, Transaction, TransactionReceipt};
+use alloy_transport::BoxTransport;
+use tracing::debug;
+
+/// 回执显示失败（status == 0）时重放交易取回 revert 数据并解码。
+/// 成功交易或没有回执时返回 `None`；两种重放方式都拿不到数据时 `source` 为 `none`。
+pub async fn resolve_failure(
+    provider: &RootProvider<BoxTransport>,
+    tx: &Transaction,
+    receipt: Option<&TransactionReceipt>,
+) -> Option<TxFailure> {
+    let receipt = receipt?;
+    if receipt.status() {
+        return None;
+    }
+
+    let mut found = None;
+    if let Some(block) = receipt.block_number {
+        found = call_at_parent(provider, tx, block)
+            .await
+            .map(|d| (d, "eth_call"));
+    }
+    if found.is_none() {
+        found = trace_revert_data(provider, receipt.transaction_hash)
+            .await
+            .map(|d| (d, "debug_traceTransaction"));
+    }
+    let (revert_data, source) = found.unwrap_or((Vec::new(), "none"));
+    let reason = abi::decode_revert(&revert_data, &abi::sigs().errors);
+    Some(TxFailure {
+        revert_data,
+        reason,
+        source,
+    })
+}
+
+/// 在父区块状态上 `eth_call` 重放交易。
+/// 同区块内排在前面的交易不会被重放，因此在父区块上成功执行时视为无法复现。
+async fn call_at_parent(
+    provider: &RootProvider<BoxTransport>,
+    tx: &Transaction,
+    block: u64,
+) -> Option<Vec<u8>> {
+    let parent = block.checked_sub(1)?;
+    let mut req = tx.clone().into_request();
+    // 费用与 nonce 在父区块上可能不再有效，只保留调用本身
+    req.nonce = None;
+    req.gas_price = None;
+    req.max_fee_per_gas = None;
+    req.max_priority_fee_per_gas = None;
+    req.max_fee_per_blob_gas = None;
+    throttle::acquire().await;
+    match provider.call(&req).block(BlockId::number(parent)).await {
+        Ok(_) => None,
+        Err(e) => {
+            let data = e.as_error_resp().and_then(|p| p.as_revert_data());
+            if data.is_none() {
+                debug!("eth_call replay of {} failed: {}", tx.inner.tx_hash(), e);
+            }
+            data.map(|d| d.to_vec())
+        }
+    }
+}
+
+/// 使用 callTracer 只取顶层调用的 output（失败时即 revert 数据）
+async fn trace_revert_data(
+    provider: &RootProvider<BoxTransport>,
+    tx_hash: B256,
+) -> Option<Vec<u8>> {
+    let options = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugTracerType::BuiltInTracer(
+            GethDebugBuiltInTracerType::CallTracer,
+        ))
+        .with_call_config(CallConfig::default().only_top_call());
+    throttle::acquire().await;
+    match provider.debug_trace_transaction(tx_hash, options).await {
+        Ok(GethTrace::CallTracer(frame)) if frame.error.is_some() => {
+            Some(frame.output.map(|o| o.to_vec()).unwrap_or_default())
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!("debug_traceTransaction {} failed: {}", tx_hash, e);
+            None
+        }
+    }
+}
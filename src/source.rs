@@ -12,14 +12,14 @@ use alloy::{
             common::TraceResult,
             geth::{
                 AccountState, CallConfig, CallFrame, DefaultFrame, DiffMode,
-                GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingOptions,
-                GethTrace, PreStateConfig, PreStateFrame,
+                GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions,
+                GethDebugTracingOptions, GethTrace, PreStateConfig, PreStateFrame,
             },
             parity::{
                 LocalizedTransactionTrace, TraceResults, TraceResultsWithTransactionHash, TraceType,
             },
         },
-        Block, BlockTransactions, BlockTransactionsKind, Filter, Log, Transaction,
+        Block, BlockId, BlockTransactions, BlockTransactionsKind, Filter, Log, Transaction,
         TransactionInput, TransactionReceipt, TransactionRequest,
     },
     transports::{http::reqwest::Url, BoxTransport, RpcError, TransportErrorKind},
@@ -150,6 +150,20 @@ impl Source {
         Ok(source)
     }
 
+    /// wrap an already connected provider (ws or http), chain_id is informational only
+    pub fn from_provider(provider: RootProvider<BoxTransport>, chain_id: u64) -> Source {
+        Source {
+            provider,
+            chain_id,
+            inner_request_size: DEFAULT_INNER_REQUEST_SIZE,
+            max_concurrent_chunks: Some(DEFAULT_MAX_CONCURRENT_CHUNKS),
+            rpc_url: String::new(),
+            labels: SourceLabels::default(),
+            rate_limiter: None.into(),
+            semaphore: None.into(),
+        }
+    }
+
     // /// set rate limit
     // pub fn rate_limit(mut self, _requests_per_second: u64) -> Source {
     //     todo!();
@@ -803,6 +817,73 @@ impl Source {
         Ok((block, txs, diffs))
     }
 
+    /// get geth debug prestate diff of a simulated call (debug_traceCall)
+    pub async fn geth_debug_trace_call_diff(
+        &self,
+        transaction: TransactionRequest,
+        block_number: Option<BlockNumber>,
+    ) -> Result<DiffMode> {
+        let tracer = GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::PreStateTracer);
+        let options = GethDebugTracingOptions::default()
+            .with_prestate_config(PreStateConfig {
+                diff_mode: Some(true),
+                disable_code: None,
+                disable_storage: None,
+            })
+            .with_tracer(tracer);
+        let block = block_number.map(BlockId::number).unwrap_or_else(BlockId::latest);
+        let trace = {
+            let _permit = self.permit_request().await;
+            Self::map_err(
+                self.provider
+                    .debug_trace_call(
+                        transaction,
+                        block,
+                        GethDebugTracingCallOptions::new(options),
+                    )
+                    .await,
+            )?
+        };
+        match trace {
+            GethTrace::PreStateTracer(PreStateFrame::Diff(diff)) => Ok(diff),
+            GethTrace::JS(serde_json::Value::Object(map)) => parse_geth_diff_object(map),
+            _ => Err(CollectError::CollectError("invalid trace result".to_string())),
+        }
+    }
+
+    /// get the top-level error (e.g. "execution reverted") of a simulated call via callTracer;
+    /// `None` when the call succeeds
+    pub async fn geth_debug_trace_call_error(
+        &self,
+        transaction: TransactionRequest,
+        block_number: Option<BlockNumber>,
+    ) -> Result<Option<String>> {
+        let tracer = GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer);
+        let options = GethDebugTracingOptions::default()
+            .with_call_config(CallConfig::default().only_top_call())
+            .with_tracer(tracer);
+        let block = block_number.map(BlockId::number).unwrap_or_else(BlockId::latest);
+        let trace = {
+            let _permit = self.permit_request().await;
+            Self::map_err(
+                self.provider
+                    .debug_trace_call(
+                        transaction,
+                        block,
+                        GethDebugTracingCallOptions::new(options),
+                    )
+                    .await,
+            )?
+        };
+        match trace {
+            GethTrace::CallTracer(frame) => Ok(frame.error),
+            GethTrace::JS(serde_json::Value::Object(map)) => {
+                Ok(map.get("error").and_then(|e| e.as_str()).map(|e| e.to_string()))
+            }
+            _ => Err(CollectError::CollectError("invalid trace result".to_string())),
+        }
+    }
+
     /// get geth debug transaction traces
     pub async fn geth_debug_trace_transaction(
         &self,
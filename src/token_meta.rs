@@ -0,0 +1,387 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::persist;
+use crate::throttle;
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types_eth::{TransactionRequest, transaction::TransactionInput};
+use alloy_transport::BoxTransport;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::{debug, warn};
+
+// ERC-20 代币元数据服务
+// - 通过 eth_call 读取 name / symbol / decimals / totalSupply，所有格式化代币数量的 Action 共用
+// - 兼容返回 bytes32 的老代币（如 MKR）；调用 revert 或无返回值视为未实现，记为缺失
+// - 结果缓存在 <cache-dir>/<chain_id>.json；传输错误或非 revert 的 RPC 错误不缓存，下次重新查询
+// - totalSupply 为首次解析时的快照
+
+static SERVICE: OnceCell<Arc<TokenMetaService>> = OnceCell::new();
+
+const NAME_SELECTOR: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+const TOTAL_SUPPLY_SELECTOR: [u8; 4] = [0x18, 0x16, 0x0d, 0xdd];
+
+/// 配置文件中的 "token-metadata" 段
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenMetaConfig {
+    /// 是否持久化到磁盘；关闭时仍在内存中缓存
+    #[serde(default = "default_persist")]
+    pub persist: bool,
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+}
+
+impl Default for TokenMetaConfig {
+    fn default() -> Self {
+        Self {
+            persist: default_persist(),
+            cache_dir: default_cache_dir(),
+        }
+    }
+}
+
+fn default_persist() -> bool {
+    true
+}
+
+fn default_cache_dir() -> String {
+    "./data/token_cache".to_string()
+}
+
+/// 单个代币的元数据，未实现或解析失败的字段为 `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<U256>,
+}
+
+impl TokenMeta {
+    /// 显示用符号，缺失时为 `TKN`
+    pub fn symbol_or_default(&self) -> &str {
+        self.symbol.as_deref().unwrap_or("TKN")
+    }
+
+    /// 按 decimals 格式化原始数量；decimals 缺失时使用 `default_decimals`
+    pub fn format_amount(&self, raw: U256, default_decimals: u8) -> String {
+        format_units(raw, self.decimals.unwrap_or(default_decimals))
+    }
+}
+
+/// 单次 eth_call 的结果
+enum CallResult {
+    Data(Vec<u8>),
+    /// 合约 revert 或没有返回值：视为未实现
+    Missing,
+    /// 传输层错误或非 revert 的 RPC 错误：结果不可信，不缓存
+    Transient,
+}
+
+pub struct TokenMetaService {
+    provider: RootProvider<BoxTransport>,
+    /// 持久化文件；`None` 表示只在内存中缓存
+    path: Option<PathBuf>,
+    entries: RwLock<HashMap<Address, Arc<TokenMeta>>>,
+    /// 正在解析的代币，避免并发事件重复查询同一个代币
+    pending: Mutex<HashMap<Address, Arc<tokio::sync::OnceCell<Arc<TokenMeta>>>>>,
+    /// 串行化缓存文件写入
+    write_lock: Mutex<()>,
+}
+
+impl TokenMetaService {
+    /// 只在内存中缓存的服务
+    pub fn new(provider: RootProvider<BoxTransport>) -> Self {
+        Self {
+            provider,
+            path: None,
+            entries: RwLock::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// 使用 `<cache_dir>/<chain_id>.json` 持久化，并载入已有缓存
+    pub fn with_cache_file(mut self, cache_dir: &str, chain_id: u64) -> Self {
+        let path = Path::new(cache_dir).join(format!("{}.json", chain_id));
+        let loaded = load_cache_file(&path);
+        debug!("[token] cache={} entries={}", path.display(), loaded.len());
+        *self.entries.get_mut().unwrap() =
+            loaded.into_iter().map(|(k, v)| (k, Arc::new(v))).collect();
+        self.path = Some(path);
+        self
+    }
+
+    /// 已缓存的元数据，不发起查询
+    pub fn cached(&self, token: Address) -> Option<Arc<TokenMeta>> {
+        self.entries.read().unwrap().get(&token).cloned()
+    }
+
+    /// 获取代币元数据，未缓存时通过 eth_call 解析
+    pub async fn get(&self, token: Address) -> Arc<TokenMeta> {
+        if let Some(m) = self.cached(token) {
+            return m;
+        }
+        let cell = self
+            .pending
+            .lock()
+            .unwrap()
+            .entry(token)
+            .or_default()
+            .clone();
+        let meta = cell
+            .get_or_init(|| async {
+                let (meta, complete) = self.resolve(token).await;
+                let meta = Arc::new(meta);
+                if complete {
+                    self.entries.write().unwrap().insert(token, meta.clone());
+                    self.persist();
+                }
+                meta
+            })
+            .await
+            .clone();
+        self.pending.lock().unwrap().remove(&token);
+        meta
+    }
+
+    /// 返回解析结果以及是否所有调用都得到了确定的答复
+    async fn resolve(&self, token: Address) -> (TokenMeta, bool) {
+        let mut complete = true;
+        let mut call = async |selector: &[u8; 4]| match self.call(token, selector).await {
+            CallResult::Data(d) => Some(d),
+            CallResult::Missing => None,
+            CallResult::Transient => {
+                complete = false;
+                None
+            }
+        };
+        let name = call(&NAME_SELECTOR)
+            .await
+            .and_then(|d| decode_string_like(&d));
+        let symbol = call(&SYMBOL_SELECTOR)
+            .await
+            .and_then(|d| decode_string_like(&d));
+        let decimals = call(&DECIMALS_SELECTOR)
+            .await
+            .and_then(|d| decode_decimals(&d));
+        let total_supply = call(&TOTAL_SUPPLY_SELECTOR)
+            .await
+            .and_then(|d| decode_uint(&d));
+        let meta = TokenMeta {
+            name,
+            symbol,
+            decimals,
+            total_supply,
+        };
+        debug!(
+            "[token] resolved {} {:?} complete={}",
+            token, meta, complete
+        );
+        (meta, complete)
+    }
+
+    async fn call(&self, token: Address, selector: &[u8; 4]) -> CallResult {
+        let tx = TransactionRequest::default()
+            .to(token)
+            .input(TransactionInput::new(Bytes::copy_from_slice(selector)));
+        throttle::acquire().await;
+        match self.provider.call(&tx).await {
+            Ok(out) if out.is_empty() => CallResult::Missing,
+            Ok(out) => CallResult::Data(out.to_vec()),
+            Err(e)
+                if e.as_error_resp()
+                    .is_some_and(|r| is_execution_reverted(r.code, &r.message)) =>
+            {
+                CallResult::Missing
+            }
+            Err(e) => {
+                debug!("[token] eth_call {} failed: {}", token, e);
+                CallResult::Transient
+            }
+        }
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let _guard = self.write_lock.lock().unwrap();
+        let snapshot: HashMap<String, TokenMeta> = self
+            .entries
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (format!("{:#x}", k), (**v).clone()))
+            .collect();
+        if let Err(e) = persist::write_json_atomic(path, &snapshot) {
+            warn!("[token] write cache {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// 节点对 eth_call 的 revert 答复：错误码 3，或消息包含 "execution reverted"。
+/// 其它 JSON-RPC 错误（限流、超时、节点内部错误等）不代表合约未实现
+fn is_execution_reverted(code: i64, message: &str) -> bool {
+    code == 3 || message.to_ascii_lowercase().contains("execution reverted")
+}
+
+fn load_cache_file(path: &Path) -> HashMap<Address, TokenMeta> {
+    persist::read_json(path)
+        .unwrap_or_else(|e| {
+            warn!("[token] ignore unreadable cache {}: {}", path.display(), e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 按配置初始化全局服务；启动时（构建 Action 之前）调用，重复调用无效
+pub async fn init_from_config(cfg: &Config, provider: &RootProvider<BoxTransport>) -> Result<()> {
+    if SERVICE.get().is_some() {
+        return Ok(());
+    }
+    let c = cfg.token_metadata.clone().unwrap_or_default();
+    let mut svc = TokenMetaService::new(provider.clone());
+    if c.persist {
+        throttle::acquire().await;
+        let chain_id = provider.get_chain_id().await?;
+        svc = svc.with_cache_file(&c.cache_dir, chain_id);
+    }
+    let _ = SERVICE.set(Arc::new(svc));
+    Ok(())
+}
+
+pub fn global() -> Option<&'static Arc<TokenMetaService>> {
+    SERVICE.get()
+}
+
+/// 全局服务；必须先调用 [`init_from_config`]，否则返回配置错误（不会静默退回无持久化的默认服务）
+pub fn shared() -> Result<Arc<TokenMetaService>> {
+    SERVICE.get().cloned().ok_or_else(|| {
+        AppError::Config("token metadata service is not initialized; call token_meta::init_from_config at startup".to_string())
+    })
+}
+
+/// 解码 `string` 返回值，失败时按老代币的 `bytes32` 解码
+pub fn decode_string_like(data: &[u8]) -> Option<String> {
+    decode_string_return(data).or_else(|| decode_bytes32_string(data))
+}
+
+fn decode_string_return(data: &[u8]) -> Option<String> {
+    if data.len() < 64 {
+        return None;
+    }
+    let offset = decode_uint(&data[..32])?;
+    if offset != U256::from(32) {
+        return None;
+    }
+    let len = decode_uint(&data[32..64])?;
+    let len = usize::try_from(len).ok()?;
+    let raw = data.get(64..64usize.checked_add(len)?)?;
+    let s = String::from_utf8_lossy(raw)
+        .trim_end_matches('\0')
+        .to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+fn decode_bytes32_string(data: &[u8]) -> Option<String> {
+    let word = data.get(..32)?;
+    let end = word.iter().position(|&b| b == 0).unwrap_or(32);
+    if end == 0 || word[end..].iter().any(|&b| b != 0) {
+        return None;
+    }
+    std::str::from_utf8(&word[..end]).ok().map(str::to_string)
+}
+
+fn decode_uint(data: &[u8]) -> Option<U256> {
+    let word: [u8; 32] = data.get(..32)?.try_into().ok()?;
+    Some(U256::from_be_bytes(word))
+}
+
+fn decode_decimals(data: &[u8]) -> Option<u8> {
+    u8::try_from(decode_uint(data)?).ok()
+}
+
+/// 原始数量按 decimals 转为十进制字符串，保留全部小数位
+pub fn format_units(v: U256, decimals: u8) -> String {
+    if decimals == 0 {
+        return v.to_string();
+    }
+    let denom = U256::from(10u8).pow(U256::from(decimals));
+    let frac = (v % denom).to_string();
+    format!(
+        "{}.{}{}",
+        v / denom,
+        "0".repeat(decimals as usize - frac.len()),
+        frac
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, hex};
+
+    #[test]
+    fn only_reverts_count_as_missing() {
+        assert!(is_execution_reverted(3, "execution reverted"));
+        assert!(is_execution_reverted(-32000, "Execution reverted: no symbol"));
+        assert!(!is_execution_reverted(-32005, "rate limit exceeded"));
+        assert!(!is_execution_reverted(-32000, "header not found"));
+        assert!(!is_execution_reverted(-32603, "internal error"));
+    }
+
+    #[test]
+    fn decodes_string_and_bytes32_returns() {
+        // symbol() -> string "USDT"
+        let abi_string = hex!(
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000004"
+            "5553445400000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(decode_string_like(&abi_string).as_deref(), Some("USDT"));
+        // MKR 风格的 bytes32 返回
+        let bytes32 = hex!("4d4b520000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(decode_string_like(&bytes32).as_deref(), Some("MKR"));
+        assert_eq!(decode_string_like(&[0u8; 32]), None);
+        assert_eq!(decode_string_like(&[0x4d, 0x4b]), None);
+
+        let mut dec = [0u8; 32];
+        dec[31] = 6;
+        assert_eq!(decode_decimals(&dec), Some(6));
+        dec[30] = 1;
+        assert_eq!(decode_decimals(&dec), None);
+
+        assert_eq!(format_units(U256::from(1_500_000u64), 6), "1.500000");
+        assert_eq!(format_units(U256::from(5u64), 3), "0.005");
+        assert_eq!(format_units(U256::from(42u64), 0), "42");
+    }
+
+    #[test]
+    fn cache_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("evm-track-token-{}", std::process::id()));
+        let path = dir.join("56.json");
+        let token = address!("55d398326f99059fF775485246999027B3197955");
+        let meta = TokenMeta {
+            name: Some("Tether USD".into()),
+            symbol: Some("USDT".into()),
+            decimals: Some(18),
+            total_supply: Some(U256::from(10u64).pow(U256::from(27))),
+        };
+        let entries = HashMap::from([(format!("{:#x}", token), meta.clone())]);
+        persist::write_json_atomic(&path, &entries).unwrap();
+        let loaded = load_cache_file(&path);
+        assert_eq!(loaded.get(&token), Some(&meta));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}